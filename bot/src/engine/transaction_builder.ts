@@ -8,7 +8,7 @@ import { logger } from '../utils/logger';
 import { ArbitrageOpportunity } from '../scanner';
 import config from '../config/config';
 import { getStellarClient } from '../utils/stellar_client';
-import { Contract, TransactionBuilder as StellarTxBuilder, Operation, nativeToScVal, Address } from '@stellar/stellar-sdk';
+import { Contract, TransactionBuilder as StellarTxBuilder, Operation, nativeToScVal, Address, xdr } from '@stellar/stellar-sdk';
 
 export interface FlashLoanTransaction {
   opportunity: ArbitrageOpportunity;
@@ -20,6 +20,33 @@ export interface FlashLoanTransaction {
   xdr?: string; // Built transaction XDR for signing
 }
 
+/**
+ * Encode a DEX name as the contract's `DexType` enum (a unit variant is a
+ * vector holding the variant's symbol)
+ */
+function dexTypeToScVal(dexName: string): xdr.ScVal {
+  return xdr.ScVal.scvVec([xdr.ScVal.scvSymbol(dexName)]);
+}
+
+/**
+ * Encode the contract's `ExecutionOptions` struct (a map keyed by field
+ * symbols in sorted order; an unset `Option` is void)
+ */
+function executionOptionsToScVal(deadline: number): xdr.ScVal {
+  const field = (name: string, val: xdr.ScVal) =>
+    new xdr.ScMapEntry({ key: xdr.ScVal.scvSymbol(name), val });
+  return xdr.ScVal.scvMap([
+    field('deadline', nativeToScVal(deadline, { type: 'u64' })),
+    field('expected_outputs', xdr.ScVal.scvVec([])), // Quoted by the contract before the first swap
+    field('expected_reserves', xdr.ScVal.scvVec([])),
+    field('max_slippage_bps', nativeToScVal(config.trading.maxSlippageBps, { type: 'u32' })),
+    field('min_profit_bps', nativeToScVal(config.trading.minProfitBps, { type: 'u32' })),
+    field('not_before_ledger', xdr.ScVal.scvVoid()),
+    field('reserve_tolerance_bps', nativeToScVal(0, { type: 'u32' })),
+    field('valid_until_ledger', xdr.ScVal.scvVoid()),
+  ]);
+}
+
 export class TransactionBuilder {
   /**
    * Build a flash loan arbitrage transaction
@@ -42,16 +69,20 @@ export class TransactionBuilder {
       const sourceKey = userPublicKey || client.getPublicKey();
       const sourceAccount = await client.server.getAccount(sourceKey);
 
+      // The swaps must settle before the transaction times out
+      const deadline = Math.floor(Date.now() / 1000) + 30;
+
       // Build contract arguments
       const contractArgs = [
+        new Address(sourceKey).toScVal(), // initiator
         new Address(opp.poolA.tokenA.address).toScVal(), // token_borrow
         new Address(opp.poolA.tokenB.address).toScVal(), // token_intermediate  
-        nativeToScVal(opp.borrowAmount, { type: 'u128' }), // amount
-        nativeToScVal(opp.poolA.pool.dex, { type: 'u32' }), // dex_a_type
+        nativeToScVal(opp.borrowAmount, { type: 'i128' }), // amount
+        dexTypeToScVal(opp.poolA.pool.dexName), // dex_a_type
         new Address(opp.poolA.pool.poolAddress).toScVal(), // dex_a_pool
-        nativeToScVal(opp.poolB.pool.dex, { type: 'u32' }), // dex_b_type
+        dexTypeToScVal(opp.poolB.pool.dexName), // dex_b_type
         new Address(opp.poolB.pool.poolAddress).toScVal(), // dex_b_pool
-        nativeToScVal(config.trading.minProfitBps, { type: 'u32' }), // min_profit_bps
+        executionOptionsToScVal(deadline), // options
       ];
 
       // Build transaction
@@ -0,0 +1,111 @@
+use soroban_sdk::{Address, Env};
+use crate::arbitrage::ArbitrageRoute;
+use crate::errors::Error;
+
+const ALLOWED_POOL: &str = "allowed_pool";
+const ALLOWED_TOKEN: &str = "allowed_token";
+
+/// Whether the owner has allowed routes to trade through `pool`
+pub fn is_pool_allowed(env: &Env, pool: &Address) -> bool {
+    let key = (ALLOWED_POOL, pool);
+    env.storage().persistent().has(&key)
+}
+
+/// Add `pool` to or remove it from the allowlist (owner only)
+pub fn set_pool_allowed(env: &Env, pool: &Address, allowed: bool) {
+    let key = (ALLOWED_POOL, pool);
+    if allowed {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Whether the owner has allowed routes to hold `token`
+pub fn is_token_allowed(env: &Env, token: &Address) -> bool {
+    let key = (ALLOWED_TOKEN, token);
+    env.storage().persistent().has(&key)
+}
+
+/// Add `token` to or remove it from the allowlist (owner only)
+pub fn set_token_allowed(env: &Env, token: &Address, allowed: bool) {
+    let key = (ALLOWED_TOKEN, token);
+    if allowed {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Every token held and every pool traded by `route` must be allowlisted
+pub fn check_route(env: &Env, route: &ArbitrageRoute) -> Result<(), Error> {
+    if !is_token_allowed(env, &route.token_borrow) {
+        return Err(Error::TokenNotAllowed);
+    }
+
+    for leg in route.legs.iter() {
+        if !is_token_allowed(env, &leg.token_out) {
+            return Err(Error::TokenNotAllowed);
+        }
+        for split in leg.splits.iter() {
+            if !is_pool_allowed(env, &split.pool) {
+                return Err(Error::PoolNotAllowed);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{vec, Vec};
+    use crate::arbitrage::{PoolSplit, SwapLeg};
+    use crate::dex_interface::DexType;
+    use crate::FlashLoanExecutorContract;
+
+    #[test]
+    fn test_route_needs_allowed_tokens_and_pools() {
+        let env = Env::default();
+        let contract = env.register_contract(None, FlashLoanExecutorContract);
+        let usdc = Address::generate(&env);
+        let xlm = Address::generate(&env);
+        let pool_a = Address::generate(&env);
+        let pool_b = Address::generate(&env);
+
+        let swap = |token_in: &Address, token_out: &Address, pool: &Address| SwapLeg {
+            token_in: token_in.clone(),
+            token_out: token_out.clone(),
+            splits: vec![&env, PoolSplit { dex_type: DexType::Soroswap, pool: pool.clone(), fraction_bps: 10000 }],
+        };
+        let route = ArbitrageRoute {
+            route_id: 1,
+            legs: vec![&env, swap(&usdc, &xlm, &pool_a), swap(&xlm, &usdc, &pool_b)],
+            token_borrow: usdc.clone(),
+            amount: 1_000,
+            min_profit_bps: 0,
+            max_slippage_bps: 100,
+            expected_outputs: Vec::new(&env),
+            deadline: 0,
+        };
+
+        env.as_contract(&contract, || {
+            assert_eq!(check_route(&env, &route), Err(Error::TokenNotAllowed));
+
+            set_token_allowed(&env, &usdc, true);
+            set_token_allowed(&env, &xlm, true);
+            set_pool_allowed(&env, &pool_a, true);
+            assert_eq!(check_route(&env, &route), Err(Error::PoolNotAllowed));
+
+            set_pool_allowed(&env, &pool_b, true);
+            assert_eq!(check_route(&env, &route), Ok(()));
+
+            // Removing an entry takes effect immediately
+            set_token_allowed(&env, &xlm, false);
+            assert!(!is_token_allowed(&env, &xlm));
+            assert_eq!(check_route(&env, &route), Err(Error::TokenNotAllowed));
+        });
+    }
+}
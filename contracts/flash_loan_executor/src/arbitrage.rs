@@ -1,21 +1,310 @@
-use soroban_sdk::{Address, Env, Vec};
+use soroban_sdk::{
+    contracttype, xdr::{FromXdr, ToXdr}, Address, Bytes, Env, Map, Symbol, TryFromVal, Val, Vec,
+};
+use crate::allowlist;
 use crate::errors::Error;
 use crate::events;
-use crate::dex_interface::{DexType, execute_dex_swap};
-use crate::flash_loan::FlashLoanContext;
-use crate::security::{check_slippage, check_minimum_profit};
+use crate::dex_interface::{DexType, execute_dex_swap, quote_dex_swap};
+use crate::flash_loan::FlashLoanManager;
+use crate::flash_loan_provider::BorrowLeg;
+use crate::oracle;
+use crate::reserve_guard::{self, ReserveSnapshot};
+use crate::route_registry::{self, RouteCall, AD_HOC_ROUTE_ID};
+use crate::security::{check_deadline, check_ledger_window, check_slippage, check_minimum_profit};
+
+/// Most hops a single route may take
+pub const MAX_HOPS: u32 = 5;
+
+/// Route parameters carried in the flash loan `data` payload
+/// 
+/// The borrowed token comes from the loan itself; everything else needed to
+/// build an `ArbitrageRoute` travels XDR-encoded through the lender(s).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbitrageParams {
+    pub legs: Vec<BorrowLeg>, // Lenders backing the trade, outermost first
+    pub swaps: Vec<SwapLeg>, // Swap cycle starting and ending in the borrowed token
+    pub amount: Option<i128>, // Trade size, at most the loan; the whole loan if None
+    pub min_profit_bps: u32,
+    pub max_slippage_bps: u32,
+    pub expected_outputs: Vec<i128>, // Output of each leg, quoted before the loan; may be empty
+    pub soft_fail: bool, // Skip instead of reverting when the pre-trade quote misses min profit; the loan is already open, so its fee is still paid
+    pub not_before_ledger: Option<u32>, // First ledger the quote may execute in
+    pub valid_until_ledger: Option<u32>, // Last ledger the quote may execute in
+    pub deadline: Option<u64>, // Ledger timestamp the swaps must settle by
+    pub expected_reserves: Vec<ReserveSnapshot>, // Pools to check before trading, may be empty
+    pub reserve_tolerance_bps: u32, // Allowed drift of each snapshotted reserve
+}
+
+/// Thresholds, validity window and reserve guard of a vault-funded two-hop trade
+/// 
+/// A contract function takes at most ten arguments, so everything
+/// `execute_flash_loan_arbitrage` needs beyond the route itself travels here.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExecutionOptions {
+    pub min_profit_bps: u32,
+    pub max_slippage_bps: u32,
+    pub expected_outputs: Vec<i128>, // Output of each leg, quoted before the loan; may be empty
+    pub not_before_ledger: Option<u32>, // First ledger the quote may execute in
+    pub valid_until_ledger: Option<u32>, // Last ledger the quote may execute in
+    pub deadline: Option<u64>, // Ledger timestamp the swaps must settle by
+    pub expected_reserves: Vec<ReserveSnapshot>, // Pools to check before borrowing, may be empty
+    pub reserve_tolerance_bps: u32, // Allowed drift of each snapshotted reserve
+}
+
+/// Why a route was not executed
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SkipReason {
+    /// The route could not be quoted (bad route, pool or DEX)
+    QuoteFailed,
+    /// Quoted output does not cover principal plus flash fee
+    NoProfit,
+    /// Quoted profit is positive but below `min_profit_bps`
+    BelowMinProfit,
+}
+
+/// A route that was quoted but not traded
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SkippedRoute {
+    pub route_id: u32,
+    pub reason: SkipReason,
+    pub amount_in: i128,
+    pub leg_outputs: Vec<i128>, // Quoted output of every leg, empty if quoting failed
+    pub amount_out: i128,
+    pub flash_fee: i128,
+    pub expected_profit: i128, // Quoted net profit
+    pub min_profit: i128,
+}
+
+/// Whether a route traded or was skipped after its pre-trade quote
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RouteExecution {
+    Executed(ArbitrageResult),
+    Skipped(SkippedRoute),
+}
+
+impl RouteExecution {
+    /// Profit kept by the contract; a skipped route still pays its flash fee
+    pub fn net_profit(&self) -> i128 {
+        match self {
+            RouteExecution::Executed(result) => result.net_profit,
+            RouteExecution::Skipped(skipped) => -skipped.flash_fee,
+        }
+    }
+}
+
+/// How a batch treats routes that no longer look profitable
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BatchPolicy {
+    /// Any unprofitable route reverts the whole batch
+    AllOrNothing,
+    /// Unprofitable routes are skipped and the rest still run
+    SkipUnprofitable,
+}
+
+/// One independent opportunity inside a batch
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchRoute {
+    pub swaps: Vec<SwapLeg>,
+    pub amount: i128, // Trade size, at most the total borrowed
+    pub min_profit_bps: u32,
+    pub max_slippage_bps: u32,
+    pub expected_outputs: Vec<i128>, // Output of each leg, quoted before the loan; may be empty
+}
+
+/// Batch payload carried in the flash loan `data`
+/// 
+/// Routes run one after another on the same borrowed funds, so each may
+/// trade up to the full loan. The flash fee is shared pro rata by amount.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchParams {
+    pub legs: Vec<BorrowLeg>, // Lenders backing the batch, outermost first
+    pub routes: Vec<BatchRoute>,
+    pub policy: BatchPolicy,
+    pub not_before_ledger: Option<u32>,
+    pub valid_until_ledger: Option<u32>,
+    pub deadline: Option<u64>, // Ledger timestamp every route must settle by
+    pub expected_reserves: Vec<ReserveSnapshot>, // Checked once before the first route trades
+    pub reserve_tolerance_bps: u32,
+}
+
+/// Decoded flash loan `data`: a single route, a batch of routes or a
+/// call to a registered route
+pub enum FlashLoanPayload {
+    Single(ArbitrageParams),
+    Batch(BatchParams),
+    Registered(RouteCall),
+}
+
+impl FlashLoanPayload {
+    /// Decode `data` as `ArbitrageParams`, `BatchParams` or `RouteCall`
+    /// 
+    /// The type is picked from the encoded struct's field names: converting
+    /// a map into a struct with other fields traps instead of erroring, so
+    /// decoding each type in turn cannot be used as a fallback.
+    pub fn decode(env: &Env, data: &Bytes) -> Result<Self, Error> {
+        let fields = Map::<Symbol, Val>::from_xdr(env, data).map_err(|_| Error::InvalidRoute)?;
+        let value = fields.to_val();
+        
+        let payload = if fields.contains_key(Symbol::new(env, "routes")) {
+            BatchParams::try_from_val(env, &value).map(FlashLoanPayload::Batch)
+        } else if fields.contains_key(Symbol::new(env, "route_id")) {
+            RouteCall::try_from_val(env, &value).map(FlashLoanPayload::Registered)
+        } else {
+            ArbitrageParams::try_from_val(env, &value).map(FlashLoanPayload::Single)
+        };
+        payload.map_err(|_| Error::InvalidRoute)
+    }
+    
+    /// Whether the payload only runs a route the owner registered
+    pub fn is_registered(&self) -> bool {
+        matches!(self, FlashLoanPayload::Registered(_))
+    }
+    
+    /// Look up a registered route, returning its id and a plain payload
+    /// 
+    /// Done only by the deployment that executes the route, so relays need
+    /// not share its registry. Ad-hoc payloads get `AD_HOC_ROUTE_ID`.
+    pub fn resolve(self, env: &Env) -> Result<(u32, Self), Error> {
+        match self {
+            FlashLoanPayload::Registered(call) => {
+                let (route_id, params) = route_registry::resolve(env, call)?;
+                Ok((route_id, FlashLoanPayload::Single(params)))
+            }
+            payload => Ok((AD_HOC_ROUTE_ID, payload)),
+        }
+    }
+    
+    /// Reject the payload outside the ledger window it was quoted for, or
+    /// past its deadline
+    pub fn check_window(&self, env: &Env) -> Result<(), Error> {
+        match self {
+            FlashLoanPayload::Single(params) => {
+                check_ledger_window(env, params.not_before_ledger, params.valid_until_ledger)?;
+                check_deadline(env, params.deadline)
+            }
+            FlashLoanPayload::Batch(batch) => {
+                check_ledger_window(env, batch.not_before_ledger, batch.valid_until_ledger)?;
+                check_deadline(env, batch.deadline)
+            }
+            FlashLoanPayload::Registered(call) => {
+                check_ledger_window(
+                    env,
+                    call.overrides.not_before_ledger,
+                    call.overrides.valid_until_ledger,
+                )?;
+                check_deadline(env, call.overrides.deadline)
+            }
+        }
+    }
+    
+    /// Compare the payload's reserve snapshots with the live pools
+    /// 
+    /// A batch's snapshots may name pools of any of its routes. Registered
+    /// calls must be resolved first.
+    pub fn check_reserves(&self, env: &Env) -> Result<(), Error> {
+        match self {
+            FlashLoanPayload::Single(params) => reserve_guard::check_snapshots(
+                env,
+                &params.swaps,
+                &params.expected_reserves,
+                params.reserve_tolerance_bps,
+            ),
+            FlashLoanPayload::Batch(batch) => {
+                let mut legs: Vec<SwapLeg> = Vec::new(env);
+                for route in batch.routes.iter() {
+                    legs.append(&route.swaps);
+                }
+                reserve_guard::check_snapshots(
+                    env,
+                    &legs,
+                    &batch.expected_reserves,
+                    batch.reserve_tolerance_bps,
+                )
+            }
+            FlashLoanPayload::Registered(_) => Err(Error::InvalidRoute),
+        }
+    }
+    
+    /// Record the fee charged for leg `index` in the encoded payload
+    /// 
+    /// Relays call this before forwarding `data`, so the executor repays
+    /// each relay exactly what its lender charged.
+    pub fn record_relay_fee(env: &Env, data: &Bytes, index: u32, fee: i128) -> Result<Bytes, Error> {
+        let mut fields = Map::<Symbol, Val>::from_xdr(env, data).map_err(|_| Error::InvalidRoute)?;
+        let key = Symbol::new(env, "legs");
+        let value = fields.get(key.clone()).ok_or(Error::InvalidRoute)?;
+        let mut legs = Vec::<BorrowLeg>::try_from_val(env, &value).map_err(|_| Error::InvalidRoute)?;
+        
+        let mut leg = legs.get(index).ok_or(Error::InvalidRoute)?;
+        leg.charged_fee = Some(fee);
+        legs.set(index, leg);
+        
+        fields.set(key, legs.to_val());
+        Ok(fields.to_xdr(env))
+    }
+    
+    /// Lenders backing the payload, outermost first
+    pub fn legs(&self) -> &Vec<BorrowLeg> {
+        match self {
+            FlashLoanPayload::Single(params) => &params.legs,
+            FlashLoanPayload::Batch(batch) => &batch.legs,
+            FlashLoanPayload::Registered(call) => &call.legs,
+        }
+    }
+}
+
+/// Per-route outcomes and the profit the batch kept
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchResult {
+    pub outcomes: Vec<RouteExecution>,
+    pub total_profit: i128,
+}
+
+/// One hop of an arbitrage cycle
+/// 
+/// The leg's input can be split across several pools trading the same
+/// pair, which limits price impact when a single pool is shallow.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapLeg {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub splits: Vec<PoolSplit>, // Fractions must add up to 10000 bps
+}
+
+/// Share of a leg's input routed through one pool
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolSplit {
+    pub dex_type: DexType,
+    pub pool: Address,
+    pub fraction_bps: u32,
+}
 
 /// Arbitrage route configuration
-#[derive(Clone)]
+/// 
+/// A closed cycle of swaps: the first leg sells `token_borrow` and the last
+/// leg buys it back, each leg's output feeding the next leg's input.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ArbitrageRoute {
     pub route_id: u32,
-    pub dex_a: DexConfig,
-    pub dex_b: DexConfig,
+    pub legs: Vec<SwapLeg>,
     pub token_borrow: Address,
-    pub token_intermediate: Address,
     pub amount: i128,
     pub min_profit_bps: u32, // Minimum profit in basis points
-    pub max_slippage_bps: u32, // Maximum slippage in basis points
+    pub max_slippage_bps: u32, // Maximum slippage per leg in basis points
+    pub expected_outputs: Vec<i128>, // Output each leg is held to; quoted before the first swap if empty
+    pub deadline: u64, // Ledger timestamp DEX routers must settle by
 }
 
 /// DEX configuration for a swap leg
@@ -25,17 +314,70 @@ pub struct DexConfig {
     pub pool_address: Address,
 }
 
+impl DexConfig {
+    /// DEX a split trades on
+    pub fn from_split(split: &PoolSplit) -> Self {
+        DexConfig {
+            dex_type: split.dex_type,
+            pool_address: split.pool.clone(),
+        }
+    }
+}
+
 /// Arbitrage execution result
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ArbitrageResult {
     pub success: bool,
     pub amount_in: i128,
-    pub amount_after_swap1: i128,
-    pub amount_after_swap2: i128,
+    pub leg_outputs: Vec<i128>, // Amount received from each leg, in order
+    pub amount_out: i128,
     pub gross_profit: i128,
     pub net_profit: i128,
     pub fees_paid: i128,
 }
 
+/// Quote for one pool's share of a leg
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitReport {
+    pub dex_type: DexType,
+    pub pool: Address,
+    pub amount_in: i128,
+    pub amount_out: i128,
+    pub spot_amount_out: i128, // Output at the pre-trade price, after the DEX fee
+    pub dex_fee: i128, // In units of the leg's token_in
+    pub price_impact_bps: u32,
+    pub reserve_in: i128,
+    pub reserve_out: i128,
+}
+
+/// Quote for one leg of a route
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegReport {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_in: i128,
+    pub amount_out: i128,
+    pub dex_fee: i128, // In units of token_in
+    pub price_impact_bps: u32,
+    pub splits: Vec<SplitReport>,
+}
+
+/// Dry-run result of a route: what each leg is expected to return and why
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SimulationReport {
+    pub token_borrow: Address,
+    pub amount_in: i128,
+    pub legs: Vec<LegReport>,
+    pub amount_out: i128,
+    pub flash_loan_fee: i128,
+    pub gross_profit: i128,
+    pub net_profit: i128,
+}
+
 /// Arbitrage Executor
 /// 
 /// Executes multi-DEX arbitrage opportunities using flash loans
@@ -45,96 +387,50 @@ impl ArbitrageExecutor {
     /// Execute a complete arbitrage cycle
     /// 
     /// Flow:
-    /// 1. Borrow token_borrow from flash loan
-    /// 2. Swap token_borrow -> token_intermediate on DEX A
-    /// 3. Swap token_intermediate -> token_borrow on DEX B
+    /// 1. Borrow token_borrow from one or more flash loan legs
+    /// 2. Swap along each leg of the cycle in order
+    /// 3. Arrive back in token_borrow on the last leg
     /// 4. Repay flash loan + fee
     /// 5. Keep profit
+    /// 
+    /// `flash_fee` is the part of the flash loan fees this route must cover.
     pub fn execute_arbitrage(
         env: &Env,
         route: &ArbitrageRoute,
-        flash_loan_ctx: &FlashLoanContext,
+        flash_fee: i128,
     ) -> Result<ArbitrageResult, Error> {
         // Validate route
-        Self::validate_route(route)?;
+        Self::validate_route(env, route)?;
         
         let amount_start = route.amount;
         
-        // === STEP 1: Swap on DEX A ===
-        // token_borrow -> token_intermediate
-        let expected_output_1 = Self::calculate_expected_output(
-            env,
-            &route.dex_a,
-            &route.token_borrow,
-            &route.token_intermediate,
-            amount_start,
-        )?;
-        
-        let min_output_1 = Self::apply_slippage(expected_output_1, route.max_slippage_bps)?;
-        
-        let amount_after_swap1 = execute_dex_swap(
-            env,
-            route.dex_a.dex_type,
-            &route.dex_a.pool_address,
-            &route.token_borrow,
-            &route.token_intermediate,
-            amount_start,
-            min_output_1,
-        )?;
-        
-        // Validate slippage
-        check_slippage(expected_output_1, amount_after_swap1, route.max_slippage_bps)?;
-        
-        // Emit swap event
-        events::emit_swap_completed(
-            env,
-            route.dex_a.pool_address.clone(),
-            route.token_borrow.clone(),
-            route.token_intermediate.clone(),
-            amount_start,
-            amount_after_swap1,
-        );
-        
-        // === STEP 2: Swap on DEX B ===
-        // token_intermediate -> token_borrow
-        let expected_output_2 = Self::calculate_expected_output(
-            env,
-            &route.dex_b,
-            &route.token_intermediate,
-            &route.token_borrow,
-            amount_after_swap1,
-        )?;
-        
-        let min_output_2 = Self::apply_slippage(expected_output_2, route.max_slippage_bps)?;
-        
-        let amount_after_swap2 = execute_dex_swap(
-            env,
-            route.dex_b.dex_type,
-            &route.dex_b.pool_address,
-            &route.token_intermediate,
-            &route.token_borrow,
-            amount_after_swap1,
-            min_output_2,
-        )?;
-        
-        // Validate slippage
-        check_slippage(expected_output_2, amount_after_swap2, route.max_slippage_bps)?;
-        
-        // Emit swap event
-        events::emit_swap_completed(
-            env,
-            route.dex_b.pool_address.clone(),
-            route.token_intermediate.clone(),
-            route.token_borrow.clone(),
-            amount_after_swap1,
-            amount_after_swap2,
-        );
+        // === STEP 1: Swap along the cycle ===
+        // Legs are held to outputs fixed before the first swap, never to a
+        // quote of a pool that may already have been pushed
+        let expected_outputs = Self::expected_outputs(env, route)?;
+        let mut leg_outputs: Vec<i128> = Vec::new(env);
+        let mut amount = amount_start;
+        for (leg, expected_output) in route.legs.iter().zip(expected_outputs.iter()) {
+            amount = Self::execute_leg(
+                env,
+                &leg,
+                amount,
+                expected_output,
+                route.max_slippage_bps,
+                route.deadline,
+            )?;
+            leg_outputs.push_back(amount);
+        }
+        let amount_out = amount;
         
-        // === STEP 3: Calculate profit ===
-        let gross_profit = amount_after_swap2.checked_sub(amount_start)
+        // === STEP 2: Calculate profit ===
+        let gross_profit = amount_out.checked_sub(amount_start)
             .ok_or(Error::ArithmeticOverflow)?;
         
-        let net_profit = flash_loan_ctx.calculate_net_profit(amount_after_swap2);
+        // Net profit = final_amount - principal - flash loan fee
+        let net_profit = gross_profit.checked_sub(flash_fee)
+            .ok_or(Error::ArithmeticOverflow)?;
+        let fees_paid = flash_fee;
         
         // Calculate minimum profit threshold
         let min_profit = amount_start.checked_mul(route.min_profit_bps as i128)
@@ -151,59 +447,512 @@ impl ArbitrageExecutor {
             route.token_borrow.clone(),
             gross_profit,
             net_profit,
-            flash_loan_ctx.fee,
+            fees_paid,
         );
         
         // Emit arbitrage executed event
-        events::emit_arbitrage_executed(
-            env,
-            route.route_id,
-            route.dex_a.pool_address.clone(),
-            route.dex_b.pool_address.clone(),
-            net_profit,
-        );
+        let mut pools: Vec<Address> = Vec::new(env);
+        for leg in route.legs.iter() {
+            for split in leg.splits.iter() {
+                pools.push_back(split.pool);
+            }
+        }
+        events::emit_arbitrage_executed(env, route.route_id, pools, net_profit);
         
         Ok(ArbitrageResult {
             success: true,
             amount_in: amount_start,
-            amount_after_swap1,
-            amount_after_swap2,
+            leg_outputs,
+            amount_out,
             gross_profit,
             net_profit,
-            fees_paid: flash_loan_ctx.fee,
+            fees_paid,
         })
     }
     
-    /// Validate arbitrage route configuration
-    fn validate_route(route: &ArbitrageRoute) -> Result<(), Error> {
-        if route.amount <= 0 {
+    /// Execute a batch of independent cycles on the same borrowed funds
+    /// 
+    /// Routes run one after another, each starting from its own `amount`
+    /// of the borrowed token. Under `SkipUnprofitable` every route is quoted
+    /// first and skipped when the quote misses its minimum profit; under
+    /// `AllOrNothing` any route below its minimum reverts the batch. A route
+    /// that fails while trading always reverts, since swaps already made
+    /// cannot be undone.
+    /// 
+    /// `flash_fee` is shared between routes pro rata by amount; the batch as
+    /// a whole must still cover all of it.
+    pub fn execute_batch(
+        env: &Env,
+        routes: &Vec<ArbitrageRoute>,
+        policy: BatchPolicy,
+        flash_fee: i128,
+    ) -> Result<BatchResult, Error> {
+        if routes.is_empty() {
+            return Err(Error::InvalidRoute);
+        }
+        
+        let mut total_amount: i128 = 0;
+        for route in routes.iter() {
+            total_amount = total_amount.checked_add(route.amount)
+                .ok_or(Error::ArithmeticOverflow)?;
+        }
+        
+        let mut outcomes: Vec<RouteExecution> = Vec::new(env);
+        let mut gross_total: i128 = 0;
+        let mut executed: u32 = 0;
+        for route in routes.iter() {
+            let fee_share = Self::fee_share(flash_fee, route.amount, total_amount)?;
+            
+            let execution = match policy {
+                BatchPolicy::SkipUnprofitable => Self::execute_or_skip(env, &route, fee_share)?,
+                BatchPolicy::AllOrNothing => {
+                    RouteExecution::Executed(Self::execute_arbitrage(env, &route, fee_share)?)
+                }
+            };
+            
+            if let RouteExecution::Executed(result) = &execution {
+                gross_total = gross_total.checked_add(result.gross_profit)
+                    .ok_or(Error::ArithmeticOverflow)?;
+                executed += 1;
+            }
+            outcomes.push_back(execution);
+        }
+        
+        // The batch pays the whole fee even when some routes were skipped
+        let total_profit = gross_total.checked_sub(flash_fee)
+            .ok_or(Error::ArithmeticOverflow)?;
+        if executed == 0 || total_profit <= 0 {
+            return Err(Error::NoProfitGenerated);
+        }
+        
+        let token_borrow = routes.get_unchecked(0).token_borrow;
+        events::emit_batch_executed(
+            env,
+            token_borrow,
+            executed,
+            outcomes.len() - executed,
+            total_profit,
+        );
+        
+        Ok(BatchResult { outcomes, total_profit })
+    }
+    
+    /// Execute a route unless its pre-trade quote misses the minimum profit
+    /// 
+    /// A skipped route emits an event and returns the quotes it was judged
+    /// on instead of reverting. Failures after trading has started still
+    /// revert as usual.
+    pub fn execute_or_skip(
+        env: &Env,
+        route: &ArbitrageRoute,
+        flash_fee: i128,
+    ) -> Result<RouteExecution, Error> {
+        if let Some(skipped) = Self::check_or_skip(env, route, flash_fee)? {
+            return Ok(RouteExecution::Skipped(skipped));
+        }
+        
+        Ok(RouteExecution::Executed(Self::execute_arbitrage(env, route, flash_fee)?))
+    }
+    
+    /// Run `pre_trade_check` and emit the skip event if the route misses
+    /// its minimum profit
+    pub fn check_or_skip(
+        env: &Env,
+        route: &ArbitrageRoute,
+        flash_fee: i128,
+    ) -> Result<Option<SkippedRoute>, Error> {
+        let skipped = Self::pre_trade_check(env, route, flash_fee)?;
+        if let Some(skipped) = &skipped {
+            events::emit_route_skipped(
+                env,
+                route.route_id,
+                skipped.reason,
+                skipped.amount_out,
+                skipped.expected_profit,
+                skipped.min_profit,
+            );
+        }
+        
+        Ok(skipped)
+    }
+    
+    /// Quote a route and describe why it should be skipped, if it should
+    pub fn pre_trade_check(
+        env: &Env,
+        route: &ArbitrageRoute,
+        flash_fee: i128,
+    ) -> Result<Option<SkippedRoute>, Error> {
+        let min_profit = route.amount.checked_mul(route.min_profit_bps as i128)
+            .ok_or(Error::ArithmeticOverflow)?
+            .checked_div(10000)
+            .ok_or(Error::ArithmeticOverflow)?;
+        let skipped = |reason, leg_outputs, amount_out, expected_profit| SkippedRoute {
+            route_id: route.route_id,
+            reason,
+            amount_in: route.amount,
+            leg_outputs,
+            amount_out,
+            flash_fee,
+            expected_profit,
+            min_profit,
+        };
+        
+        // An invalid route is an error; one that cannot be quoted is skipped
+        Self::validate_route(env, route)?;
+        let legs = match Self::quote_legs(env, route) {
+            Ok(legs) => legs,
+            Err(_) => return Ok(Some(skipped(SkipReason::QuoteFailed, Vec::new(env), 0, 0))),
+        };
+        
+        let mut leg_outputs: Vec<i128> = Vec::new(env);
+        for leg in legs.iter() {
+            leg_outputs.push_back(leg.amount_out);
+        }
+        let amount_out = leg_outputs.last().unwrap_or(0);
+        
+        let expected_profit = amount_out
+            .checked_sub(route.amount)
+            .ok_or(Error::ArithmeticOverflow)?
+            .checked_sub(flash_fee)
+            .ok_or(Error::ArithmeticOverflow)?;
+        
+        let reason = match check_minimum_profit(expected_profit, min_profit) {
+            Ok(()) => return Ok(None),
+            Err(Error::NoProfitGenerated) => SkipReason::NoProfit,
+            Err(_) => SkipReason::BelowMinProfit,
+        };
+        
+        Ok(Some(skipped(reason, leg_outputs, amount_out, expected_profit)))
+    }
+    
+    /// Part of `flash_fee` owed by a route trading `amount` out of `total_amount`
+    fn fee_share(flash_fee: i128, amount: i128, total_amount: i128) -> Result<i128, Error> {
+        if amount <= 0 || total_amount <= 0 {
             return Err(Error::InvalidFlashLoanAmount);
         }
         
-        // Ensure we're not swapping to the same DEX
-        // (Though same DEX arbitrage is technically possible with different pools)
+        // Rounded up so the shares never undercount the fee
+        flash_fee.checked_mul(amount)
+            .ok_or(Error::ArithmeticOverflow)?
+            .checked_add(total_amount - 1)
+            .ok_or(Error::ArithmeticOverflow)?
+            .checked_div(total_amount)
+            .ok_or(Error::ArithmeticOverflow)
+    }
+    
+    /// Output each leg of `route` is held to
+    /// 
+    /// The caller's expectations when given, quoted off-chain before the
+    /// transaction; only these catch a pool pushed before it lands, for
+    /// example by a sandwich. Otherwise the whole route is quoted here,
+    /// before the first swap, so a leg filling badly is not excused by the
+    /// legs after it.
+    fn expected_outputs(env: &Env, route: &ArbitrageRoute) -> Result<Vec<i128>, Error> {
+        if !route.expected_outputs.is_empty() {
+            return Ok(route.expected_outputs.clone());
+        }
         
-        Ok(())
+        let mut outputs: Vec<i128> = Vec::new(env);
+        for leg in Self::quote_legs(env, route)?.iter() {
+            outputs.push_back(leg.amount_out);
+        }
+        Ok(outputs)
+    }
+    
+    /// Execute one leg, swapping each split on its own pool
+    /// 
+    /// Every split is checked against its quote as it trades, and the leg's
+    /// combined output against `expected_output`. Returns the total amount
+    /// of `token_out` received.
+    fn execute_leg(
+        env: &Env,
+        leg: &SwapLeg,
+        amount_in: i128,
+        expected_output: i128,
+        max_slippage_bps: u32,
+        deadline: u64,
+    ) -> Result<i128, Error> {
+        // Split minimums come from the pools as they stand now
+        let quote = Self::quote_leg(env, leg, amount_in)?;
+        
+        let mut amount_out: i128 = 0;
+        for split in quote.splits.iter() {
+            let expected_output = split.amount_out;
+            let min_output = Self::apply_slippage(expected_output, max_slippage_bps)?;
+            
+            let split_out = execute_dex_swap(
+                env,
+                split.dex_type,
+                &split.pool,
+                &leg.token_in,
+                &leg.token_out,
+                split.amount_in,
+                min_output,
+                deadline,
+            )?;
+            
+            // Validate slippage for this pool
+            check_slippage(expected_output, split_out, max_slippage_bps)?;
+            
+            // Emit swap event
+            events::emit_swap_completed(
+                env,
+                split.pool.clone(),
+                leg.token_in.clone(),
+                leg.token_out.clone(),
+                split.amount_in,
+                split_out,
+            );
+            
+            amount_out = amount_out.checked_add(split_out)
+                .ok_or(Error::ArithmeticOverflow)?;
+        }
+        
+        // Validate slippage for the leg as a whole
+        check_slippage(expected_output, amount_out, max_slippage_bps)?;
+        
+        // A manipulated pool can quote and fill at the same bad price
+        oracle::check_leg_price(env, &leg.token_in, &leg.token_out, amount_in, amount_out)?;
+        
+        Ok(amount_out)
     }
     
-    /// Calculate expected output for a swap
-    fn calculate_expected_output(
+    /// Quote one leg, pool by pool, without trading
+    fn quote_leg(env: &Env, leg: &SwapLeg, amount_in: i128) -> Result<LegReport, Error> {
+        let amounts = Self::split_amounts(env, amount_in, &leg.splits)?;
+        
+        let mut splits: Vec<SplitReport> = Vec::new(env);
+        let mut amount_out: i128 = 0;
+        let mut spot_amount_out: i128 = 0;
+        let mut dex_fee: i128 = 0;
+        for (split, split_in) in leg.splits.iter().zip(amounts.iter()) {
+            if split_in == 0 {
+                continue;
+            }
+            let report = Self::quote_split(env, &split, leg, split_in)?;
+            
+            amount_out = amount_out.checked_add(report.amount_out)
+                .ok_or(Error::ArithmeticOverflow)?;
+            spot_amount_out = spot_amount_out.checked_add(report.spot_amount_out)
+                .ok_or(Error::ArithmeticOverflow)?;
+            dex_fee = dex_fee.checked_add(report.dex_fee)
+                .ok_or(Error::ArithmeticOverflow)?;
+            splits.push_back(report);
+        }
+        
+        Ok(LegReport {
+            token_in: leg.token_in.clone(),
+            token_out: leg.token_out.clone(),
+            amount_in,
+            amount_out,
+            dex_fee,
+            price_impact_bps: price_impact_bps(spot_amount_out, amount_out)?,
+            splits,
+        })
+    }
+    
+    /// Quote one pool's share of a leg
+    fn quote_split(
         env: &Env,
-        dex: &DexConfig,
-        token_in: &Address,
-        token_out: &Address,
+        split: &PoolSplit,
+        leg: &SwapLeg,
         amount_in: i128,
+    ) -> Result<SplitReport, Error> {
+        let dex = DexConfig::from_split(split);
+        let quote = quote_dex_swap(
+            env,
+            dex.dex_type,
+            &dex.pool_address,
+            &leg.token_in,
+            &leg.token_out,
+            amount_in,
+        )?;
+        
+        // Fee is taken from the input before it reaches the curve
+        let dex_fee = amount_in.checked_mul(quote.fee_bps as i128)
+            .ok_or(Error::ArithmeticOverflow)?
+            .checked_div(10000)
+            .ok_or(Error::ArithmeticOverflow)?;
+        
+        Ok(SplitReport {
+            dex_type: split.dex_type,
+            pool: split.pool.clone(),
+            amount_in,
+            amount_out: quote.amount_out,
+            spot_amount_out: quote.spot_amount_out,
+            dex_fee,
+            price_impact_bps: price_impact_bps(quote.spot_amount_out, quote.amount_out)?,
+            reserve_in: quote.reserve_in,
+            reserve_out: quote.reserve_out,
+        })
+    }
+    
+    /// Divide `amount` between splits by their fractions
+    /// 
+    /// Rounding dust goes to the last split so the whole amount is traded.
+    pub fn split_amounts(
+        env: &Env,
+        amount: i128,
+        splits: &Vec<PoolSplit>,
+    ) -> Result<Vec<i128>, Error> {
+        let mut amounts: Vec<i128> = Vec::new(env);
+        let mut remaining = amount;
+        for (i, split) in splits.iter().enumerate() {
+            let share = if i as u32 + 1 == splits.len() {
+                remaining
+            } else {
+                amount.checked_mul(split.fraction_bps as i128)
+                    .ok_or(Error::ArithmeticOverflow)?
+                    .checked_div(10000)
+                    .ok_or(Error::ArithmeticOverflow)?
+            };
+            remaining = remaining.checked_sub(share)
+                .ok_or(Error::ArithmeticOverflow)?;
+            amounts.push_back(share);
+        }
+        Ok(amounts)
+    }
+    
+    /// Quote every leg of a route in order, without trading
+    pub fn quote_route(env: &Env, route: &ArbitrageRoute) -> Result<Vec<LegReport>, Error> {
+        Self::validate_route(env, route)?;
+        Self::quote_legs(env, route)
+    }
+    
+    /// Quote the legs of an already validated route
+    fn quote_legs(env: &Env, route: &ArbitrageRoute) -> Result<Vec<LegReport>, Error> {
+        let mut legs: Vec<LegReport> = Vec::new(env);
+        let mut amount = route.amount;
+        for leg in route.legs.iter() {
+            let report = Self::quote_leg(env, &leg, amount)?;
+            amount = report.amount_out;
+            legs.push_back(report);
+        }
+        
+        Ok(legs)
+    }
+    
+    /// Check the profit measured from token balances matches the computed one
+    /// 
+    /// `balance_before` is the contract balance before the borrow arrived and
+    /// `balance_after` the balance once every debt is repaid. A fee-on-transfer
+    /// token or an adapter misreporting its output shows up as a difference.
+    pub fn verify_profit(
+        net_profit: i128,
+        balance_before: i128,
+        balance_after: i128,
     ) -> Result<i128, Error> {
-        // This would call the DEX's quote/calculate function
-        // For now, placeholder implementation
+        let measured = balance_after.checked_sub(balance_before)
+            .ok_or(Error::ArithmeticOverflow)?;
+        
+        if measured != net_profit {
+            return Err(Error::ProfitMismatch);
+        }
+        
+        Ok(measured)
+    }
+    
+    /// Validate arbitrage route configuration
+    /// 
+    /// The route must have a sound structure and only touch tokens and
+    /// pools the owner has allowlisted.
+    pub fn validate_route(env: &Env, route: &ArbitrageRoute) -> Result<(), Error> {
+        Self::check_route_structure(route)?;
+        allowlist::check_route(env, route)
+    }
+    
+    /// Check the shape of a route without reading contract storage
+    /// 
+    /// The legs must chain into a closed cycle of 2 to `MAX_HOPS` hops that
+    /// starts and ends in the borrowed token, visits every other token at
+    /// most once and never reuses a pool.
+    fn check_route_structure(route: &ArbitrageRoute) -> Result<(), Error> {
+        if route.amount <= 0 {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
+        
+        if route.max_slippage_bps > 10000 {
+            return Err(Error::InvalidSlippage);
+        }
+        
+        if route.min_profit_bps > 10000 {
+            return Err(Error::InvalidProfitThreshold);
+        }
+        
+        if route.legs.len() < 2 {
+            return Err(Error::RouteTooShort);
+        }
+        
+        if route.legs.len() > MAX_HOPS {
+            return Err(Error::TooManyHops);
+        }
         
-        // TODO: Implement actual calculation based on DEX type
-        // match dex.dex_type {
-        //     DexType::Soroswap => SoroswapDex::calculate_output(...),
-        //     DexType::Phoenix => PhoenixDex::calculate_output(...),
-        // }
+        if !route.expected_outputs.is_empty() && route.expected_outputs.len() != route.legs.len() {
+            return Err(Error::InvalidRoute);
+        }
+        
+        let env = route.legs.env();
+        let mut visited: Vec<Address> = Vec::new(env);
+        visited.push_back(route.token_borrow.clone());
+        let mut pools: Vec<Address> = Vec::new(env);
+        
+        let mut token = route.token_borrow.clone();
+        for (i, leg) in route.legs.iter().enumerate() {
+            if leg.token_in != token {
+                return Err(Error::RouteNotChained);
+            }
+            
+            Self::validate_splits(&leg.splits)?;
+            for split in leg.splits.iter() {
+                if pools.contains(&split.pool) {
+                    return Err(Error::DuplicatePool);
+                }
+                pools.push_back(split.pool);
+            }
+            
+            token = leg.token_out;
+            
+            // Intermediate tokens are visited once and never the borrowed token
+            let is_last = i as u32 + 1 == route.legs.len();
+            if !is_last {
+                if visited.contains(&token) {
+                    return Err(Error::DuplicateToken);
+                }
+                visited.push_back(token.clone());
+            }
+        }
+        
+        // Cycle must close back on the borrowed token
+        if token != route.token_borrow {
+            return Err(Error::RouteNotClosed);
+        }
         
-        Ok(amount_in) // Placeholder
+        Ok(())
+    }
+    
+    /// Splits must be non-empty, use distinct pools and add up to 100%
+    fn validate_splits(splits: &Vec<PoolSplit>) -> Result<(), Error> {
+        if splits.is_empty() {
+            return Err(Error::InvalidSplit);
+        }
+        
+        let mut total: u32 = 0;
+        for (i, split) in splits.iter().enumerate() {
+            if split.fraction_bps == 0 {
+                return Err(Error::InvalidSplit);
+            }
+            if splits.iter().skip(i + 1).any(|other| other.pool == split.pool) {
+                return Err(Error::DuplicatePool);
+            }
+            total = total.checked_add(split.fraction_bps)
+                .ok_or(Error::ArithmeticOverflow)?;
+        }
+        
+        if total != 10000 {
+            return Err(Error::InvalidSplit);
+        }
+        
+        Ok(())
     }
     
     /// Apply slippage tolerance to expected output
@@ -216,47 +965,89 @@ impl ArbitrageExecutor {
     
     /// Simulate arbitrage execution (dry run)
     /// Used for testing profitability before executing
+    /// 
+    /// Quotes every leg exactly as `execute_arbitrage` does before it
+    /// trades. The flash loan fee comes from the fee schedule configured
+    /// for `lender`.
     pub fn simulate_arbitrage(
         env: &Env,
         route: &ArbitrageRoute,
-    ) -> Result<i128, Error> {
-        // Calculate expected outputs for both swaps
-        let output_1 = Self::calculate_expected_output(
-            env,
-            &route.dex_a,
-            &route.token_borrow,
-            &route.token_intermediate,
-            route.amount,
-        )?;
-        
-        let output_2 = Self::calculate_expected_output(
-            env,
-            &route.dex_b,
-            &route.token_intermediate,
-            &route.token_borrow,
-            output_1,
-        )?;
+        lender: &Address,
+    ) -> Result<SimulationReport, Error> {
+        // Calculate expected outputs along the cycle
+        let legs = Self::quote_route(env, route)?;
+        let amount = legs.last().map_or(route.amount, |leg| leg.amount_out);
         
         // Calculate flash loan fee
-        let fee = route.amount.checked_mul(9)
-            .ok_or(Error::ArithmeticOverflow)?
-            .checked_div(10000)
-            .ok_or(Error::ArithmeticOverflow)?;
+        let fee = FlashLoanManager::calculate_flash_loan_fee(env, lender, route.amount)?;
         
         // Net profit = final_amount - borrowed_amount - fee
-        let net_profit = output_2
+        let gross_profit = amount
             .checked_sub(route.amount)
-            .ok_or(Error::ArithmeticOverflow)?
+            .ok_or(Error::ArithmeticOverflow)?;
+        let net_profit = gross_profit
             .checked_sub(fee)
             .ok_or(Error::ArithmeticOverflow)?;
         
-        Ok(net_profit)
+        Ok(SimulationReport {
+            token_borrow: route.token_borrow.clone(),
+            amount_in: route.amount,
+            legs,
+            amount_out: amount,
+            flash_loan_fee: fee,
+            gross_profit,
+            net_profit,
+        })
     }
 }
 
+/// Shortfall of `amount_out` against `spot_amount_out`, in basis points
+fn price_impact_bps(spot_amount_out: i128, amount_out: i128) -> Result<u32, Error> {
+    if spot_amount_out <= 0 {
+        return Ok(0);
+    }
+    
+    let impact = spot_amount_out.checked_sub(amount_out)
+        .ok_or(Error::ArithmeticOverflow)?
+        .checked_mul(10000)
+        .ok_or(Error::ArithmeticOverflow)?
+        .checked_div(spot_amount_out)
+        .ok_or(Error::ArithmeticOverflow)?;
+    
+    Ok(impact.clamp(0, 10000) as u32)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::vec;
+
+    fn swap(env: &Env, token_in: &Address, token_out: &Address) -> SwapLeg {
+        SwapLeg {
+            token_in: token_in.clone(),
+            token_out: token_out.clone(),
+            splits: vec![env, split(env, 10000)],
+        }
+    }
+
+    fn split(env: &Env, fraction_bps: u32) -> PoolSplit {
+        PoolSplit { dex_type: DexType::Soroswap, pool: Address::generate(env), fraction_bps }
+    }
+
+    fn route(legs: Vec<SwapLeg>, token_borrow: &Address) -> ArbitrageRoute {
+        let expected_outputs = Vec::new(legs.env());
+        ArbitrageRoute {
+            route_id: 1,
+            legs,
+            token_borrow: token_borrow.clone(),
+            amount: 1_000,
+            min_profit_bps: 0,
+            max_slippage_bps: 100,
+            expected_outputs,
+            deadline: 0,
+        }
+    }
 
     #[test]
     fn test_apply_slippage() {
@@ -268,4 +1059,223 @@ mod tests {
         let result = ArbitrageExecutor::apply_slippage(10000, 50).unwrap();
         assert_eq!(result, 9950);
     }
+
+    #[test]
+    fn test_verify_profit_uses_balance_delta() {
+        let result = ArbitrageResult {
+            success: true,
+            amount_in: 1_000_000,
+            leg_outputs: Vec::new(&Env::default()),
+            amount_out: 1_010_000,
+            gross_profit: 10_000,
+            net_profit: 9_100,
+            fees_paid: 900,
+        };
+        
+        // Balance grew by exactly the computed profit
+        assert_eq!(ArbitrageExecutor::verify_profit(result.net_profit, 50_000, 59_100), Ok(9_100));
+        
+        // A 1% transfer tax on repayment eats into the real balance
+        assert_eq!(
+            ArbitrageExecutor::verify_profit(result.net_profit, 50_000, 49_091),
+            Err(Error::ProfitMismatch)
+        );
+        
+        // More than computed means an adapter misreported its output
+        assert_eq!(
+            ArbitrageExecutor::verify_profit(result.net_profit, 50_000, 60_000),
+            Err(Error::ProfitMismatch)
+        );
+    }
+
+    #[test]
+    fn test_validate_route_requires_closed_cycle() {
+        let env = Env::default();
+        let usdc = Address::generate(&env);
+        let xlm = Address::generate(&env);
+        let aqua = Address::generate(&env);
+        let btc = Address::generate(&env);
+        let check = |route: &ArbitrageRoute| ArbitrageExecutor::check_route_structure(route);
+        
+        // USDC -> XLM -> AQUA -> BTC -> USDC
+        let cycle = vec![
+            &env,
+            swap(&env, &usdc, &xlm),
+            swap(&env, &xlm, &aqua),
+            swap(&env, &aqua, &btc),
+            swap(&env, &btc, &usdc),
+        ];
+        assert_eq!(check(&route(cycle.clone(), &usdc)), Ok(()));
+        
+        // Must start from the borrowed token
+        assert_eq!(check(&route(cycle, &xlm)), Err(Error::RouteNotChained));
+        
+        // Legs must chain: XLM -> USDC does not follow USDC -> BTC
+        let broken = vec![&env, swap(&env, &usdc, &btc), swap(&env, &xlm, &usdc)];
+        assert_eq!(check(&route(broken, &usdc)), Err(Error::RouteNotChained));
+        
+        // Must end in the borrowed token
+        let open = vec![&env, swap(&env, &usdc, &xlm), swap(&env, &xlm, &aqua)];
+        assert_eq!(check(&route(open, &usdc)), Err(Error::RouteNotClosed));
+        
+        // A single hop cannot form a cycle
+        let single = vec![&env, swap(&env, &usdc, &usdc)];
+        assert_eq!(check(&route(single, &usdc)), Err(Error::RouteTooShort));
+    }
+
+    #[test]
+    fn test_validate_route_rejects_repeats_and_bad_bounds() {
+        let env = Env::default();
+        let usdc = Address::generate(&env);
+        let xlm = Address::generate(&env);
+        let aqua = Address::generate(&env);
+        let check = |route: &ArbitrageRoute| ArbitrageExecutor::check_route_structure(route);
+        
+        // Passing through the borrowed token midway
+        let through_borrow = vec![
+            &env,
+            swap(&env, &usdc, &xlm),
+            swap(&env, &xlm, &usdc),
+            swap(&env, &usdc, &aqua),
+            swap(&env, &aqua, &usdc),
+        ];
+        assert_eq!(check(&route(through_borrow, &usdc)), Err(Error::DuplicateToken));
+        
+        // Visiting an intermediate token twice
+        let revisit = vec![
+            &env,
+            swap(&env, &usdc, &xlm),
+            swap(&env, &xlm, &aqua),
+            swap(&env, &aqua, &xlm),
+            swap(&env, &xlm, &usdc),
+        ];
+        assert_eq!(check(&route(revisit, &usdc)), Err(Error::DuplicateToken));
+        
+        // Same pool on both legs
+        let there = swap(&env, &usdc, &xlm);
+        let mut back = swap(&env, &xlm, &usdc);
+        back.splits = there.splits.clone();
+        assert_eq!(check(&route(vec![&env, there, back], &usdc)), Err(Error::DuplicatePool));
+        
+        // Hop limit
+        let mut long = Vec::new(&env);
+        let mut token = usdc.clone();
+        for _ in 0..MAX_HOPS {
+            let next = Address::generate(&env);
+            long.push_back(swap(&env, &token, &next));
+            token = next;
+        }
+        long.push_back(swap(&env, &token, &usdc));
+        assert_eq!(check(&route(long, &usdc)), Err(Error::TooManyHops));
+        
+        // Basis points must stay within 100%
+        let cycle = vec![&env, swap(&env, &usdc, &xlm), swap(&env, &xlm, &usdc)];
+        let mut bad_slippage = route(cycle.clone(), &usdc);
+        bad_slippage.max_slippage_bps = 10_001;
+        assert_eq!(check(&bad_slippage), Err(Error::InvalidSlippage));
+        
+        let mut bad_profit = route(cycle, &usdc);
+        bad_profit.min_profit_bps = 10_001;
+        assert_eq!(check(&bad_profit), Err(Error::InvalidProfitThreshold));
+    }
+
+    #[test]
+    fn test_splits_divide_leg_input() {
+        let env = Env::default();
+        let usdc = Address::generate(&env);
+        let xlm = Address::generate(&env);
+        
+        let splits = vec![&env, split(&env, 3333), split(&env, 3333), split(&env, 3334)];
+        let amounts = ArbitrageExecutor::split_amounts(&env, 1_000, &splits).unwrap();
+        assert_eq!(amounts, vec![&env, 333, 333, 334]);
+        assert_eq!(ArbitrageExecutor::validate_splits(&splits), Ok(()));
+        
+        // Fractions must cover the whole input
+        let short = vec![&env, split(&env, 5000), split(&env, 4000)];
+        assert_eq!(ArbitrageExecutor::validate_splits(&short), Err(Error::InvalidSplit));
+        
+        // The same pool cannot take two shares of one leg
+        let pool = split(&env, 5000);
+        let duplicated = vec![&env, pool.clone(), pool];
+        assert_eq!(ArbitrageExecutor::validate_splits(&duplicated), Err(Error::DuplicatePool));
+        
+        // Routes check the splits of every leg
+        let mut leg = swap(&env, &usdc, &xlm);
+        leg.splits = short;
+        let bad = vec![&env, leg, swap(&env, &xlm, &usdc)];
+        assert_eq!(
+            ArbitrageExecutor::check_route_structure(&route(bad, &usdc)),
+            Err(Error::InvalidSplit)
+        );
+    }
+
+    #[test]
+    fn test_batch_skips_unprofitable_routes() {
+        use crate::mocks::soroswap::{MockSoroswapPair, MockSoroswapPairClient};
+        
+        let env = Env::default();
+        let contract = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let usdc = Address::generate(&env);
+        let xlm = Address::generate(&env);
+        let pool = |reserve: i128| {
+            let pair = env.register_contract(None, MockSoroswapPair);
+            MockSoroswapPairClient::new(&env, &pair).init(&usdc, &xlm, &reserve, &reserve);
+            env.as_contract(&contract, || allowlist::set_pool_allowed(&env, &pair, true));
+            vec![&env, PoolSplit { dex_type: DexType::Soroswap, pool: pair, fraction_bps: 10000 }]
+        };
+        let deep = 1_000_000_000_000_i128;
+        let leg = |token_in: &Address, token_out: &Address, splits: Vec<PoolSplit>| SwapLeg {
+            token_in: token_in.clone(),
+            token_out: token_out.clone(),
+            splits,
+        };
+        
+        // Two equal pools: the round trip only pays their fees
+        let losing = route(vec![&env, leg(&usdc, &xlm, pool(deep)), leg(&xlm, &usdc, pool(deep))], &usdc);
+        // An empty pool cannot be quoted
+        let unquotable = route(vec![&env, leg(&usdc, &xlm, pool(0)), leg(&xlm, &usdc, pool(deep))], &usdc);
+        
+        env.as_contract(&contract, || {
+            // Allowlisting is checked before quoting and is never skipped
+            assert_eq!(
+                ArbitrageExecutor::pre_trade_check(&env, &losing, 1),
+                Err(Error::TokenNotAllowed)
+            );
+            allowlist::set_token_allowed(&env, &usdc, true);
+            allowlist::set_token_allowed(&env, &xlm, true);
+            
+            let skipped = ArbitrageExecutor::pre_trade_check(&env, &losing, 1).unwrap().unwrap();
+            assert_eq!(skipped.reason, SkipReason::NoProfit);
+            assert_eq!(skipped.leg_outputs.len(), 2);
+            assert!(skipped.amount_out < losing.amount);
+            assert!(skipped.expected_profit < 0);
+            
+            let skipped = ArbitrageExecutor::pre_trade_check(&env, &unquotable, 1).unwrap().unwrap();
+            assert_eq!(skipped.reason, SkipReason::QuoteFailed);
+            assert_eq!(skipped.amount_out, 0);
+            
+            // Nothing left to run, so the batch cannot pay the fee
+            let routes = vec![&env, losing, unquotable];
+            assert_eq!(
+                ArbitrageExecutor::execute_batch(&env, &routes, BatchPolicy::SkipUnprofitable, 2),
+                Err(Error::NoProfitGenerated)
+            );
+            assert_eq!(
+                ArbitrageExecutor::execute_batch(&env, &Vec::new(&env), BatchPolicy::AllOrNothing, 2),
+                Err(Error::InvalidRoute)
+            );
+        });
+    }
+
+    #[test]
+    fn test_fee_share_rounds_up() {
+        // 10 split across a third of the traded amount
+        assert_eq!(ArbitrageExecutor::fee_share(10, 1_000, 3_000), Ok(4));
+        assert_eq!(ArbitrageExecutor::fee_share(10, 3_000, 3_000), Ok(10));
+        assert_eq!(ArbitrageExecutor::fee_share(0, 1_000, 3_000), Ok(0));
+        assert_eq!(
+            ArbitrageExecutor::fee_share(10, 0, 3_000),
+            Err(Error::InvalidFlashLoanAmount)
+        );
+    }
 }
@@ -0,0 +1,228 @@
+use soroban_sdk::{contractclient, token, Address, Env, Vec};
+use crate::dex_interface::DexQuote;
+use crate::errors::Error;
+
+const DEX_ADAPTER: &str = "dex_adapter";
+const ADAPTER_IDS: &str = "adapter_ids";
+
+/// Interface an external DEX adapter contract implements
+///
+/// Adapters let new venues be onboarded without redeploying the executor:
+/// deploy an adapter that speaks the venue's protocol, then register it
+/// under an id that routes reference as `DexType::External(id)`.
+#[contractclient(name = "DexAdapterClient")]
+#[allow(dead_code)]
+pub trait DexAdapter {
+    /// Quote swapping `amount_in` of `token_in` for `token_out` on `pool`,
+    /// including the output at the pool's pre-trade price
+    fn quote(env: Env, pool: Address, token_in: Address, token_out: Address, amount_in: i128) -> DexQuote;
+
+    /// Swap `amount_in` of `token_in`, already transferred to the adapter,
+    /// and send the output to `to`. Must trap rather than return less than
+    /// `min_amount_out`.
+    #[allow(clippy::too_many_arguments)]
+    fn swap(
+        env: Env,
+        pool: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_amount_out: i128,
+        to: Address,
+        deadline: u64,
+    ) -> i128;
+
+    /// Reserves of `pool`, ordered as (`token_a`, `token_b`)
+    fn get_reserves(env: Env, pool: Address, token_a: Address, token_b: Address) -> (i128, i128);
+}
+
+/// Adapter for venues served by a registered adapter contract
+///
+/// The adapter is trusted with the swap input, so only the owner can
+/// register one; the output is still measured from balances.
+pub struct ExternalDex;
+
+impl ExternalDex {
+    /// Quote a swap through adapter `adapter_id`
+    pub fn quote(
+        env: &Env,
+        adapter_id: u32,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+        amount_in: i128,
+    ) -> Result<DexQuote, Error> {
+        let adapter = Self::adapter(env, adapter_id)?;
+        Ok(adapter.quote(pool_address, token_in, token_out, &amount_in))
+    }
+
+    /// Output for one whole unit of `token_in`, fees included
+    pub fn get_price(
+        env: &Env,
+        adapter_id: u32,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+    ) -> Result<i128, Error> {
+        let decimals = token::Client::new(env, token_in).decimals();
+        let one = 10_i128.checked_pow(decimals).ok_or(Error::ArithmeticOverflow)?;
+
+        Ok(Self::quote(env, adapter_id, pool_address, token_in, token_out, one)?.amount_out)
+    }
+
+    /// Hand `amount_in` to the adapter and swap it back to this contract
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap(
+        env: &Env,
+        adapter_id: u32,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+        amount_in: i128,
+        min_amount_out: i128,
+        deadline: u64,
+    ) -> Result<i128, Error> {
+        let adapter = Self::adapter(env, adapter_id)?;
+
+        let this = env.current_contract_address();
+        let token_out_client = token::Client::new(env, token_out);
+        let balance_before = token_out_client.balance(&this);
+
+        token::Client::new(env, token_in).transfer(&this, &adapter.address, &amount_in);
+        adapter.swap(pool_address, token_in, token_out, &amount_in, &min_amount_out, &this, &deadline);
+
+        // Trust balances rather than the amount the adapter reports
+        let amount_out = token_out_client.balance(&this)
+            .checked_sub(balance_before)
+            .ok_or(Error::ArithmeticOverflow)?;
+        if amount_out < min_amount_out {
+            return Err(Error::SlippageExceeded);
+        }
+
+        Ok(amount_out)
+    }
+
+    /// Live reserves reported by the adapter, ordered as (`token_a`, `token_b`)
+    pub fn get_reserves(
+        env: &Env,
+        adapter_id: u32,
+        pool_address: &Address,
+        token_a: &Address,
+        token_b: &Address,
+    ) -> Result<(i128, i128), Error> {
+        let adapter = Self::adapter(env, adapter_id)?;
+        Ok(adapter.get_reserves(pool_address, token_a, token_b))
+    }
+
+    fn adapter(env: &Env, adapter_id: u32) -> Result<DexAdapterClient<'_>, Error> {
+        let address = get_adapter(env, adapter_id).ok_or(Error::DexAdapterNotSet)?;
+        Ok(DexAdapterClient::new(env, &address))
+    }
+}
+
+/// Adapter contract registered under `adapter_id`, if any
+pub fn get_adapter(env: &Env, adapter_id: u32) -> Option<Address> {
+    let key = (DEX_ADAPTER, adapter_id);
+    env.storage().instance().get(&key)
+}
+
+/// Ids of every registered adapter, in registration order
+pub fn list_adapters(env: &Env) -> Vec<u32> {
+    env.storage().instance().get(&ADAPTER_IDS).unwrap_or(Vec::new(env))
+}
+
+/// Register `adapter` under `adapter_id`, replacing any previous one (owner only)
+pub fn set_adapter(env: &Env, adapter_id: u32, adapter: &Address) {
+    let key = (DEX_ADAPTER, adapter_id);
+    env.storage().instance().set(&key, adapter);
+
+    let mut ids = list_adapters(env);
+    if !ids.contains(adapter_id) {
+        ids.push_back(adapter_id);
+        env.storage().instance().set(&ADAPTER_IDS, &ids);
+    }
+}
+
+/// Forget the adapter registered under `adapter_id` (owner only)
+pub fn remove_adapter(env: &Env, adapter_id: u32) {
+    let key = (DEX_ADAPTER, adapter_id);
+    env.storage().instance().remove(&key);
+
+    let mut ids = list_adapters(env);
+    if let Some(index) = ids.first_index_of(adapter_id) {
+        ids.remove(index);
+        env.storage().instance().set(&ADAPTER_IDS, &ids);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use crate::dex_interface::{constant_product_output, execute_dex_swap, quote_dex_swap, DexType};
+    use crate::mocks::create_token;
+    use crate::mocks::dex_adapter::MockDexAdapter;
+    use crate::mocks::soroswap::{create_pair, MockSoroswapRouter};
+    use crate::{FlashLoanExecutorContract, FlashLoanExecutorContractClient};
+
+    #[test]
+    fn test_swaps_through_registered_adapter() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let executor = env.register_contract(None, FlashLoanExecutorContract);
+        let router = env.register_contract(None, MockSoroswapRouter);
+        let adapter = env.register_contract(None, MockDexAdapter);
+
+        let token_a = create_token(&env, &admin, &[(&executor, 1_000)]);
+        let token_b = create_token(&env, &admin, &[]);
+        let pair = create_pair(&env, &router, &token_b, &token_a, 200_000, 100_000);
+        let expected = constant_product_output(1_000, 100_000, 200_000, 30).unwrap();
+        let venue = DexType::External(7);
+
+        env.as_contract(&executor, || {
+            assert_eq!(
+                execute_dex_swap(&env, venue, &pair, &token_a, &token_b, 1_000, 0, 0),
+                Err(Error::DexAdapterNotSet)
+            );
+            set_adapter(&env, 7, &adapter);
+
+            let quote = quote_dex_swap(&env, venue, &pair, &token_a, &token_b, 1_000).unwrap();
+            assert_eq!((quote.amount_out, quote.reserve_in, quote.reserve_out), (expected, 100_000, 200_000));
+
+            let amount_out = execute_dex_swap(&env, venue, &pair, &token_a, &token_b, 1_000, expected, 0);
+            assert_eq!(amount_out, Ok(expected));
+            assert_eq!(
+                ExternalDex::get_reserves(&env, 7, &pair, &token_a, &token_b),
+                Ok((101_000, 200_000 - expected))
+            );
+        });
+
+        assert_eq!(token::Client::new(&env, &token_b).balance(&executor), expected);
+        assert_eq!(token::Client::new(&env, &token_a).balance(&adapter), 0);
+    }
+
+    #[test]
+    fn test_adapter_registry_is_owner_managed() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let owner = Address::generate(&env);
+        let executor = env.register_contract(None, FlashLoanExecutorContract);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        client.initialize(&owner);
+
+        let adapter = Address::generate(&env);
+        let replacement = Address::generate(&env);
+        assert!(client.try_set_dex_adapter(&Address::generate(&env), &1, &adapter).is_err());
+
+        client.set_dex_adapter(&owner, &1, &adapter);
+        client.set_dex_adapter(&owner, &2, &adapter);
+        client.set_dex_adapter(&owner, &1, &replacement);
+        assert_eq!(client.get_dex_adapter(&1), Some(replacement));
+        assert_eq!(client.list_dex_adapters(), soroban_sdk::vec![&env, 1, 2]);
+
+        client.remove_dex_adapter(&owner, &1);
+        assert_eq!(client.get_dex_adapter(&1), None);
+        assert_eq!(client.list_dex_adapters(), soroban_sdk::vec![&env, 2]);
+    }
+}
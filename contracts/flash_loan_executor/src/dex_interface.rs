@@ -1,4 +1,10 @@
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    contractclient, contracttype, token, vec, Address, BytesN, Env, IntoVal, Map, Symbol, Vec, I256,
+};
+use crate::dex_adapter::ExternalDex;
+
+const DEX_ROUTER: &str = "dex_router";
 
 /// Generic DEX interface for cross-DEX compatibility
 /// This trait defines the standard interface that all DEX adapters must implement
@@ -13,7 +19,8 @@ pub trait DexInterface {
     ) -> Result<i128, crate::errors::Error>;
 
     /// Execute a swap on the DEX
-    /// Returns the actual amount received
+    /// Returns the actual amount received; `deadline` is passed to routers
+    /// that reject stale swaps
     fn swap(
         env: &Env,
         pool_address: &Address,
@@ -21,6 +28,7 @@ pub trait DexInterface {
         token_out: &Address,
         amount_in: i128,
         min_amount_out: i128,
+        deadline: u64,
     ) -> Result<i128, crate::errors::Error>;
 
     /// Get pool reserves for liquidity check
@@ -41,6 +49,32 @@ pub trait DexInterface {
     ) -> Result<i128, crate::errors::Error>;
 }
 
+/// Soroswap pair contract interface
+#[contractclient(name = "SoroswapPairClient")]
+#[allow(dead_code)]
+pub trait SoroswapPair {
+    fn token_0(env: Env) -> Address;
+    fn token_1(env: Env) -> Address;
+    fn get_reserves(env: Env) -> (i128, i128);
+    fn swap(env: Env, amount_0_out: i128, amount_1_out: i128, to: Address);
+}
+
+/// Soroswap router contract interface
+#[contractclient(name = "SoroswapRouterClient")]
+#[allow(dead_code)]
+pub trait SoroswapRouter {
+    fn router_pair_for(env: Env, token_a: Address, token_b: Address) -> Address;
+    fn router_get_amounts_out(env: Env, amount_in: i128, path: Vec<Address>) -> Vec<i128>;
+    fn swap_exact_tokens_for_tokens(
+        env: Env,
+        amount_in: i128,
+        amount_out_min: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<i128>;
+}
+
 /// Soroswap DEX adapter
 pub struct SoroswapDex;
 
@@ -51,14 +85,11 @@ impl DexInterface for SoroswapDex {
         token_in: &Address,
         token_out: &Address,
     ) -> Result<i128, crate::errors::Error> {
-        // TODO: Implement Soroswap price fetching
-        // This would call the Soroswap router contract
-        // Example pseudocode:
-        // let router = SoroswapRouterClient::new(env, pool_address);
-        // let amounts = router.get_amounts_out(&amount_in, &path);
-        // Ok(amounts.get(1).unwrap())
+        // Output for one whole unit of token_in, fees included
+        let decimals = token::Client::new(env, token_in).decimals();
+        let one = 10_i128.checked_pow(decimals).ok_or(crate::errors::Error::ArithmeticOverflow)?;
 
-        Ok(0) // Placeholder
+        Self::calculate_output(env, pool_address, token_in, token_out, one)
     }
 
     fn swap(
@@ -68,19 +99,40 @@ impl DexInterface for SoroswapDex {
         token_out: &Address,
         amount_in: i128,
         min_amount_out: i128,
+        deadline: u64,
     ) -> Result<i128, crate::errors::Error> {
-        // TODO: Implement Soroswap swap execution
-        // let router = SoroswapRouterClient::new(env, pool_address);
-        // let path = vec![env, token_in.clone(), token_out.clone()];
-        // let amounts = router.swap_exact_tokens_for_tokens(
-        //     &amount_in,
-        //     &min_amount_out,
-        //     &path,
-        //     &env.current_contract_address(),
-        //     &deadline
-        // );
+        let router_address = get_dex_router(env, DexType::Soroswap)
+            .ok_or(crate::errors::Error::DexRouterNotSet)?;
+        let router = SoroswapRouterClient::new(env, &router_address);
+
+        // The router must trade through the pair the route was priced on
+        if &router.router_pair_for(token_in, token_out) != pool_address {
+            return Err(crate::errors::Error::InvalidPoolAddress);
+        }
 
-        Ok(0) // Placeholder
+        let this = env.current_contract_address();
+        let token_out_client = token::Client::new(env, token_out);
+        let balance_before = token_out_client.balance(&this);
+
+        // The router moves our input straight into the pair
+        authorize_transfer(env, token_in, pool_address, amount_in);
+        router.swap_exact_tokens_for_tokens(
+            &amount_in,
+            &min_amount_out,
+            &vec![env, token_in.clone(), token_out.clone()],
+            &this,
+            &deadline,
+        );
+
+        // Trust balances rather than the amounts the router reports
+        let amount_out = token_out_client.balance(&this)
+            .checked_sub(balance_before)
+            .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+        if amount_out < min_amount_out {
+            return Err(crate::errors::Error::SlippageExceeded);
+        }
+
+        Ok(amount_out)
     }
 
     fn get_reserves(
@@ -89,11 +141,19 @@ impl DexInterface for SoroswapDex {
         token_a: &Address,
         token_b: &Address,
     ) -> Result<(i128, i128), crate::errors::Error> {
-        // TODO: Implement reserve fetching
-        // let pair = SoroswapPairClient::new(env, pool_address);
-        // let reserves = pair.get_reserves();
+        // Pair reserves are ordered by the pair's token_0 / token_1
+        let pair = SoroswapPairClient::new(env, pool_address);
+        let (reserve_0, reserve_1) = pair.get_reserves();
+        let token_0 = pair.token_0();
+        let token_1 = pair.token_1();
 
-        Ok((0, 0)) // Placeholder
+        if token_a == &token_0 && token_b == &token_1 {
+            Ok((reserve_0, reserve_1))
+        } else if token_a == &token_1 && token_b == &token_0 {
+            Ok((reserve_1, reserve_0))
+        } else {
+            Err(crate::errors::Error::InvalidTokenAddress)
+        }
     }
 
     fn calculate_output(
@@ -103,37 +163,340 @@ impl DexInterface for SoroswapDex {
         token_out: &Address,
         amount_in: i128,
     ) -> Result<i128, crate::errors::Error> {
-        // Constant product formula: x * y = k
-        // amount_out = (amount_in * reserve_out * 997) / (reserve_in * 1000 + amount_in * 997)
-        // 997/1000 = 0.3% fee
-
         let (reserve_in, reserve_out) = Self::get_reserves(env, pool_address, token_in, token_out)?;
 
-        let amount_in_with_fee = amount_in
-            .checked_mul(997)
-            .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+        constant_product_output(amount_in, reserve_in, reserve_out, SOROSWAP_FEE_BPS)
+    }
+}
 
-        let numerator = amount_in_with_fee
-            .checked_mul(reserve_out)
-            .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+/// Constant product formula: x * y = k
+///
+/// amount_out = (amount_in * (10000 - fee) * reserve_out)
+///            / (reserve_in * 10000 + amount_in * (10000 - fee))
+pub fn constant_product_output(
+    amount_in: i128,
+    reserve_in: i128,
+    reserve_out: i128,
+    fee_bps: u32,
+) -> Result<i128, crate::errors::Error> {
+    if reserve_in <= 0 || reserve_out <= 0 {
+        return Err(crate::errors::Error::InsufficientLiquidity);
+    }
 
-        let denominator = reserve_in
-            .checked_mul(1000)
-            .ok_or(crate::errors::Error::ArithmeticOverflow)?
-            .checked_add(amount_in_with_fee)
-            .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+    let amount_in_with_fee = amount_in
+        .checked_mul(10000 - fee_bps as i128)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)?;
 
-        let amount_out = numerator
-            .checked_div(denominator)
+    let numerator = amount_in_with_fee
+        .checked_mul(reserve_out)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+
+    let denominator = reserve_in
+        .checked_mul(10000)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)?
+        .checked_add(amount_in_with_fee)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+
+    let amount_out = numerator
+        .checked_div(denominator)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+
+    Ok(amount_out)
+}
+
+/// Output of `amount_in` at a constant-product pool's pre-trade price,
+/// with the fee taken from the input
+pub fn constant_product_spot_output(
+    amount_in: i128,
+    reserve_in: i128,
+    reserve_out: i128,
+    fee_bps: u32,
+) -> Result<i128, crate::errors::Error> {
+    if reserve_in <= 0 || reserve_out <= 0 {
+        return Err(crate::errors::Error::InsufficientLiquidity);
+    }
+
+    let fee = amount_in.checked_mul(fee_bps as i128)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)?
+        / 10000;
+    checked_mul_div(amount_in - fee, reserve_out, reserve_in)
+}
+
+/// Token and amount pair used by Phoenix pool queries
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PhoenixAsset {
+    pub address: Address,
+    pub amount: i128,
+}
+
+/// Phoenix `query_pool_info` response
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PhoenixPoolResponse {
+    pub asset_a: PhoenixAsset,
+    pub asset_b: PhoenixAsset,
+    pub asset_lp_share: PhoenixAsset,
+    pub stake_address: Address,
+}
+
+/// Phoenix `simulate_swap` response
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PhoenixSimulateSwapResponse {
+    pub ask_amount: i128, // Output after the commission
+    pub commission_amount: i128,
+    pub spread_amount: i128,
+    pub total_return: i128,
+}
+
+/// Phoenix pool contract interface, shared by XYK and stable pools
+#[contractclient(name = "PhoenixPoolClient")]
+#[allow(dead_code)]
+pub trait PhoenixPool {
+    fn query_pool_info(env: Env) -> PhoenixPoolResponse;
+    fn simulate_swap(env: Env, offer_asset: Address, offer_amount: i128) -> PhoenixSimulateSwapResponse;
+    #[allow(clippy::too_many_arguments)]
+    fn swap(
+        env: Env,
+        sender: Address,
+        offer_asset: Address,
+        offer_amount: i128,
+        ask_asset_min_amount: Option<i128>,
+        max_spread_bps: Option<i64>,
+        deadline: Option<u64>,
+        max_allowed_fee_bps: Option<i64>,
+    ) -> i128;
+}
+
+/// Phoenix DEX adapter
+///
+/// Phoenix XYK and stable pools expose the same entry points and price
+/// swaps themselves, so quotes come from the pool's `simulate_swap` and
+/// swaps go directly to the pool; no router is involved.
+pub struct PhoenixDex;
+
+impl PhoenixDex {
+    /// Quote a swap as the pool would execute it
+    pub fn simulate(
+        env: &Env,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+        amount_in: i128,
+    ) -> Result<PhoenixSimulateSwapResponse, crate::errors::Error> {
+        // Checks both tokens belong to the pool before asking it
+        Self::get_reserves(env, pool_address, token_in, token_out)?;
+        Ok(PhoenixPoolClient::new(env, pool_address).simulate_swap(token_in, &amount_in))
+    }
+}
+
+impl DexInterface for PhoenixDex {
+    fn get_price(
+        env: &Env,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+    ) -> Result<i128, crate::errors::Error> {
+        // Output for one whole unit of token_in, fees included
+        let decimals = token::Client::new(env, token_in).decimals();
+        let one = 10_i128.checked_pow(decimals).ok_or(crate::errors::Error::ArithmeticOverflow)?;
+
+        Self::calculate_output(env, pool_address, token_in, token_out, one)
+    }
+
+    fn swap(
+        env: &Env,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+        amount_in: i128,
+        min_amount_out: i128,
+        deadline: u64,
+    ) -> Result<i128, crate::errors::Error> {
+        Self::get_reserves(env, pool_address, token_in, token_out)?;
+
+        let this = env.current_contract_address();
+        let token_out_client = token::Client::new(env, token_out);
+        let balance_before = token_out_client.balance(&this);
+
+        // The pool pulls our input itself
+        authorize_transfer(env, token_in, pool_address, amount_in);
+        PhoenixPoolClient::new(env, pool_address).swap(
+            &this,
+            token_in,
+            &amount_in,
+            &Some(min_amount_out),
+            &None,
+            &Some(deadline),
+            &None,
+        );
+
+        // Trust balances rather than the amount the pool reports
+        let amount_out = token_out_client.balance(&this)
+            .checked_sub(balance_before)
             .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+        if amount_out < min_amount_out {
+            return Err(crate::errors::Error::SlippageExceeded);
+        }
 
         Ok(amount_out)
     }
+
+    fn get_reserves(
+        env: &Env,
+        pool_address: &Address,
+        token_a: &Address,
+        token_b: &Address,
+    ) -> Result<(i128, i128), crate::errors::Error> {
+        let info = PhoenixPoolClient::new(env, pool_address).query_pool_info();
+        let (asset_a, asset_b) = (info.asset_a, info.asset_b);
+
+        if token_a == &asset_a.address && token_b == &asset_b.address {
+            Ok((asset_a.amount, asset_b.amount))
+        } else if token_a == &asset_b.address && token_b == &asset_a.address {
+            Ok((asset_b.amount, asset_a.amount))
+        } else {
+            Err(crate::errors::Error::InvalidTokenAddress)
+        }
+    }
+
+    fn calculate_output(
+        env: &Env,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+        amount_in: i128,
+    ) -> Result<i128, crate::errors::Error> {
+        Ok(Self::simulate(env, pool_address, token_in, token_out, amount_in)?.ask_amount)
+    }
+}
+
+/// Aquarius pool contract interface, shared by every pool kind
+#[contractclient(name = "AquariusPoolClient")]
+#[allow(dead_code)]
+pub trait AquariusPool {
+    fn pool_type(env: Env) -> Symbol;
+    fn get_tokens(env: Env) -> Vec<Address>;
+    fn get_reserves(env: Env) -> Vec<u128>;
+    fn get_fee_fraction(env: Env) -> u32;
+    /// Amplification coefficient (stableswap pools only)
+    fn a(env: Env) -> u128;
+    fn swap(env: Env, user: Address, in_idx: u32, out_idx: u32, in_amount: u128, out_min: u128) -> u128;
+}
+
+/// Aquarius router contract interface
+#[contractclient(name = "AquariusRouterClient")]
+#[allow(dead_code)]
+pub trait AquariusRouter {
+    fn get_pools(env: Env, tokens: Vec<Address>) -> Map<BytesN<32>, Address>;
+    #[allow(clippy::too_many_arguments)]
+    fn swap(
+        env: Env,
+        user: Address,
+        tokens: Vec<Address>,
+        token_in: Address,
+        token_out: Address,
+        pool_index: BytesN<32>,
+        in_amount: u128,
+        out_min: u128,
+    ) -> u128;
+}
+
+/// Pricing formula of an Aquarius pool
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AquariusPoolKind {
+    ConstantProduct,
+    Stable,
 }
 
 /// Aquarius DEX adapter
+///
+/// Pools hold their tokens in sorted order and identify them by index;
+/// reserves and swaps are mapped through those indices.
 pub struct AquariusDex;
 
+impl AquariusDex {
+    /// Detect the pool's pricing formula from its reported type
+    pub fn pool_kind(
+        env: &Env,
+        pool_address: &Address,
+    ) -> Result<AquariusPoolKind, crate::errors::Error> {
+        let pool_type = AquariusPoolClient::new(env, pool_address).pool_type();
+        if pool_type == Symbol::new(env, "constant_product") {
+            Ok(AquariusPoolKind::ConstantProduct)
+        } else if pool_type == Symbol::new(env, "stable") {
+            Ok(AquariusPoolKind::Stable)
+        } else {
+            Err(crate::errors::Error::InvalidPoolAddress)
+        }
+    }
+
+    /// Positions of `token_in` and `token_out` in the pool's token list
+    pub fn token_indices(
+        tokens: &Vec<Address>,
+        token_in: &Address,
+        token_out: &Address,
+    ) -> Result<(u32, u32), crate::errors::Error> {
+        let in_idx = tokens.first_index_of(token_in);
+        let out_idx = tokens.first_index_of(token_out);
+        match (in_idx, out_idx) {
+            (Some(in_idx), Some(out_idx)) if in_idx != out_idx => Ok((in_idx, out_idx)),
+            _ => Err(crate::errors::Error::InvalidTokenAddress),
+        }
+    }
+
+    /// Pool reserves as signed amounts, in the pool's token order
+    fn balances(env: &Env, pool: &AquariusPoolClient) -> Result<Vec<i128>, crate::errors::Error> {
+        let mut balances: Vec<i128> = Vec::new(env);
+        for reserve in pool.get_reserves().iter() {
+            balances.push_back(to_i128(reserve)?);
+        }
+        Ok(balances)
+    }
+
+    /// Stableswap state of a stable pool, with the decimals of its tokens
+    fn stable_pool(
+        env: &Env,
+        pool: &AquariusPoolClient,
+        balances: Vec<i128>,
+        fee_bps: u32,
+    ) -> Result<StablePool, crate::errors::Error> {
+        let mut decimals: Vec<u32> = Vec::new(env);
+        for token in pool.get_tokens().iter() {
+            decimals.push_back(token::Client::new(env, &token).decimals());
+        }
+        Ok(StablePool { balances, decimals, amp: to_i128(pool.a())?, fee_bps })
+    }
+
+    /// Output of `amount_in` at the pool's pre-trade marginal price, after the fee
+    pub fn spot_output(
+        env: &Env,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+        amount_in: i128,
+    ) -> Result<i128, crate::errors::Error> {
+        let pool = AquariusPoolClient::new(env, pool_address);
+        let (in_idx, out_idx) = Self::token_indices(&pool.get_tokens(), token_in, token_out)?;
+        let balances = Self::balances(env, &pool)?;
+        let fee_bps = pool.get_fee_fraction();
+
+        match Self::pool_kind(env, pool_address)? {
+            AquariusPoolKind::ConstantProduct => constant_product_spot_output(
+                amount_in,
+                balances.get_unchecked(in_idx),
+                balances.get_unchecked(out_idx),
+                fee_bps,
+            ),
+            AquariusPoolKind::Stable => {
+                let stable = Self::stable_pool(env, &pool, balances, fee_bps)?;
+                stable_spot_output(env, &stable, in_idx, out_idx, amount_in)
+            }
+        }
+    }
+}
+
 impl DexInterface for AquariusDex {
     fn get_price(
         env: &Env,
@@ -141,9 +504,11 @@ impl DexInterface for AquariusDex {
         token_in: &Address,
         token_out: &Address,
     ) -> Result<i128, crate::errors::Error> {
-        // TODO: Implement Aquarius price fetching
-        // Aquarius supports both constant product and stable swap AMMs
-        Ok(0) // Placeholder
+        // Output for one whole unit of token_in, fees included
+        let decimals = token::Client::new(env, token_in).decimals();
+        let one = 10_i128.checked_pow(decimals).ok_or(crate::errors::Error::ArithmeticOverflow)?;
+
+        Self::calculate_output(env, pool_address, token_in, token_out, one)
     }
 
     fn swap(
@@ -153,9 +518,58 @@ impl DexInterface for AquariusDex {
         token_out: &Address,
         amount_in: i128,
         min_amount_out: i128,
+        deadline: u64,
     ) -> Result<i128, crate::errors::Error> {
-        // TODO: Implement Aquarius swap execution
-        Ok(0) // Placeholder
+        // Aquarius swaps take no deadline, so it is enforced here
+        if env.ledger().timestamp() > deadline {
+            return Err(crate::errors::Error::OpportunityExpired);
+        }
+
+        let router_address = get_dex_router(env, DexType::Aquarius)
+            .ok_or(crate::errors::Error::DexRouterNotSet)?;
+        let router = AquariusRouterClient::new(env, &router_address);
+        let pool = AquariusPoolClient::new(env, pool_address);
+
+        // The router addresses pools by token set and index, not address
+        let tokens = pool.get_tokens();
+        let (in_idx, out_idx) = Self::token_indices(&tokens, token_in, token_out)?;
+        let mut pool_index = None;
+        for (index, address) in router.get_pools(&tokens).iter() {
+            if &address == pool_address {
+                pool_index = Some(index);
+            }
+        }
+        let pool_index = pool_index.ok_or(crate::errors::Error::InvalidPoolAddress)?;
+
+        let this = env.current_contract_address();
+        let token_out_client = token::Client::new(env, token_out);
+        let balance_before = token_out_client.balance(&this);
+
+        // The pool pulls our input while the router calls it on our behalf
+        let in_amount = to_u128(amount_in)?;
+        let out_min = to_u128(min_amount_out.max(0))?;
+        env.authorize_as_current_contract(vec![
+            env,
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: pool_address.clone(),
+                    fn_name: Symbol::new(env, "swap"),
+                    args: (this.clone(), in_idx, out_idx, in_amount, out_min).into_val(env),
+                },
+                sub_invocations: vec![env, transfer_auth_entry(env, token_in, pool_address, amount_in)],
+            }),
+        ]);
+        router.swap(&this, &tokens, token_in, token_out, &pool_index, &in_amount, &out_min);
+
+        // Trust balances rather than the amount the router reports
+        let amount_out = token_out_client.balance(&this)
+            .checked_sub(balance_before)
+            .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+        if amount_out < min_amount_out {
+            return Err(crate::errors::Error::SlippageExceeded);
+        }
+
+        Ok(amount_out)
     }
 
     fn get_reserves(
@@ -164,8 +578,11 @@ impl DexInterface for AquariusDex {
         token_a: &Address,
         token_b: &Address,
     ) -> Result<(i128, i128), crate::errors::Error> {
-        // TODO: Implement Aquarius reserve fetching
-        Ok((0, 0)) // Placeholder
+        let pool = AquariusPoolClient::new(env, pool_address);
+        let (idx_a, idx_b) = Self::token_indices(&pool.get_tokens(), token_a, token_b)?;
+        let balances = Self::balances(env, &pool)?;
+
+        Ok((balances.get_unchecked(idx_a), balances.get_unchecked(idx_b)))
     }
 
     fn calculate_output(
@@ -175,13 +592,673 @@ impl DexInterface for AquariusDex {
         token_out: &Address,
         amount_in: i128,
     ) -> Result<i128, crate::errors::Error> {
-        // TODO: Implement Aquarius output calculation
-        // Aquarius uses constant product (x*y=k) and stable swap formulas
-        Ok(0) // Placeholder
+        let pool = AquariusPoolClient::new(env, pool_address);
+        let (in_idx, out_idx) = Self::token_indices(&pool.get_tokens(), token_in, token_out)?;
+        let balances = Self::balances(env, &pool)?;
+        let fee_bps = pool.get_fee_fraction();
+
+        match Self::pool_kind(env, pool_address)? {
+            AquariusPoolKind::ConstantProduct => constant_product_output(
+                amount_in,
+                balances.get_unchecked(in_idx),
+                balances.get_unchecked(out_idx),
+                fee_bps,
+            ),
+            AquariusPoolKind::Stable => {
+                let stable = Self::stable_pool(env, &pool, balances, fee_bps)?;
+                stable_swap_output(env, &stable, in_idx, out_idx, amount_in)
+            }
+        }
+    }
+}
+
+/// Newton iterations allowed before a stableswap solve is abandoned
+const MAX_NEWTON_ITERATIONS: u32 = 255;
+
+/// Balances and parameters of a stableswap pool
+///
+/// Tokens may have different decimals. The curve is solved on balances
+/// scaled up to the pool's largest decimals, in 256-bit integers so that
+/// deep pools cannot overflow the intermediate products.
+#[derive(Clone)]
+pub struct StablePool {
+    pub balances: Vec<i128>, // Raw token amounts, in the pool's token order
+    pub decimals: Vec<u32>, // Decimals of each token, in the same order
+    pub amp: i128,
+    pub fee_bps: u32,
+}
+
+impl StablePool {
+    /// Balances scaled to a common precision, with the factor applied to each
+    fn scaled(&self, env: &Env) -> Result<(Vec<I256>, Vec<I256>), crate::errors::Error> {
+        if self.decimals.len() != self.balances.len() {
+            return Err(crate::errors::Error::InvalidPoolAddress);
+        }
+        let precision = self.decimals.iter().max().unwrap_or(0);
+
+        let mut xp: Vec<I256> = Vec::new(env);
+        let mut rates: Vec<I256> = Vec::new(env);
+        for (balance, decimals) in self.balances.iter().zip(self.decimals.iter()) {
+            let rate = I256::from_i32(env, 10).pow(precision - decimals);
+            xp.push_back(I256::from_i128(env, balance).mul(&rate));
+            rates.push_back(rate);
+        }
+        Ok((xp, rates))
+    }
+
+    /// Amplification times n^n
+    fn ann(&self, env: &Env) -> I256 {
+        let n = self.balances.len();
+        I256::from_i128(env, self.amp).mul(&I256::from_i128(env, n as i128).pow(n))
+    }
+}
+
+/// Stableswap invariant `D` of the scaled balances `xp`, solving
+/// A*n^n*S + D = A*D*n^n + D^(n+1) / (n^n * prod(x)) by Newton's method
+///
+/// Fails with `SwapFailed` if the iteration does not converge.
+fn stable_invariant(env: &Env, xp: &Vec<I256>, ann: &I256) -> Result<I256, crate::errors::Error> {
+    let zero = I256::from_i32(env, 0);
+    let one = I256::from_i32(env, 1);
+    let n = I256::from_i128(env, xp.len() as i128);
+    if *ann <= one {
+        return Err(crate::errors::Error::InvalidPoolAddress);
+    }
+
+    let mut sum = zero.clone();
+    for x in xp.iter() {
+        if x <= zero {
+            return Err(crate::errors::Error::InsufficientLiquidity);
+        }
+        sum = sum.add(&x);
+    }
+
+    let mut d = sum.clone();
+    for _ in 0..MAX_NEWTON_ITERATIONS {
+        // D_P = D^(n+1) / (n^n * prod(x))
+        let mut d_p = d.clone();
+        for x in xp.iter() {
+            d_p = d_p.mul(&d).div(&x.mul(&n));
+        }
+
+        let previous = d.clone();
+        // D = (Ann*S + D_P*n) * D / ((Ann - 1)*D + (n + 1)*D_P)
+        let numerator = ann.mul(&sum).add(&d_p.mul(&n));
+        let denominator = ann.sub(&one).mul(&d).add(&d_p.mul(&n.add(&one)));
+        d = numerator.mul(&d).div(&denominator);
+
+        if within_one(env, &d, &previous) {
+            return Ok(d);
+        }
+    }
+
+    Err(crate::errors::Error::SwapFailed)
+}
+
+/// Stableswap output for `amount_in`, with the pool fee taken from the output
+pub fn stable_swap_output(
+    env: &Env,
+    pool: &StablePool,
+    in_idx: u32,
+    out_idx: u32,
+    amount_in: i128,
+) -> Result<i128, crate::errors::Error> {
+    let (xp, rates) = pool.scaled(env)?;
+    let ann = pool.ann(env);
+    let d = stable_invariant(env, &xp, &ann)?;
+    let n = I256::from_i128(env, xp.len() as i128);
+    let dx = I256::from_i128(env, amount_in).mul(&rates.get_unchecked(in_idx));
+
+    // Solve y^2 + (b - D)*y = c for the new balance of token_out, where
+    // c = D^(n+1) / (n^n * prod(x_j) * Ann) and b = S' + D/Ann over j != out
+    let mut c = d.clone();
+    let mut sum = I256::from_i32(env, 0);
+    for (i, balance) in xp.iter().enumerate() {
+        let i = i as u32;
+        let x = if i == in_idx {
+            balance.add(&dx)
+        } else if i == out_idx {
+            continue;
+        } else {
+            balance
+        };
+        sum = sum.add(&x);
+        c = c.mul(&d).div(&x.mul(&n));
+    }
+    c = c.mul(&d).div(&ann.mul(&n));
+    let b = sum.add(&d.div(&ann));
+
+    let zero = I256::from_i32(env, 0);
+    let mut y = d.clone();
+    let mut converged = false;
+    for _ in 0..MAX_NEWTON_ITERATIONS {
+        let previous = y.clone();
+        // y = (y^2 + c) / (2y + b - D)
+        let numerator = y.mul(&y).add(&c);
+        let denominator = y.add(&y).add(&b).sub(&d);
+        if denominator <= zero {
+            return Err(crate::errors::Error::ArithmeticOverflow);
+        }
+        y = numerator.div(&denominator);
+
+        if within_one(env, &y, &previous) {
+            converged = true;
+            break;
+        }
+    }
+    if !converged {
+        return Err(crate::errors::Error::SwapFailed);
+    }
+
+    // Round against the trader and back to token_out's decimals, then
+    // take the fee from the output
+    let dy = xp.get_unchecked(out_idx).sub(&y).sub(&I256::from_i32(env, 1)).div(&rates.get_unchecked(out_idx));
+    let dy = to_narrow(&dy)?.max(0);
+    let fee = dy.checked_mul(pool.fee_bps as i128)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)?
+        / 10000;
+
+    Ok(dy - fee)
+}
+
+/// Stableswap output for `amount_in` at the pool's marginal price, with the
+/// pool fee taken from the output
+///
+/// Differentiating the invariant gives the marginal rate
+/// dy/dx = y * (Ann*x + D_P) / (x * (Ann*y + D_P)), where
+/// D_P = D^(n+1) / (n^n * prod(x)).
+pub fn stable_spot_output(
+    env: &Env,
+    pool: &StablePool,
+    in_idx: u32,
+    out_idx: u32,
+    amount_in: i128,
+) -> Result<i128, crate::errors::Error> {
+    let (xp, rates) = pool.scaled(env)?;
+    let ann = pool.ann(env);
+    let d = stable_invariant(env, &xp, &ann)?;
+    let n = I256::from_i128(env, xp.len() as i128);
+
+    let mut d_p = d.clone();
+    for x in xp.iter() {
+        d_p = d_p.mul(&d).div(&x.mul(&n));
+    }
+
+    let x = xp.get_unchecked(in_idx);
+    let y = xp.get_unchecked(out_idx);
+    let numerator = ann.mul(&x).add(&d_p);
+    let denominator = ann.mul(&y).add(&d_p);
+
+    // dx * y * (Ann*x + D_P) / (x * (Ann*y + D_P)), in two steps to keep
+    // the products in range, then back to token_out's decimals
+    let dx = I256::from_i128(env, amount_in).mul(&rates.get_unchecked(in_idx));
+    let dy = dx.mul(&y).div(&x).mul(&numerator).div(&denominator).div(&rates.get_unchecked(out_idx));
+    let dy = to_narrow(&dy)?;
+    let fee = dy.checked_mul(pool.fee_bps as i128)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)?
+        / 10000;
+
+    Ok(dy - fee)
+}
+
+/// Whether two Newton iterates differ by at most one
+fn within_one(env: &Env, a: &I256, b: &I256) -> bool {
+    let diff = a.sub(b);
+    diff <= I256::from_i32(env, 1) && diff >= I256::from_i32(env, -1)
+}
+
+fn to_narrow(amount: &I256) -> Result<i128, crate::errors::Error> {
+    amount.to_i128().ok_or(crate::errors::Error::ArithmeticOverflow)
+}
+
+/// Comet weighted pool contract interface
+///
+/// Weights, the swap fee and spot prices are fixed-point values scaled by
+/// `COMET_SCALE`.
+#[contractclient(name = "CometPoolClient")]
+#[allow(dead_code)]
+pub trait CometPool {
+    fn get_tokens(env: Env) -> Vec<Address>;
+    fn get_balance(env: Env, token: Address) -> i128;
+    fn get_normalized_weight(env: Env, token: Address) -> i128;
+    fn get_swap_fee(env: Env) -> i128;
+    /// Price of `token_out` in `token_in`, swap fee included
+    fn get_spot_price(env: Env, token_in: Address, token_out: Address) -> i128;
+    /// Returns the amount out and the spot price after the swap
+    fn swap_exact_amount_in(
+        env: Env,
+        token_in: Address,
+        token_amount_in: i128,
+        token_out: Address,
+        min_amount_out: i128,
+        max_price: i128,
+        user: Address,
+    ) -> (i128, i128);
+}
+
+/// Fixed-point scale of Comet weights, fees and prices
+pub const COMET_SCALE: i128 = 10_000_000;
+
+/// Comet DEX adapter
+///
+/// Comet is a Balancer-style weighted pool (used by Blend's backstop), so
+/// prices depend on each token's weight as well as its balance. Swaps go
+/// directly to the pool, bounded by both `min_amount_out` and a
+/// `max_price` on the pool's spot price.
+pub struct CometDex;
+
+/// Comet pool state that prices a swap from `token_in` to `token_out`
+struct CometSide {
+    balance_in: i128,
+    weight_in: i128,
+    balance_out: i128,
+    weight_out: i128,
+    swap_fee: i128,
+}
+
+impl CometDex {
+    /// Pool swap fee, scaled by `COMET_SCALE`
+    pub fn swap_fee(env: &Env, pool_address: &Address) -> i128 {
+        CometPoolClient::new(env, pool_address).get_swap_fee()
+    }
+
+    /// Balances and weights of the two tokens, checked to be in the pool
+    fn side(
+        env: &Env,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+    ) -> Result<CometSide, crate::errors::Error> {
+        let (balance_in, balance_out) = Self::get_reserves(env, pool_address, token_in, token_out)?;
+        let pool = CometPoolClient::new(env, pool_address);
+
+        Ok(CometSide {
+            balance_in,
+            weight_in: pool.get_normalized_weight(token_in),
+            balance_out,
+            weight_out: pool.get_normalized_weight(token_out),
+            swap_fee: pool.get_swap_fee(),
+        })
+    }
+
+    /// Output of `amount_in` at the pool's pre-trade weighted spot price, after the fee
+    pub fn spot_output(
+        env: &Env,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+        amount_in: i128,
+    ) -> Result<i128, crate::errors::Error> {
+        let side = Self::side(env, pool_address, token_in, token_out)?;
+        let spot_price = weighted_spot_price(
+            side.balance_in,
+            side.weight_in,
+            side.balance_out,
+            side.weight_out,
+            side.swap_fee,
+        )?;
+
+        checked_mul_div(amount_in, COMET_SCALE, spot_price)
+    }
+}
+
+impl DexInterface for CometDex {
+    fn get_price(
+        env: &Env,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+    ) -> Result<i128, crate::errors::Error> {
+        // Output for one whole unit of token_in, fees included
+        let decimals = token::Client::new(env, token_in).decimals();
+        let one = 10_i128.checked_pow(decimals).ok_or(crate::errors::Error::ArithmeticOverflow)?;
+
+        Self::calculate_output(env, pool_address, token_in, token_out, one)
+    }
+
+    fn swap(
+        env: &Env,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+        amount_in: i128,
+        min_amount_out: i128,
+        deadline: u64,
+    ) -> Result<i128, crate::errors::Error> {
+        // Comet swaps take no deadline, so it is enforced here
+        if env.ledger().timestamp() > deadline {
+            return Err(crate::errors::Error::OpportunityExpired);
+        }
+
+        let side = Self::side(env, pool_address, token_in, token_out)?;
+        let expected = weighted_output(
+            side.balance_in,
+            side.weight_in,
+            side.balance_out,
+            side.weight_out,
+            amount_in,
+            side.swap_fee,
+        )?;
+        if expected < min_amount_out {
+            return Err(crate::errors::Error::SlippageExceeded);
+        }
+
+        // The pool's price may move against us as far as the output may
+        let spot_after = weighted_spot_price(
+            side.balance_in.checked_add(amount_in).ok_or(crate::errors::Error::ArithmeticOverflow)?,
+            side.weight_in,
+            side.balance_out.checked_sub(expected).ok_or(crate::errors::Error::ArithmeticOverflow)?,
+            side.weight_out,
+            side.swap_fee,
+        )?;
+        let max_price = if min_amount_out > 0 {
+            checked_mul_div(spot_after, expected, min_amount_out)?
+                .checked_add(1)
+                .ok_or(crate::errors::Error::ArithmeticOverflow)?
+        } else {
+            i128::MAX
+        };
+
+        let this = env.current_contract_address();
+        let token_out_client = token::Client::new(env, token_out);
+        let balance_before = token_out_client.balance(&this);
+
+        // The pool pulls our input itself
+        authorize_transfer(env, token_in, pool_address, amount_in);
+        CometPoolClient::new(env, pool_address).swap_exact_amount_in(
+            token_in,
+            &amount_in,
+            token_out,
+            &min_amount_out.max(0),
+            &max_price,
+            &this,
+        );
+
+        // Trust balances rather than the amount the pool reports
+        let amount_out = token_out_client.balance(&this)
+            .checked_sub(balance_before)
+            .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+        if amount_out < min_amount_out {
+            return Err(crate::errors::Error::SlippageExceeded);
+        }
+
+        Ok(amount_out)
+    }
+
+    fn get_reserves(
+        env: &Env,
+        pool_address: &Address,
+        token_a: &Address,
+        token_b: &Address,
+    ) -> Result<(i128, i128), crate::errors::Error> {
+        let pool = CometPoolClient::new(env, pool_address);
+        let tokens = pool.get_tokens();
+        if token_a == token_b || !tokens.contains(token_a) || !tokens.contains(token_b) {
+            return Err(crate::errors::Error::InvalidTokenAddress);
+        }
+
+        Ok((pool.get_balance(token_a), pool.get_balance(token_b)))
+    }
+
+    fn calculate_output(
+        env: &Env,
+        pool_address: &Address,
+        token_in: &Address,
+        token_out: &Address,
+        amount_in: i128,
+    ) -> Result<i128, crate::errors::Error> {
+        let side = Self::side(env, pool_address, token_in, token_out)?;
+        weighted_output(
+            side.balance_in,
+            side.weight_in,
+            side.balance_out,
+            side.weight_out,
+            amount_in,
+            side.swap_fee,
+        )
+    }
+}
+
+/// Internal precision of the weighted-pool math (18 decimals)
+const BONE: i128 = 1_000_000_000_000_000_000;
+
+/// Weighted pools reject swaps of more than half the input balance
+const MAX_IN_RATIO: i128 = BONE / 2;
+
+/// Series terms below this are dropped from fractional powers
+const POW_PRECISION: i128 = BONE / 10_000_000_000;
+
+/// Series terms allowed before a fractional power is abandoned
+const MAX_POW_TERMS: i128 = 255;
+
+/// Spot price of the output token in the input token, scaled by `COMET_SCALE`
+///
+/// (balance_in / weight_in) / (balance_out / weight_out), grossed up by
+/// the swap fee. Weights and `swap_fee` are scaled by `COMET_SCALE`.
+pub fn weighted_spot_price(
+    balance_in: i128,
+    weight_in: i128,
+    balance_out: i128,
+    weight_out: i128,
+    swap_fee: i128,
+) -> Result<i128, crate::errors::Error> {
+    check_weighted_pool(balance_in, weight_in, balance_out, weight_out, swap_fee)?;
+
+    let ratio = checked_mul_div(
+        checked_mul_div(balance_in, weight_out, weight_in)?,
+        COMET_SCALE,
+        balance_out,
+    )?;
+    checked_mul_div(ratio, COMET_SCALE, COMET_SCALE - swap_fee)
+}
+
+/// Output of a weighted-pool swap of `amount_in`, fee taken from the input
+///
+/// balance_out * (1 - (balance_in / (balance_in + amount_in * (1 - fee)))
+/// ^ (weight_in / weight_out)), computed in 18-decimal fixed point.
+pub fn weighted_output(
+    balance_in: i128,
+    weight_in: i128,
+    balance_out: i128,
+    weight_out: i128,
+    amount_in: i128,
+    swap_fee: i128,
+) -> Result<i128, crate::errors::Error> {
+    check_weighted_pool(balance_in, weight_in, balance_out, weight_out, swap_fee)?;
+    if amount_in <= 0 {
+        return Err(crate::errors::Error::InvalidFlashLoanAmount);
+    }
+    if amount_in > fixed_mul(balance_in, MAX_IN_RATIO)? {
+        return Err(crate::errors::Error::InsufficientLiquidity);
+    }
+
+    let weight_ratio = checked_mul_div(weight_in, BONE, weight_out)?;
+    let fee = swap_fee * (BONE / COMET_SCALE);
+    let adjusted_in = fixed_mul(amount_in, BONE - fee)?;
+    let base = fixed_div(
+        balance_in,
+        balance_in.checked_add(adjusted_in).ok_or(crate::errors::Error::ArithmeticOverflow)?,
+    )?;
+    let retained = fixed_pow(base, weight_ratio)?;
+
+    fixed_mul(balance_out, BONE - retained)
+}
+
+fn check_weighted_pool(
+    balance_in: i128,
+    weight_in: i128,
+    balance_out: i128,
+    weight_out: i128,
+    swap_fee: i128,
+) -> Result<(), crate::errors::Error> {
+    if balance_in <= 0 || balance_out <= 0 {
+        return Err(crate::errors::Error::InsufficientLiquidity);
+    }
+    if weight_in <= 0 || weight_out <= 0 {
+        return Err(crate::errors::Error::InvalidPoolAddress);
+    }
+    if !(0..COMET_SCALE).contains(&swap_fee) {
+        return Err(crate::errors::Error::InvalidFee);
+    }
+    Ok(())
+}
+
+/// `a * b` in 18-decimal fixed point, rounded half up
+fn fixed_mul(a: i128, b: i128) -> Result<i128, crate::errors::Error> {
+    a.checked_mul(b)
+        .and_then(|product| product.checked_add(BONE / 2))
+        .map(|product| product / BONE)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)
+}
+
+/// `a / b` in 18-decimal fixed point, rounded half up
+fn fixed_div(a: i128, b: i128) -> Result<i128, crate::errors::Error> {
+    if b == 0 {
+        return Err(crate::errors::Error::ArithmeticOverflow);
+    }
+    a.checked_mul(BONE)
+        .and_then(|numerator| numerator.checked_add(b / 2))
+        .map(|numerator| numerator / b)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)
+}
+
+/// `base ^ exp` for a fixed-point `base` in (0, 2) and fixed-point `exp`
+///
+/// The whole part of the exponent is applied by squaring and the
+/// fractional part by a binomial series.
+fn fixed_pow(base: i128, exp: i128) -> Result<i128, crate::errors::Error> {
+    if base <= 0 || base >= 2 * BONE || exp < 0 {
+        return Err(crate::errors::Error::SwapFailed);
+    }
+
+    let whole = exp / BONE;
+    let remain = exp % BONE;
+
+    let mut result = BONE;
+    let mut square = base;
+    let mut n = whole;
+    while n > 0 {
+        if n % 2 == 1 {
+            result = fixed_mul(result, square)?;
+        }
+        square = fixed_mul(square, square)?;
+        n /= 2;
+    }
+    if remain == 0 {
+        return Ok(result);
+    }
+
+    fixed_mul(result, fixed_pow_fraction(base, remain)?)
+}
+
+/// `base ^ exp` for a fractional `exp` by the series of (1 + x) ^ exp
+fn fixed_pow_fraction(base: i128, exp: i128) -> Result<i128, crate::errors::Error> {
+    let x = (base - BONE).abs();
+    let x_negative = base < BONE;
+
+    let mut term = BONE;
+    let mut sum = BONE;
+    let mut negative = false;
+    let mut k = 1;
+    while term >= POW_PRECISION {
+        if k > MAX_POW_TERMS {
+            return Err(crate::errors::Error::SwapFailed);
+        }
+
+        // term *= (exp - (k - 1)) * x / k
+        let big_k = k * BONE;
+        let c = exp - (big_k - BONE);
+        term = fixed_div(fixed_mul(term, fixed_mul(c.abs(), x)?)?, big_k)?;
+        if term == 0 {
+            break;
+        }
+
+        if x_negative {
+            negative = !negative;
+        }
+        if c < 0 {
+            negative = !negative;
+        }
+        sum = if negative { sum - term } else { sum + term };
+        k += 1;
+    }
+
+    Ok(sum)
+}
+
+fn checked_mul_div(a: i128, b: i128, divisor: i128) -> Result<i128, crate::errors::Error> {
+    a.checked_mul(b)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)?
+        .checked_div(divisor)
+        .ok_or(crate::errors::Error::ArithmeticOverflow)
+}
+
+fn to_i128(amount: u128) -> Result<i128, crate::errors::Error> {
+    i128::try_from(amount).map_err(|_| crate::errors::Error::ArithmeticOverflow)
+}
+
+fn to_u128(amount: i128) -> Result<u128, crate::errors::Error> {
+    u128::try_from(amount).map_err(|_| crate::errors::Error::ArithmeticOverflow)
+}
+
+/// Auth entry for a token `transfer` of `amount` from this contract to `to`
+fn transfer_auth_entry(
+    env: &Env,
+    token: &Address,
+    to: &Address,
+    amount: i128,
+) -> InvokerContractAuthEntry {
+    InvokerContractAuthEntry::Contract(SubContractInvocation {
+        context: ContractContext {
+            contract: token.clone(),
+            fn_name: Symbol::new(env, "transfer"),
+            args: (env.current_contract_address(), to.clone(), amount).into_val(env),
+        },
+        sub_invocations: Vec::new(env),
+    })
+}
+
+/// Pre-authorize a `transfer` of `amount` of `token` from this contract to `to`
+///
+/// Routers pull swap input from the executor in a nested call, which the
+/// executor's own invocation does not authorize.
+pub fn authorize_transfer(env: &Env, token: &Address, to: &Address, amount: i128) {
+    env.authorize_as_current_contract(vec![env, transfer_auth_entry(env, token, to, amount)]);
+}
+
+/// Router the executor swaps through for `dex_type`, if configured
+pub fn get_dex_router(env: &Env, dex_type: DexType) -> Option<Address> {
+    let key = (DEX_ROUTER, dex_type);
+    env.storage().instance().get(&key)
+}
+
+/// Configure the router used for `dex_type` (owner only)
+pub fn set_dex_router(env: &Env, dex_type: DexType, router: &Address) {
+    let key = (DEX_ROUTER, dex_type);
+    env.storage().instance().set(&key, router);
+}
+
+/// Output for one whole unit of `token_in`, routed to the DEX adapter
+pub fn get_dex_price(
+    env: &Env,
+    dex_type: DexType,
+    pool_address: &Address,
+    token_in: &Address,
+    token_out: &Address,
+) -> Result<i128, crate::errors::Error> {
+    match dex_type {
+        DexType::Soroswap => SoroswapDex::get_price(env, pool_address, token_in, token_out),
+        DexType::Phoenix => PhoenixDex::get_price(env, pool_address, token_in, token_out),
+        DexType::Aquarius => AquariusDex::get_price(env, pool_address, token_in, token_out),
+        DexType::Comet => CometDex::get_price(env, pool_address, token_in, token_out),
+        DexType::External(adapter_id) => {
+            ExternalDex::get_price(env, adapter_id, pool_address, token_in, token_out)
+        }
     }
 }
 
 /// Generic DEX executor that routes to the appropriate DEX implementation
+#[allow(clippy::too_many_arguments)]
 pub fn execute_dex_swap(
     env: &Env,
     dex_type: DexType,
@@ -190,6 +1267,7 @@ pub fn execute_dex_swap(
     token_out: &Address,
     amount_in: i128,
     min_amount_out: i128,
+    deadline: u64, // Ledger timestamp, for routers that take one
 ) -> Result<i128, crate::errors::Error> {
     match dex_type {
         DexType::Soroswap => SoroswapDex::swap(
@@ -199,6 +1277,16 @@ pub fn execute_dex_swap(
             token_out,
             amount_in,
             min_amount_out,
+            deadline,
+        ),
+        DexType::Phoenix => PhoenixDex::swap(
+            env,
+            pool_address,
+            token_in,
+            token_out,
+            amount_in,
+            min_amount_out,
+            deadline,
         ),
         DexType::Aquarius => AquariusDex::swap(
             env,
@@ -207,13 +1295,519 @@ pub fn execute_dex_swap(
             token_out,
             amount_in,
             min_amount_out,
+            deadline,
         ),
+        DexType::Comet => CometDex::swap(
+            env,
+            pool_address,
+            token_in,
+            token_out,
+            amount_in,
+            min_amount_out,
+            deadline,
+        ),
+        DexType::External(adapter_id) => ExternalDex::swap(
+            env,
+            adapter_id,
+            pool_address,
+            token_in,
+            token_out,
+            amount_in,
+            min_amount_out,
+            deadline,
+        ),
+    }
+}
+
+/// Quote for a swap together with the pool state it was priced from
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DexQuote {
+    pub amount_out: i128,
+    pub reserve_in: i128,
+    pub reserve_out: i128,
+    pub fee_bps: u32, // Swap fee kept by the pool
+    pub spot_amount_out: i128, // Output at the pre-trade price, after the fee
+}
+
+/// Soroswap pairs charge 0.3% on the input
+const SOROSWAP_FEE_BPS: u32 = 30;
+
+/// Phoenix spot prices are probed with this fraction of the input reserve
+const PHOENIX_SPOT_PROBE_DIVISOR: i128 = 10_000;
+
+/// Generic quote that routes to the appropriate DEX implementation
+pub fn quote_dex_swap(
+    env: &Env,
+    dex_type: DexType,
+    pool_address: &Address,
+    token_in: &Address,
+    token_out: &Address,
+    amount_in: i128,
+) -> Result<DexQuote, crate::errors::Error> {
+    match dex_type {
+        DexType::Soroswap => {
+            let (reserve_in, reserve_out) =
+                SoroswapDex::get_reserves(env, pool_address, token_in, token_out)?;
+            let amount_out =
+                constant_product_output(amount_in, reserve_in, reserve_out, SOROSWAP_FEE_BPS)?;
+            let spot_amount_out =
+                constant_product_spot_output(amount_in, reserve_in, reserve_out, SOROSWAP_FEE_BPS)?;
+            Ok(DexQuote { amount_out, reserve_in, reserve_out, fee_bps: SOROSWAP_FEE_BPS, spot_amount_out })
+        }
+        DexType::Phoenix => {
+            let (reserve_in, reserve_out) =
+                PhoenixDex::get_reserves(env, pool_address, token_in, token_out)?;
+            let simulation =
+                PhoenixDex::simulate(env, pool_address, token_in, token_out, amount_in)?;
+
+            // Phoenix takes its commission from the output
+            let gross = simulation.ask_amount
+                .checked_add(simulation.commission_amount)
+                .ok_or(crate::errors::Error::ArithmeticOverflow)?;
+            let fee_bps = if gross > 0 {
+                (simulation.commission_amount.checked_mul(10000)
+                    .ok_or(crate::errors::Error::ArithmeticOverflow)? / gross) as u32
+            } else {
+                0
+            };
+
+            // Pools may be XYK or stable and do not expose their curve, so the
+            // marginal price comes from simulating a probe of a small slice of
+            // the pool; the real trade's own rate bounds it from below
+            let probe = (reserve_in / PHOENIX_SPOT_PROBE_DIVISOR).clamp(1, amount_in.max(1));
+            let probe_gross = if probe == amount_in {
+                gross
+            } else {
+                let probe_simulation =
+                    PhoenixDex::simulate(env, pool_address, token_in, token_out, probe)?;
+                probe_simulation.ask_amount
+                    .checked_add(probe_simulation.commission_amount)
+                    .ok_or(crate::errors::Error::ArithmeticOverflow)?
+            };
+            let spot_gross = checked_mul_div(probe_gross, amount_in, probe)?.max(gross);
+            let spot_amount_out = spot_gross - spot_gross.checked_mul(fee_bps as i128)
+                .ok_or(crate::errors::Error::ArithmeticOverflow)?
+                / 10000;
+            Ok(DexQuote { amount_out: simulation.ask_amount, reserve_in, reserve_out, fee_bps, spot_amount_out })
+        }
+        DexType::Aquarius => {
+            let (reserve_in, reserve_out) =
+                AquariusDex::get_reserves(env, pool_address, token_in, token_out)?;
+            let amount_out = AquariusDex::calculate_output(
+                env,
+                pool_address,
+                token_in,
+                token_out,
+                amount_in,
+            )?;
+            let fee_bps = AquariusPoolClient::new(env, pool_address).get_fee_fraction();
+            let spot_amount_out =
+                AquariusDex::spot_output(env, pool_address, token_in, token_out, amount_in)?;
+            Ok(DexQuote { amount_out, reserve_in, reserve_out, fee_bps, spot_amount_out })
+        }
+        DexType::Comet => {
+            let (reserve_in, reserve_out) =
+                CometDex::get_reserves(env, pool_address, token_in, token_out)?;
+            let amount_out =
+                CometDex::calculate_output(env, pool_address, token_in, token_out, amount_in)?;
+            let fee_bps = checked_mul_div(CometDex::swap_fee(env, pool_address), 10000, COMET_SCALE)? as u32;
+            let spot_amount_out =
+                CometDex::spot_output(env, pool_address, token_in, token_out, amount_in)?;
+            Ok(DexQuote { amount_out, reserve_in, reserve_out, fee_bps, spot_amount_out })
+        }
+        DexType::External(adapter_id) => {
+            ExternalDex::quote(env, adapter_id, pool_address, token_in, token_out, amount_in)
+        }
+    }
+}
+
+/// Live reserves of a pool, ordered as (`token_in`, `token_out`)
+pub fn get_dex_reserves(
+    env: &Env,
+    dex_type: DexType,
+    pool_address: &Address,
+    token_in: &Address,
+    token_out: &Address,
+) -> Result<(i128, i128), crate::errors::Error> {
+    match dex_type {
+        DexType::Soroswap => SoroswapDex::get_reserves(env, pool_address, token_in, token_out),
+        DexType::Phoenix => PhoenixDex::get_reserves(env, pool_address, token_in, token_out),
+        DexType::Aquarius => AquariusDex::get_reserves(env, pool_address, token_in, token_out),
+        DexType::Comet => CometDex::get_reserves(env, pool_address, token_in, token_out),
+        DexType::External(adapter_id) => {
+            ExternalDex::get_reserves(env, adapter_id, pool_address, token_in, token_out)
+        }
     }
 }
 
 /// Supported DEX types
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum DexType {
     Soroswap,
+    Phoenix,
     Aquarius,
+    Comet,
+    /// Venue served by the adapter contract registered under this id
+    External(u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use crate::errors::Error;
+    use crate::mocks::{create_token, soroswap::{create_pair, MockSoroswapRouter}};
+    use crate::mocks::aquarius::{create_pool, MockAquariusRouter};
+    use crate::mocks::comet::{MockCometPool, MockCometPoolClient};
+    use crate::mocks::phoenix::{MockPhoenixPool, MockPhoenixPoolClient};
+    use crate::FlashLoanExecutorContract;
+
+    #[test]
+    fn test_soroswap_swap_measures_output() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let executor = env.register_contract(None, FlashLoanExecutorContract);
+        let router = env.register_contract(None, MockSoroswapRouter);
+
+        let token_a = create_token(&env, &admin, &[(&executor, 1_000)]);
+        let token_b = create_token(&env, &admin, &[]);
+        // Pair stores token_b first, so reserves must be flipped for a -> b
+        let pair = create_pair(&env, &router, &token_b, &token_a, 200_000, 100_000);
+        let expected = constant_product_output(1_000, 100_000, 200_000, SOROSWAP_FEE_BPS).unwrap();
+
+        env.as_contract(&executor, || {
+            assert_eq!(
+                SoroswapDex::swap(&env, &pair, &token_a, &token_b, 1_000, 0, 0),
+                Err(Error::DexRouterNotSet)
+            );
+            set_dex_router(&env, DexType::Soroswap, &router);
+
+            // A route priced on another pool is rejected before trading
+            let other = Address::generate(&env);
+            assert_eq!(
+                SoroswapDex::swap(&env, &other, &token_a, &token_b, 1_000, 0, 0),
+                Err(Error::InvalidPoolAddress)
+            );
+
+            let amount_out = SoroswapDex::swap(&env, &pair, &token_a, &token_b, 1_000, expected, 0);
+            assert_eq!(amount_out, Ok(expected));
+        });
+
+        assert_eq!(token::Client::new(&env, &token_b).balance(&executor), expected);
+        assert_eq!(token::Client::new(&env, &token_a).balance(&executor), 0);
+        assert_eq!(
+            SoroswapPairClient::new(&env, &pair).get_reserves(),
+            (200_000 - expected, 101_000)
+        );
+    }
+
+    #[test]
+    fn test_constant_product_rejects_empty_pool() {
+        assert_eq!(constant_product_output(1_000, 0, 1_000, 30), Err(Error::InsufficientLiquidity));
+        assert_eq!(constant_product_output(1_000, 1_000, 0, 30), Err(Error::InsufficientLiquidity));
+
+        // 0.3% fee and price impact on a 10% trade
+        assert_eq!(constant_product_output(100, 1_000, 1_000, 30), Ok(90));
+    }
+
+    fn stable_pool(env: &Env, balances: [i128; 2], decimals: [u32; 2], fee_bps: u32) -> StablePool {
+        StablePool {
+            balances: vec![env, balances[0], balances[1]],
+            decimals: vec![env, decimals[0], decimals[1]],
+            amp: 100,
+            fee_bps,
+        }
+    }
+
+    fn invariant(env: &Env, pool: &StablePool) -> Result<I256, Error> {
+        let (xp, _) = pool.scaled(env)?;
+        stable_invariant(env, &xp, &pool.ann(env))
+    }
+
+    #[test]
+    fn test_stable_swap_prices_near_parity() {
+        let env = Env::default();
+        let balanced = stable_pool(&env, [1_000_000_000, 1_000_000_000], [7, 7], 0);
+
+        // A balanced pool's invariant is the sum of its balances
+        assert_eq!(invariant(&env, &balanced), Ok(I256::from_i128(&env, 2_000_000_000)));
+
+        // Much less price impact than x*y=k at the same depth
+        let stable = stable_swap_output(&env, &balanced, 0, 1, 10_000_000).unwrap();
+        let constant = constant_product_output(10_000_000, 1_000_000_000, 1_000_000_000, 0).unwrap();
+        assert!(stable > 9_990_000 && stable < 10_000_000);
+        assert!(constant < 9_910_000);
+
+        // Swapping into the scarce side pays more than parity
+        let skewed = stable_pool(&env, [1_500_000_000, 500_000_000], [7, 7], 0);
+        let to_scarce = stable_swap_output(&env, &skewed, 0, 1, 10_000_000).unwrap();
+        let to_plentiful = stable_swap_output(&env, &skewed, 1, 0, 10_000_000).unwrap();
+        assert!(to_scarce < 10_000_000 && to_plentiful > 10_000_000);
+
+        // Fee comes off the output
+        let with_fee = StablePool { fee_bps: 4, ..balanced.clone() };
+        assert_eq!(stable_swap_output(&env, &with_fee, 0, 1, 10_000_000), Ok(stable - stable * 4 / 10000));
+
+        // The marginal price is parity when balanced and below it towards
+        // the scarce side; a real trade never beats it
+        assert_eq!(stable_spot_output(&env, &balanced, 0, 1, 10_000_000), Ok(10_000_000));
+        assert_eq!(stable_spot_output(&env, &with_fee, 0, 1, 10_000_000), Ok(9_996_000));
+        let spot_to_scarce = stable_spot_output(&env, &skewed, 0, 1, 10_000_000).unwrap();
+        assert!(to_scarce < spot_to_scarce && spot_to_scarce < 10_000_000);
+        let spot_to_plentiful = stable_spot_output(&env, &skewed, 1, 0, 10_000_000).unwrap();
+        assert!(to_plentiful < spot_to_plentiful);
+    }
+
+    #[test]
+    fn test_stable_swap_handles_deep_pools() {
+        let env = Env::default();
+        // D^(n+1) alone is ~1e60 here, far beyond i128
+        let deep = stable_pool(&env, [100_000_000_000_000_000_000, 100_000_000_000_000_000_000], [7, 7], 0);
+
+        assert_eq!(invariant(&env, &deep), Ok(I256::from_i128(&env, 200_000_000_000_000_000_000)));
+        assert_eq!(stable_spot_output(&env, &deep, 0, 1, 100_000_000_000_000_000), Ok(100_000_000_000_000_000));
+        let out = stable_swap_output(&env, &deep, 0, 1, 100_000_000_000_000_000).unwrap();
+        assert!(out > 99_990_000_000_000_000 && out < 100_000_000_000_000_000);
+    }
+
+    #[test]
+    fn test_stable_swap_scales_mixed_decimals() {
+        let env = Env::default();
+        // 1M of a 6-decimal token against 1M of an 18-decimal token
+        let mixed = stable_pool(&env, [1_000_000_000_000, 1_000_000_000_000_000_000_000_000], [6, 18], 0);
+
+        // One unit of either side is worth one unit of the other
+        assert_eq!(stable_spot_output(&env, &mixed, 0, 1, 1_000_000), Ok(1_000_000_000_000_000_000));
+        assert_eq!(stable_spot_output(&env, &mixed, 1, 0, 1_000_000_000_000_000_000), Ok(1_000_000));
+
+        let to_wide = stable_swap_output(&env, &mixed, 0, 1, 1_000_000).unwrap();
+        assert!(to_wide > 999_990_000_000_000_000 && to_wide < 1_000_000_000_000_000_000);
+        // Rounded down to the 6-decimal side
+        assert_eq!(stable_swap_output(&env, &mixed, 1, 0, 1_000_000_000_000_000_000), Ok(999_999));
+    }
+
+    #[test]
+    fn test_aquarius_swaps_through_router_by_index() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let executor = env.register_contract(None, FlashLoanExecutorContract);
+        let router = env.register_contract(None, MockAquariusRouter);
+
+        let usdc = create_token(&env, &admin, &[(&executor, 2_000_000)]);
+        let usdt = create_token(&env, &admin, &[]);
+        let reserves = vec![&env, 1_000_000_000_u128, 1_000_000_000];
+        let sorted = vec![&env, usdc.clone(), usdt.clone()];
+        let volatile = create_pool(&env, &router, "constant_product", &sorted, &reserves, 30, 0);
+        let unknown = create_pool(&env, &router, "weighted", &sorted, &reserves, 30, 0);
+        // Same tokens in the other order, so indices differ from the first pool
+        let reversed = vec![&env, usdt.clone(), usdc.clone()];
+        let stable = create_pool(&env, &router, "stable", &reversed, &reserves, 4, 100);
+
+        let (quote, stable_quote) = env.as_contract(&executor, || {
+            set_dex_router(&env, DexType::Aquarius, &router);
+            assert_eq!(AquariusDex::pool_kind(&env, &stable), Ok(AquariusPoolKind::Stable));
+            assert_eq!(
+                AquariusDex::calculate_output(&env, &unknown, &usdc, &usdt, 1_000),
+                Err(Error::InvalidPoolAddress)
+            );
+
+            let quote =
+                AquariusDex::calculate_output(&env, &volatile, &usdc, &usdt, 1_000_000).unwrap();
+            let amount_out = AquariusDex::swap(&env, &volatile, &usdc, &usdt, 1_000_000, quote, 0);
+            assert_eq!(amount_out, Ok(quote));
+
+            let stable_quote =
+                AquariusDex::calculate_output(&env, &stable, &usdc, &usdt, 1_000_000).unwrap();
+            assert!(stable_quote > quote);
+
+            // Spot output follows the stable curve's parity price, not x*y=k
+            let dex_quote = quote_dex_swap(&env, DexType::Aquarius, &stable, &usdc, &usdt, 1_000_000).unwrap();
+            assert_eq!(dex_quote.spot_amount_out, 999_600);
+            assert!(dex_quote.spot_amount_out - stable_quote < 100);
+            let amount_out = AquariusDex::swap(&env, &stable, &usdc, &usdt, 1_000_000, stable_quote, 0);
+            assert_eq!(amount_out, Ok(stable_quote));
+
+            // Reserves moved on the matching indices of the stable pool
+            assert_eq!(
+                AquariusDex::get_reserves(&env, &stable, &usdc, &usdt),
+                Ok((1_001_000_000, 1_000_000_000 - stable_quote))
+            );
+
+            // The router takes no deadline, so an expired one stops the swap here
+            env.ledger().with_mut(|ledger| ledger.timestamp = 100);
+            assert_eq!(
+                AquariusDex::swap(&env, &volatile, &usdc, &usdt, 1_000, 0, 99),
+                Err(Error::OpportunityExpired)
+            );
+            (quote, stable_quote)
+        });
+
+        assert_eq!(token::Client::new(&env, &usdc).balance(&executor), 0);
+        assert_eq!(token::Client::new(&env, &usdt).balance(&executor), quote + stable_quote);
+    }
+
+    #[test]
+    fn test_phoenix_quotes_and_swaps_on_the_pool() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let executor = env.register_contract(None, FlashLoanExecutorContract);
+
+        let xlm = create_token(&env, &admin, &[(&executor, 1_000_000)]);
+        let usdc = create_token(&env, &admin, &[]);
+        let pool = env.register_contract(None, MockPhoenixPool);
+        MockPhoenixPoolClient::new(&env, &pool).init(&xlm, &usdc, &100_000_000, &50_000_000, &30, &0);
+        token::StellarAssetClient::new(&env, &usdc).mint(&pool, &50_000_000);
+
+        // Commission comes off the output, so the quote is net of the pool fee
+        let gross = constant_product_output(1_000_000, 100_000_000, 50_000_000, 0).unwrap();
+        let quote = quote_dex_swap(&env, DexType::Phoenix, &pool, &xlm, &usdc, 1_000_000).unwrap();
+        let amount_out = quote.amount_out;
+        assert_eq!(amount_out, gross - gross * 30 / 10000);
+        assert_eq!((quote.reserve_in, quote.reserve_out), (100_000_000, 50_000_000));
+
+        // Tokens the pool does not hold are rejected before it is asked
+        let other = Address::generate(&env);
+        assert_eq!(
+            PhoenixDex::calculate_output(&env, &pool, &xlm, &other, 1_000),
+            Err(Error::InvalidTokenAddress)
+        );
+
+        env.as_contract(&executor, || {
+            let received = PhoenixDex::swap(&env, &pool, &xlm, &usdc, 1_000_000, amount_out, 0);
+            assert_eq!(received, Ok(amount_out));
+        });
+
+        assert_eq!(token::Client::new(&env, &usdc).balance(&executor), amount_out);
+        assert_eq!(
+            PhoenixDex::get_reserves(&env, &pool, &usdc, &xlm),
+            Ok((50_000_000 - amount_out, 101_000_000))
+        );
+    }
+
+    #[test]
+    fn test_phoenix_stable_pool_quotes_its_own_curve() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let executor = env.register_contract(None, FlashLoanExecutorContract);
+
+        let usdc = create_token(&env, &admin, &[(&executor, 10_000_000)]);
+        let usdt = create_token(&env, &admin, &[]);
+        let pool = env.register_contract(None, MockPhoenixPool);
+        MockPhoenixPoolClient::new(&env, &pool).init(&usdc, &usdt, &1_500_000_000, &500_000_000, &30, &100);
+        token::StellarAssetClient::new(&env, &usdt).mint(&pool, &500_000_000);
+
+        // A skewed stable pool still trades near parity, where x*y=k would
+        // price usdt at three times usdc
+        let quote = quote_dex_swap(&env, DexType::Phoenix, &pool, &usdc, &usdt, 10_000_000).unwrap();
+        let constant_spot =
+            constant_product_spot_output(10_000_000, 1_500_000_000, 500_000_000, 30).unwrap();
+        assert!(quote.amount_out > 9_000_000 && quote.amount_out < 10_000_000);
+        assert!(constant_spot < 3_400_000);
+
+        // The marginal price follows the stable curve and bounds the trade
+        assert!(quote.spot_amount_out >= quote.amount_out);
+        assert!(quote.spot_amount_out - quote.amount_out < 100_000);
+
+        env.as_contract(&executor, || {
+            let received = PhoenixDex::swap(&env, &pool, &usdc, &usdt, 10_000_000, quote.amount_out, 0);
+            assert_eq!(received, Ok(quote.amount_out));
+        });
+
+        assert_eq!(token::Client::new(&env, &usdt).balance(&executor), quote.amount_out);
+        assert_eq!(
+            PhoenixDex::get_reserves(&env, &pool, &usdc, &usdt),
+            Ok((1_510_000_000, 500_000_000 - quote.amount_out))
+        );
+    }
+
+    #[test]
+    fn test_weighted_math_matches_balancer_formulas() {
+        // Equal weights reduce to x*y=k with the fee on the input
+        let weighted = weighted_output(1_000_000_000, 5_000_000, 1_000_000_000, 5_000_000, 10_000_000, 30_000);
+        let constant = constant_product_output(10_000_000, 1_000_000_000, 1_000_000_000, 30).unwrap();
+        assert!((weighted.unwrap() - constant).abs() <= 1);
+
+        // 80/20 pool priced at parity when balances match the weights
+        let spot = weighted_spot_price(40_000_000_000, 8_000_000, 10_000_000_000, 2_000_000, 0);
+        assert_eq!(spot, Ok(COMET_SCALE));
+
+        // Fractional weight ratio goes through the power series
+        let (balance_in, balance_out, amount_in) = (30_000_000_000_i128, 70_000_000_000_i128, 1_000_000_000);
+        let amount_out = weighted_output(balance_in, 3_000_000, balance_out, 7_000_000, amount_in, 0).unwrap();
+        let base = balance_in as f64 / (balance_in + amount_in) as f64;
+        let expected = balance_out as f64 * (1.0 - base.powf(3.0 / 7.0));
+        assert!((amount_out as f64 - expected).abs() < expected * 1e-7);
+
+        // Comet caps a swap at half the input balance
+        assert_eq!(
+            weighted_output(1_000_000, 5_000_000, 1_000_000, 5_000_000, 500_001, 30_000),
+            Err(Error::InsufficientLiquidity)
+        );
+    }
+
+    #[test]
+    fn test_comet_swap_bounded_by_max_price() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let executor = env.register_contract(None, FlashLoanExecutorContract);
+
+        let blnd = create_token(&env, &admin, &[(&executor, 10_000_000)]);
+        let usdc = create_token(&env, &admin, &[]);
+        let pool = env.register_contract(None, MockCometPool);
+        MockCometPoolClient::new(&env, &pool).init(
+            &vec![&env, blnd.clone(), usdc.clone()],
+            &vec![&env, 8_000_000, 2_000_000],
+            &vec![&env, 40_000_000_000, 10_000_000_000],
+            &30_000,
+        );
+        token::StellarAssetClient::new(&env, &blnd).mint(&pool, &40_000_000_000);
+        token::StellarAssetClient::new(&env, &usdc).mint(&pool, &10_000_000_000);
+
+        let quote = quote_dex_swap(&env, DexType::Comet, &pool, &blnd, &usdc, 10_000_000).unwrap();
+        assert_eq!(quote.fee_bps, 30);
+        assert!(quote.amount_out < 10_000_000);
+
+        // 80/20 weights price the pair at parity, not at the 4:1 balance ratio
+        assert_eq!(quote.spot_amount_out, 9_970_000);
+        assert!(quote.spot_amount_out >= quote.amount_out);
+        assert_ne!(
+            Ok(quote.spot_amount_out),
+            constant_product_spot_output(10_000_000, 40_000_000_000, 10_000_000_000, 30)
+        );
+
+        env.as_contract(&executor, || {
+            assert_eq!(
+                CometDex::swap(&env, &pool, &blnd, &usdc, 10_000_000, quote.amount_out + 1, 0),
+                Err(Error::SlippageExceeded)
+            );
+            let amount_out = CometDex::swap(&env, &pool, &blnd, &usdc, 10_000_000, quote.amount_out, 0);
+            assert_eq!(amount_out, Ok(quote.amount_out));
+
+            // The pool takes no deadline, so an expired one stops the swap here
+            env.ledger().with_mut(|ledger| ledger.timestamp = 100);
+            assert_eq!(
+                CometDex::swap(&env, &pool, &blnd, &usdc, 1_000, 0, 99),
+                Err(Error::OpportunityExpired)
+            );
+        });
+
+        assert_eq!(token::Client::new(&env, &usdc).balance(&executor), quote.amount_out);
+        assert_eq!(
+            CometDex::get_reserves(&env, &pool, &blnd, &usdc),
+            Ok((40_010_000_000, 10_000_000_000 - quote.amount_out))
+        );
+
+        // The pool refuses to trade above the caller's max price
+        let client = MockCometPoolClient::new(&env, &pool);
+        let spot = client.get_spot_price(&blnd, &usdc);
+        token::StellarAssetClient::new(&env, &blnd).mint(&admin, &1_000_000);
+        assert!(client.try_swap_exact_amount_in(&blnd, &1_000_000, &usdc, &0, &(spot - 1), &admin).is_err());
+    }
 }
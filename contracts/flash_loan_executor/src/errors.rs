@@ -52,4 +52,125 @@ pub enum Error {
     
     /// Invalid withdraw amount
     InvalidWithdrawAmount = 16,
+    
+    /// Lender called back without transferring the borrowed tokens
+    FlashLoanNotReceived = 17,
+    
+    /// Flash loan receiver cannot be this contract
+    InvalidReceiver = 18,
+    
+    /// Fee configuration is out of range
+    InvalidFee = 19,
+    
+    /// Lender charged more than the quoted fee for its leg
+    FlashLoanFeeTooHigh = 23,
+    
+    /// Fee tiers are empty, unordered or do not start at zero
+    InvalidFeeSchedule = 24,
+    
+    /// No fee schedule is configured for the lender
+    FeeScheduleNotSet = 25,
+    
+    /// Measured balance change disagrees with the computed profit
+    ProfitMismatch = 26,
+    
+    /// An entry point tried to return while the debt ledger was not empty
+    UnsettledDebt = 27,
+    
+    /// Booked profit cannot pay the flash fee of a skipped route
+    InsufficientProfit = 28,
+    
+    /// Route has fewer than two hops
+    RouteTooShort = 29,
+    
+    /// Route has more hops than `MAX_HOPS`
+    TooManyHops = 30,
+    
+    /// A leg does not start in the token the previous leg ended in
+    RouteNotChained = 31,
+    
+    /// Last leg does not return to the borrowed token
+    RouteNotClosed = 32,
+    
+    /// An intermediate token repeats or equals the borrowed token
+    DuplicateToken = 33,
+    
+    /// The same pool appears more than once in a route
+    DuplicatePool = 34,
+    
+    /// Leg splits are empty, zero or do not add up to 10000 bps
+    InvalidSplit = 35,
+    
+    /// Slippage tolerance above 10000 bps
+    InvalidSlippage = 36,
+    
+    /// Minimum profit above 10000 bps
+    InvalidProfitThreshold = 37,
+    
+    /// Pool is not on the owner's allowlist
+    PoolNotAllowed = 38,
+    
+    /// Token is not on the owner's allowlist
+    TokenNotAllowed = 39,
+    
+    /// Current ledger is past the payload's `valid_until_ledger` or `deadline`
+    OpportunityExpired = 40,
+    
+    /// Current ledger is before the payload's `not_before_ledger`
+    OpportunityNotYetValid = 41,
+    
+    /// A pool's live reserves moved beyond the tolerance from the snapshot
+    ReservesMoved = 42,
+    
+    /// A reserve snapshot names a pool the route does not trade
+    SnapshotPoolNotInRoute = 43,
+    
+    /// Token has no oracle asset mapping
+    OracleAssetNotMapped = 44,
+    
+    /// Oracle has no usable price for the asset
+    OraclePriceUnavailable = 45,
+    
+    /// Oracle price is older than the configured maximum age
+    OraclePriceStale = 46,
+    
+    /// Execution price strays from the oracle price beyond the configured bps
+    PriceDeviationTooHigh = 47,
+    
+    /// No route is registered under the id
+    RouteNotFound = 48,
+    
+    /// Registered route has been disabled by the owner
+    RouteDisabled = 49,
+    
+    /// No router is configured for the route's DEX
+    DexRouterNotSet = 50,
+    
+    /// Reserve tolerance exceeds 10000 bps
+    InvalidReserveTolerance = 51,
+    
+    /// Oracle deviation bound exceeds 10000 bps
+    InvalidOracleDeviation = 52,
+    
+    /// No adapter is registered under the route's external DEX id
+    DexAdapterNotSet = 53,
+}
+
+/// Flash loan and debt errors that do not fit in `Error`
+/// 
+/// A contract error enum is limited to 50 cases and `Error` is full. Entry
+/// points return `Error`, so these are raised with `panic_with_error!` and
+/// reach callers as contract errors with the codes below.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum LoanError {
+    /// A flash loan debt was still open at the end of execution
+    OutstandingDebt = 20,
+    
+    /// The same token was borrowed twice in one multi-asset loan
+    DuplicateFlashLoanAsset = 21,
+    
+    /// Flash loan provider cannot be opened from inside another loan
+    ProviderNotRelayable = 22,
 }
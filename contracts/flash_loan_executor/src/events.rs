@@ -1,4 +1,5 @@
-use soroban_sdk::{Address, Env, Symbol, Vec, symbol_short};
+use soroban_sdk::{Address, Env, Vec, symbol_short};
+use crate::arbitrage::SkipReason;
 
 /// Event emitted when a flash loan is initiated
 pub fn emit_flash_loan_started(
@@ -15,12 +16,11 @@ pub fn emit_flash_loan_started(
 pub fn emit_arbitrage_executed(
     env: &Env,
     route_id: u32,
-    dex_a: Address,
-    dex_b: Address,
+    pools: Vec<Address>,
     profit: i128,
 ) {
     let topics = (symbol_short!("arb_exec"), route_id);
-    let data = (dex_a, dex_b, profit);
+    let data = (pools, profit);
     env.events().publish(topics, data);
 }
 
@@ -74,14 +74,87 @@ pub fn emit_profit_withdrawn(
     env.events().publish(topics, amount);
 }
 
-/// Event emitted when arbitrage fails
-pub fn emit_arbitrage_failed(
+/// Event emitted when liquidity is deposited into the lending vault
+pub fn emit_liquidity_deposited(
+    env: &Env,
+    depositor: Address,
+    token: Address,
+    amount: i128,
+    shares: i128,
+) {
+    let topics = (symbol_short!("deposit"), depositor, token);
+    let data = (amount, shares);
+    env.events().publish(topics, data);
+}
+
+/// Event emitted when liquidity is withdrawn from the lending vault
+pub fn emit_liquidity_withdrawn(
+    env: &Env,
+    depositor: Address,
+    token: Address,
+    amount: i128,
+    shares: i128,
+) {
+    let topics = (symbol_short!("redeem"), depositor, token);
+    let data = (amount, shares);
+    env.events().publish(topics, data);
+}
+
+/// Event emitted when a soft-fail route is skipped after its pre-trade quote
+pub fn emit_route_skipped(
+    env: &Env,
+    route_id: u32,
+    reason: SkipReason,
+    amount_out: i128,
+    expected_profit: i128,
+    min_profit: i128,
+) {
+    let topics = (symbol_short!("arb_skip"), route_id);
+    let data = (reason, amount_out, expected_profit, min_profit);
+    env.events().publish(topics, data);
+}
+
+/// Event emitted when a batch of routes has run
+pub fn emit_batch_executed(
+    env: &Env,
+    token: Address,
+    executed: u32,
+    skipped: u32,
+    total_profit: i128,
+) {
+    let topics = (symbol_short!("batch"), token);
+    let data = (executed, skipped, total_profit);
+    env.events().publish(topics, data);
+}
+
+/// Event emitted when a route is registered or its legs are updated
+pub fn emit_route_registered(
     env: &Env,
     route_id: u32,
-    reason: Symbol,
+    hops: u32,
+) {
+    let topics = (symbol_short!("rt_reg"), route_id);
+    env.events().publish(topics, hops);
+}
+
+/// Event emitted when a registered route is enabled or disabled
+pub fn emit_route_status_changed(
+    env: &Env,
+    route_id: u32,
+    enabled: bool,
+) {
+    let topics = (symbol_short!("rt_status"), route_id);
+    env.events().publish(topics, enabled);
+}
+
+/// Event emitted when an external DEX adapter is registered or removed
+pub fn emit_dex_adapter_changed(
+    env: &Env,
+    adapter_id: u32,
+    adapter: Option<Address>,
 ) {
-    let topics = (symbol_short!("arb_fail"), route_id);
-    env.events().publish(topics, reason);
+    let topics = (symbol_short!("dex_adpt"), adapter_id);
+    env.events().publish(topics, adapter);
 }
 
 /// Event emitted when contract is paused/unpaused
@@ -0,0 +1,190 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+use crate::errors::Error;
+use crate::flash_loan_provider::bps_fee_round_up;
+
+const FEE_SCHEDULE: &str = "fee_schedule";
+
+/// Fee rate applied from `min_amount` upwards
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeTier {
+    pub min_amount: i128, // Smallest loan this tier applies to
+    pub fee_bps: u32,
+}
+
+/// How a lender prices a flash loan
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FeeModel {
+    /// Same rate for every loan size
+    FlatBps(u32),
+    /// Rate picked by loan size; tiers sorted by `min_amount`, first at 0
+    Tiered(Vec<FeeTier>),
+}
+
+/// Fee schedule configured by the owner for one lender
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeSchedule {
+    pub model: FeeModel,
+    pub min_fee: i128, // Fixed floor charged on every loan
+}
+
+impl FeeSchedule {
+    /// Fee for borrowing `amount`, rounded up so repayment is never underestimated
+    pub fn fee_for(&self, amount: i128) -> Result<i128, Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
+
+        let fee_bps = self.rate_bps(amount)?;
+        let fee = bps_fee_round_up(amount, fee_bps as i128)?;
+        Ok(fee.max(self.min_fee))
+    }
+
+    /// Proportional rate charged on a loan of `amount`, ignoring the minimum fee
+    pub fn rate_bps(&self, amount: i128) -> Result<u32, Error> {
+        let fee_bps = match &self.model {
+            FeeModel::FlatBps(fee_bps) => *fee_bps,
+            FeeModel::Tiered(tiers) => {
+                // Highest tier whose threshold the loan reaches
+                let mut fee_bps = None;
+                for tier in tiers.iter() {
+                    if tier.min_amount > amount {
+                        break;
+                    }
+                    fee_bps = Some(tier.fee_bps);
+                }
+                fee_bps.ok_or(Error::InvalidFeeSchedule)?
+            }
+        };
+        Ok(fee_bps)
+    }
+
+    /// Check rates are within 0-10000 bps and tiers cover every loan size
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.min_fee < 0 {
+            return Err(Error::InvalidFee);
+        }
+
+        match &self.model {
+            FeeModel::FlatBps(fee_bps) => {
+                if *fee_bps > 10000 {
+                    return Err(Error::InvalidFee);
+                }
+            }
+            FeeModel::Tiered(tiers) => {
+                let first = tiers.get(0).ok_or(Error::InvalidFeeSchedule)?;
+                if first.min_amount != 0 {
+                    return Err(Error::InvalidFeeSchedule);
+                }
+
+                let mut previous: Option<i128> = None;
+                for tier in tiers.iter() {
+                    if tier.fee_bps > 10000 {
+                        return Err(Error::InvalidFee);
+                    }
+                    if previous.is_some_and(|min_amount| tier.min_amount <= min_amount) {
+                        return Err(Error::InvalidFeeSchedule);
+                    }
+                    previous = Some(tier.min_amount);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Fee schedule configured for `lender`, if any
+pub fn get_fee_schedule(env: &Env, lender: &Address) -> Option<FeeSchedule> {
+    let key = (FEE_SCHEDULE, lender);
+    env.storage().persistent().get(&key)
+}
+
+/// Store a validated fee schedule for `lender` (owner only)
+pub fn set_fee_schedule(env: &Env, lender: &Address, schedule: &FeeSchedule) -> Result<(), Error> {
+    schedule.validate()?;
+
+    let key = (FEE_SCHEDULE, lender);
+    env.storage().persistent().set(&key, schedule);
+
+    Ok(())
+}
+
+/// Forget the fee schedule for `lender` (owner only)
+pub fn remove_fee_schedule(env: &Env, lender: &Address) {
+    let key = (FEE_SCHEDULE, lender);
+    env.storage().persistent().remove(&key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_flat_fee_rounds_up_with_minimum() {
+        let schedule = FeeSchedule { model: FeeModel::FlatBps(9), min_fee: 0 };
+
+        // 0.09% of 1,000,000 = 900 exactly
+        assert_eq!(schedule.fee_for(1_000_000).unwrap(), 900);
+        // 0.09% of 100 = 0.09, rounded up to 1
+        assert_eq!(schedule.fee_for(100).unwrap(), 1);
+
+        // Small loans pay the floor instead
+        let schedule = FeeSchedule { model: FeeModel::FlatBps(9), min_fee: 50 };
+        assert_eq!(schedule.fee_for(100).unwrap(), 50);
+        assert_eq!(schedule.fee_for(1_000_000).unwrap(), 900);
+    }
+
+    #[test]
+    fn test_tiered_fee_picks_tier_by_size() {
+        let env = Env::default();
+        let schedule = FeeSchedule {
+            model: FeeModel::Tiered(vec![
+                &env,
+                FeeTier { min_amount: 0, fee_bps: 30 },
+                FeeTier { min_amount: 100_000, fee_bps: 10 },
+                FeeTier { min_amount: 1_000_000, fee_bps: 5 },
+            ]),
+            min_fee: 0,
+        };
+        assert_eq!(schedule.validate(), Ok(()));
+
+        assert_eq!(schedule.fee_for(99_999).unwrap(), 300);
+        assert_eq!(schedule.fee_for(100_000).unwrap(), 100);
+        assert_eq!(schedule.fee_for(2_000_000).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_schedules() {
+        let env = Env::default();
+
+        let schedule = FeeSchedule { model: FeeModel::FlatBps(10_001), min_fee: 0 };
+        assert_eq!(schedule.validate(), Err(Error::InvalidFee));
+
+        let schedule = FeeSchedule { model: FeeModel::FlatBps(9), min_fee: -1 };
+        assert_eq!(schedule.validate(), Err(Error::InvalidFee));
+
+        // Tiers must start at zero and strictly increase
+        let schedule = FeeSchedule { model: FeeModel::Tiered(vec![&env]), min_fee: 0 };
+        assert_eq!(schedule.validate(), Err(Error::InvalidFeeSchedule));
+
+        let schedule = FeeSchedule {
+            model: FeeModel::Tiered(vec![&env, FeeTier { min_amount: 10, fee_bps: 5 }]),
+            min_fee: 0,
+        };
+        assert_eq!(schedule.validate(), Err(Error::InvalidFeeSchedule));
+
+        let schedule = FeeSchedule {
+            model: FeeModel::Tiered(vec![
+                &env,
+                FeeTier { min_amount: 0, fee_bps: 5 },
+                FeeTier { min_amount: 0, fee_bps: 3 },
+            ]),
+            min_fee: 0,
+        };
+        assert_eq!(schedule.validate(), Err(Error::InvalidFeeSchedule));
+    }
+}
@@ -1,38 +1,260 @@
-use soroban_sdk::{Address, Env};
-use crate::errors::Error;
+use soroban_sdk::{contractclient, contracttype, panic_with_error, token, Address, Bytes, Env, Map, Vec};
+use crate::errors::{Error, LoanError};
 use crate::events;
+use crate::fee_schedule::get_fee_schedule;
+use crate::vault::LiquidityVault;
+use crate::flash_loan_provider::{
+    provider_creditor, provider_flash_loan, provider_repay, BorrowLeg, FlashLoanProviderType,
+};
+
+/// Standard lender interface (ERC-3156 style)
+///
+/// Soroban forbids a contract from being re-entered while it is already on
+/// the call stack, so the executor can never be the one calling `flash_loan`:
+/// the lender would have to call straight back into it. Instead the lender is
+/// the root of the transaction and the executor only ever runs as the
+/// `receiver` inside the lender's callback.
+#[contractclient(name = "FlashLenderClient")]
+pub trait FlashLender {
+    /// Maximum amount of `token` available for a flash loan
+    fn max_flash_loan(env: Env, token: Address) -> Result<i128, Error>;
+
+    /// Fee charged for borrowing `amount` of `token`
+    fn flash_fee(env: Env, token: Address, amount: i128) -> Result<i128, Error>;
+
+    /// Lend `amount` of `token` to `receiver` and invoke its
+    /// `on_flash_loan` callback. The lender reverts unless it holds at
+    /// least `amount + fee` more than it lent out when the callback returns.
+    fn flash_loan(
+        env: Env,
+        initiator: Address,
+        receiver: Address,
+        token: Address,
+        amount: i128,
+        data: Bytes,
+    ) -> Result<(), Error>;
+}
+
+/// Lender extension for borrowing several assets in one loan
+/// 
+/// Soroban's re-entry rule means a receiver can only ever sit inside one
+/// lender callback at a time, so strategies that need two or three assets
+/// together must get them from a single `flash_loan_multi` call.
+#[contractclient(name = "MultiFlashLenderClient")]
+#[allow(dead_code)]
+pub trait MultiFlashLender {
+    /// Lend `amounts[i]` of `tokens[i]` to `receiver` and invoke its
+    /// `on_flash_loan_multi` callback. Each asset is checked for
+    /// repayment independently when the callback returns.
+    fn flash_loan_multi(
+        env: Env,
+        initiator: Address,
+        receiver: Address,
+        tokens: Vec<Address>,
+        amounts: Vec<i128>,
+        data: Bytes,
+    ) -> Result<(), Error>;
+}
+
+/// Callback interface implemented by flash loan receivers
+///
+/// Called by the lender after the borrowed tokens have been transferred to
+/// the receiver. The receiver must transfer `amount + fee` back to the
+/// lender before returning.
+#[contractclient(name = "FlashLoanReceiverClient")]
+pub trait FlashLoanReceiver {
+    fn on_flash_loan(
+        env: Env,
+        initiator: Address,
+        token: Address,
+        amount: i128,
+        fee: i128,
+        data: Bytes,
+    ) -> Result<(), Error>;
+}
+
+/// Callback interface for multi-asset flash loans
+/// 
+/// `loans` lists every asset advanced with its own fee; the receiver must
+/// return `amount + fee` of each before returning.
+#[contractclient(name = "MultiFlashLoanReceiverClient")]
+pub trait MultiFlashLoanReceiver {
+    fn on_flash_loan_multi(
+        env: Env,
+        initiator: Address,
+        loans: Vec<FlashLoanAsset>,
+        data: Bytes,
+    ) -> Result<(), Error>;
+}
+
+/// Callback of lenders following the `exec_op` receiver standard
+/// 
+/// Blend pools and xycLoans call it after transferring `amount` of `token`,
+/// with `caller` the account that opened the loan. They forward no payload,
+/// so the route is staged beforehand with `stage_flash_loan`.
+#[contractclient(name = "ExecOpReceiverClient")]
+pub trait ExecOpReceiver {
+    fn exec_op(
+        env: Env,
+        caller: Address,
+        token: Address,
+        amount: i128,
+        fee: i128,
+    ) -> Result<(), Error>;
+}
+
+/// Callback a Soroswap pair makes to the recipient of a flash swap
+/// 
+/// `sender` opened the flash swap and `data` is forwarded as given. The
+/// pair names the token it sent since it cannot be queried while it is on
+/// the stack, and checks `k` once the callback returns.
+#[contractclient(name = "SoroswapCalleeClient")]
+pub trait SoroswapCallee {
+    fn soroswap_call(
+        env: Env,
+        sender: Address,
+        token: Address,
+        amount: i128,
+        data: Bytes,
+    ) -> Result<(), Error>;
+}
+
+/// One asset advanced by a lender, as reported in its callback
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlashLoanAsset {
+    pub token: Address,
+    pub amount: i128,
+    pub fee: i128,
+}
+
+const OPEN_DEBTS: &str = "open_debts";
+const OPEN_PRINCIPAL: &str = "open_principal";
+
+/// Open-debt ledger
+/// 
+/// Every debt opened by `FlashLoanManager::begin_flash_loan` is recorded in
+/// temporary storage and cleared by `FlashLoanManager::repay_flash_loan`.
+/// Public entry points finish with `ensure_settled`, so no code path can
+/// return successfully while a lender is still owed.
+pub struct DebtLedger;
+
+impl DebtLedger {
+    /// Record `repay_amount` owed to `pool` in `token`
+    pub fn register(env: &Env, context: &FlashLoanContext) -> Result<(), Error> {
+        let mut debts = Self::open_debts(env);
+        let key = (context.pool.clone(), context.token.clone());
+        let owed = debts.get(key.clone()).unwrap_or(0)
+            .checked_add(context.repay_amount)
+            .ok_or(Error::ArithmeticOverflow)?;
+        debts.set(key, owed);
+        env.storage().temporary().set(&OPEN_DEBTS, &debts);
+        
+        let principal = Self::open_principal(env, &context.token)
+            .checked_add(context.borrowed_amount)
+            .ok_or(Error::ArithmeticOverflow)?;
+        Self::set_open_principal(env, &context.token, principal);
+        Ok(())
+    }
+    
+    /// Remove a repaid debt from the ledger
+    pub fn clear(env: &Env, context: &FlashLoanContext) -> Result<(), Error> {
+        let mut debts = Self::open_debts(env);
+        let key = (context.pool.clone(), context.token.clone());
+        let owed = debts.get(key.clone()).ok_or(Error::UnsettledDebt)?
+            .checked_sub(context.repay_amount)
+            .ok_or(Error::ArithmeticOverflow)?;
+        
+        if owed > 0 {
+            debts.set(key, owed);
+        } else {
+            debts.remove(key);
+        }
+        
+        if debts.is_empty() {
+            env.storage().temporary().remove(&OPEN_DEBTS);
+        } else {
+            env.storage().temporary().set(&OPEN_DEBTS, &debts);
+        }
+        
+        let principal = Self::open_principal(env, &context.token)
+            .checked_sub(context.borrowed_amount)
+            .ok_or(Error::ArithmeticOverflow)?;
+        Self::set_open_principal(env, &context.token, principal);
+        Ok(())
+    }
+    
+    /// Fail if any debt is still open
+    pub fn ensure_settled(env: &Env) -> Result<(), Error> {
+        if !Self::open_debts(env).is_empty() {
+            return Err(Error::UnsettledDebt);
+        }
+        Ok(())
+    }
+    
+    /// Outstanding amounts keyed by (lender, token)
+    pub fn open_debts(env: &Env) -> Map<(Address, Address), i128> {
+        env.storage().temporary().get(&OPEN_DEBTS).unwrap_or(Map::new(env))
+    }
+    
+    /// Principal of the still-open loans in `token`
+    pub fn open_principal(env: &Env, token: &Address) -> i128 {
+        let key = (OPEN_PRINCIPAL, token);
+        env.storage().temporary().get(&key).unwrap_or(0)
+    }
+    
+    fn set_open_principal(env: &Env, token: &Address, principal: i128) {
+        let key = (OPEN_PRINCIPAL, token);
+        if principal > 0 {
+            env.storage().temporary().set(&key, &principal);
+        } else {
+            env.storage().temporary().remove(&key);
+        }
+    }
+}
 
 /// Flash Loan Manager
 /// 
-/// On Soroban, flash loans are simulated using atomic transactions.
-/// The contract temporarily "borrows" tokens from a liquidity pool,
-/// executes arbitrage, and repays within the same transaction.
+/// Tracks the flash loans issued by an external lender while the executor
+/// runs inside the lender's callback, and settles them before control
+/// returns to the lender. Each borrowed asset is an independent debt with
+/// its own fee.
 pub struct FlashLoanManager;
 
 impl FlashLoanManager {
-    /// Request a flash loan from a liquidity pool
+    /// Open a flash loan that the lender has just advanced
     /// 
-    /// This initiates the flash loan process by:
-    /// 1. Validating the loan amount
-    /// 2. Checking pool liquidity
-    /// 3. Transferring tokens from pool to executor
-    /// 4. Recording the debt that must be repaid
-    pub fn request_flash_loan(
+    /// This starts the flash loan bookkeeping by:
+    /// 1. Validating the loan amount and fee
+    /// 2. Confirming the borrowed tokens actually arrived
+    /// 3. Recording the debt that must be repaid in the `DebtLedger`
+    pub fn begin_flash_loan(
         env: &Env,
+        provider: FlashLoanProviderType,
         pool_address: &Address,
         token: &Address,
         amount: i128,
+        fee: i128,
     ) -> Result<FlashLoanContext, Error> {
         // Validate amount
-        if amount <= 0 {
+        if amount <= 0 || fee < 0 {
             return Err(Error::InvalidFlashLoanAmount);
         }
         
-        // Check pool has sufficient liquidity
-        Self::check_pool_liquidity(env, pool_address, token, amount)?;
+        // The lender transfers before calling back, so the funds must be here
+        // on top of what the contract already held
+        let contract_balance = Self::get_token_balance(
+            env,
+            token,
+            &env.current_contract_address(),
+        )?;
+        let received = contract_balance.checked_sub(Self::held_balance(env, token)?)
+            .ok_or(Error::ArithmeticOverflow)?;
+        
+        if received < amount {
+            return Err(Error::FlashLoanNotReceived);
+        }
         
-        // Calculate fee (typically 0.09% or 9 basis points)
-        let fee = Self::calculate_flash_loan_fee(amount)?;
         let repay_amount = amount.checked_add(fee)
             .ok_or(Error::ArithmeticOverflow)?;
         
@@ -44,24 +266,246 @@ impl FlashLoanManager {
             env.current_contract_address(),
         );
         
-        // TODO: Actually transfer tokens from pool
-        // This would call the pool contract's flash loan function
-        // let pool = LiquidityPoolClient::new(env, pool_address);
-        // pool.flash_loan(&token, &amount, &env.current_contract_address());
-        
-        Ok(FlashLoanContext {
+        let context = FlashLoanContext {
+            provider,
             pool: pool_address.clone(),
             token: token.clone(),
             borrowed_amount: amount,
             fee,
             repay_amount,
             is_repaid: false,
+        };
+        DebtLedger::register(env, &context)?;
+        
+        Ok(context)
+    }
+    
+    /// Open one debt per asset advanced in a multi-asset loan
+    /// 
+    /// Each token may appear only once, otherwise a single transfer could
+    /// satisfy the receipt check for several debts; a repeat aborts with
+    /// `LoanError::DuplicateFlashLoanAsset`.
+    pub fn begin_multi_flash_loan(
+        env: &Env,
+        provider: FlashLoanProviderType,
+        pool_address: &Address,
+        loans: &Vec<FlashLoanAsset>,
+    ) -> Result<Vec<FlashLoanContext>, Error> {
+        if loans.is_empty() {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
+        
+        let mut debts: Vec<FlashLoanContext> = Vec::new(env);
+        for loan in loans.iter() {
+            if debts.iter().any(|debt| debt.token == loan.token) {
+                panic_with_error!(env, LoanError::DuplicateFlashLoanAsset);
+            }
+            
+            let context = Self::begin_flash_loan(
+                env,
+                provider,
+                pool_address,
+                &loan.token,
+                loan.amount,
+                loan.fee,
+            )?;
+            debts.push_back(context);
+        }
+        
+        Ok(debts)
+    }
+    
+    /// Find the leg this contract is receiving and authenticate its lender
+    /// 
+    /// Returns the leg's index. The lender named in the leg must be the
+    /// contract calling us back; a direct contract invoker satisfies its
+    /// own auth without a signature.
+    pub fn locate_leg(
+        env: &Env,
+        legs: &Vec<BorrowLeg>,
+        amount: i128,
+        fee: i128,
+    ) -> Result<u32, Error> {
+        let this = env.current_contract_address();
+        
+        // Each contract may appear only once in the chain
+        let mut found: Option<u32> = None;
+        for (i, leg) in legs.iter().enumerate() {
+            if leg.receiver == this {
+                if found.is_some() {
+                    return Err(Error::InvalidReceiver);
+                }
+                found = Some(i as u32);
+            }
+        }
+        
+        let index = found.ok_or(Error::InvalidReceiver)?;
+        let leg = legs.get_unchecked(index);
+        
+        leg.lender.require_auth();
+        
+        if leg.amount != amount {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
+        Self::check_fee(env, &leg, fee)?;
+        
+        Ok(index)
+    }
+    
+    /// Reject a fee above the leg's quoted cap or its lender's schedule
+    fn check_fee(env: &Env, leg: &BorrowLeg, fee: i128) -> Result<(), Error> {
+        if fee > leg.max_fee {
+            return Err(Error::FlashLoanFeeTooHigh);
+        }
+        
+        // Lenders with a configured schedule may not charge above it
+        if let Some(schedule) = get_fee_schedule(env, &leg.lender) {
+            if fee > schedule.fee_for(leg.amount)? {
+                return Err(Error::FlashLoanFeeTooHigh);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Open every leg of an aggregated loan as a separate debt
+    /// 
+    /// Called on the last leg's receiver. Legs paid out to relays are owed
+    /// back to the relay (which repays its own lender) with the fee the relay
+    /// recorded, the last leg is owed to its lender's creditor directly.
+    pub fn begin_aggregated_flash_loan(
+        env: &Env,
+        initiator: &Address,
+        legs: &Vec<BorrowLeg>,
+        token: &Address,
+        fee: i128,
+    ) -> Result<Vec<FlashLoanContext>, Error> {
+        let last_index = legs.len().checked_sub(1).ok_or(Error::InvalidRoute)?;
+        
+        // Each leg's receipt is checked on top of the legs opened before it,
+        // so every relay must have forwarded its leg before we were called
+        let mut debts: Vec<FlashLoanContext> = Vec::new(env);
+        for (i, leg) in legs.iter().enumerate() {
+            let context = if i as u32 == last_index {
+                let creditor = provider_creditor(&leg, initiator);
+                Self::begin_flash_loan(env, leg.provider, &creditor, token, leg.amount, fee)?
+            } else {
+                // The lender calling the relay back is still on the stack and
+                // cannot be queried, so the relay records what it was charged
+                let relay_fee = leg.charged_fee.ok_or(Error::InvalidRoute)?;
+                Self::check_fee(env, &leg, relay_fee)?;
+                
+                // Relays are repaid by plain transfer
+                Self::begin_flash_loan(
+                    env,
+                    FlashLoanProviderType::Erc3156,
+                    &leg.receiver,
+                    token,
+                    leg.amount,
+                    relay_fee,
+                )?
+            };
+            debts.push_back(context);
+        }
+        
+        Ok(debts)
+    }
+    
+    /// Relay one leg of an aggregated loan
+    /// 
+    /// Forwards this leg's funds to the executor running the route, opens
+    /// the next leg from inside the current callback, and once that returns
+    /// repays this leg's lender from what the executor sent back.
+    pub fn relay_flash_loan(
+        env: &Env,
+        initiator: &Address,
+        legs: &Vec<BorrowLeg>,
+        index: u32,
+        token: &Address,
+        fee: i128,
+        data: &Bytes,
+    ) -> Result<(), Error> {
+        let leg = legs.get(index).ok_or(Error::InvalidRoute)?;
+        let next_leg = legs.get(index + 1).ok_or(Error::InvalidRoute)?;
+        let executor = legs.last().ok_or(Error::InvalidRoute)?.receiver;
+        
+        let mut context = Self::begin_flash_loan(
+            env,
+            leg.provider,
+            &provider_creditor(&leg, initiator),
+            token,
+            leg.amount,
+            fee,
+        )?;
+        
+        let token_client = token::Client::new(env, token);
+        token_client.transfer(&env.current_contract_address(), &executor, &leg.amount);
+        
+        provider_flash_loan(env, &next_leg, initiator, token, data)?;
+        
+        Self::repay_flash_loan(env, &mut context)
+    }
+    
+    /// Balance of `token` this contract held before the current loan arrived
+    /// 
+    /// Booked profit, vault liquidity and the principal of loans opened
+    /// earlier in this transaction are all already in the balance, so none
+    /// of them can stand in for funds a lender failed to send.
+    fn held_balance(env: &Env, token: &Address) -> Result<i128, Error> {
+        crate::FlashLoanExecutorContract::get_profit(env, token)
+            .checked_add(LiquidityVault::total_assets(env, token))
+            .and_then(|held| held.checked_add(DebtLedger::open_principal(env, token)))
+            .ok_or(Error::ArithmeticOverflow)
+    }
+    
+    /// Total fees owed across a set of debts
+    pub fn total_fee(debts: &Vec<FlashLoanContext>) -> Result<i128, Error> {
+        debts.iter().try_fold(0i128, |total, debt| {
+            total.checked_add(debt.fee).ok_or(Error::ArithmeticOverflow)
+        })
+    }
+    
+    /// Total principal borrowed across a set of debts
+    pub fn total_borrowed(debts: &Vec<FlashLoanContext>) -> Result<i128, Error> {
+        debts.iter().try_fold(0i128, |total, debt| {
+            total.checked_add(debt.borrowed_amount).ok_or(Error::ArithmeticOverflow)
         })
     }
     
+    /// Repayments still held for lenders that pull them after the callback
+    pub fn awaiting_pull(debts: &Vec<FlashLoanContext>) -> Result<i128, Error> {
+        debts.iter()
+            .filter(|debt| debt.is_repaid && debt.provider.pulls_repayment())
+            .try_fold(0i128, |total, debt| {
+                total.checked_add(debt.repay_amount).ok_or(Error::ArithmeticOverflow)
+            })
+    }
+    
+    /// Repay every open debt
+    pub fn repay_all(
+        env: &Env,
+        debts: &mut Vec<FlashLoanContext>,
+    ) -> Result<(), Error> {
+        for i in 0..debts.len() {
+            let mut context = debts.get_unchecked(i);
+            Self::repay_flash_loan(env, &mut context)?;
+            debts.set(i, context);
+        }
+        
+        Ok(())
+    }
+    
+    /// End-of-execution check: abort with `LoanError::OutstandingDebt` if
+    /// any debt is still outstanding
+    pub fn ensure_all_repaid(env: &Env, debts: &Vec<FlashLoanContext>) {
+        if debts.iter().any(|debt| !debt.is_repaid) {
+            panic_with_error!(env, LoanError::OutstandingDebt);
+        }
+    }
+    
     /// Repay the flash loan with fee
     /// 
-    /// This must be called before the transaction completes.
+    /// This must be called before the callback returns to the lender.
     /// If repayment fails, the entire transaction reverts.
     pub fn repay_flash_loan(
         env: &Env,
@@ -82,15 +526,11 @@ impl FlashLoanManager {
             return Err(Error::RepaymentFailed);
         }
         
-        // TODO: Transfer tokens back to pool
-        // let token_client = TokenClient::new(env, &context.token);
-        // token_client.transfer(
-        //     &env.current_contract_address(),
-        //     &context.pool,
-        //     &context.repay_amount,
-        // );
+        // Settle principal + fee the way this lender expects
+        provider_repay(env, context)?;
         
         context.is_repaid = true;
+        DebtLedger::clear(env, context)?;
         
         // Emit event
         events::emit_flash_loan_repaid(
@@ -103,49 +543,35 @@ impl FlashLoanManager {
         Ok(())
     }
     
-    /// Calculate flash loan fee (0.09% = 9 basis points)
-    fn calculate_flash_loan_fee(amount: i128) -> Result<i128, Error> {
-        amount.checked_mul(9)
-            .ok_or(Error::ArithmeticOverflow)?
-            .checked_div(10000)
-            .ok_or(Error::ArithmeticOverflow)
-    }
-    
-    /// Check if pool has sufficient liquidity
-    fn check_pool_liquidity(
+    /// Calculate the flash loan fee from the lender's configured schedule
+    pub fn calculate_flash_loan_fee(
         env: &Env,
-        pool_address: &Address,
-        token: &Address,
+        lender: &Address,
         amount: i128,
-    ) -> Result<(), Error> {
-        let pool_balance = Self::get_token_balance(env, token, pool_address)?;
-        
-        if pool_balance < amount {
-            return Err(Error::InsufficientLiquidity);
-        }
-        
-        Ok(())
+    ) -> Result<i128, Error> {
+        get_fee_schedule(env, lender)
+            .ok_or(Error::FeeScheduleNotSet)?
+            .fee_for(amount)
     }
     
     /// Get token balance of an address
-    fn get_token_balance(
+    pub fn get_token_balance(
         env: &Env,
         token: &Address,
         address: &Address,
     ) -> Result<i128, Error> {
-        // TODO: Implement token balance check
-        // let token_client = TokenClient::new(env, token);
-        // Ok(token_client.balance(address))
-        
-        Ok(1000000000) // Placeholder: 1B tokens
+        let token_client = token::Client::new(env, token);
+        Ok(token_client.balance(address))
     }
 }
 
 /// Flash loan execution context
-/// Tracks the state of an active flash loan
-#[derive(Clone)]
+/// Tracks the state of a single debt (one token from one lender)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct FlashLoanContext {
-    pub pool: Address,
+    pub provider: FlashLoanProviderType,
+    pub pool: Address, // Creditor: the lender, or the account repaying it for us
     pub token: Address,
     pub borrowed_amount: i128,
     pub fee: i128,
@@ -169,21 +595,37 @@ impl FlashLoanContext {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use crate::fee_schedule::{set_fee_schedule, FeeModel, FeeSchedule};
+    use crate::mocks::create_token;
 
     #[test]
     fn test_flash_loan_fee_calculation() {
-        // 0.09% of 1,000,000 = 900
-        let fee = FlashLoanManager::calculate_flash_loan_fee(1_000_000).unwrap();
-        assert_eq!(fee, 900);
+        let env = Env::default();
+        let executor = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let lender = Address::generate(&env);
         
-        // 0.09% of 100 = 0.09 ≈ 0 (integer division)
-        let fee_small = FlashLoanManager::calculate_flash_loan_fee(100).unwrap();
-        assert_eq!(fee_small, 0);
+        env.as_contract(&executor, || {
+            let result = FlashLoanManager::calculate_flash_loan_fee(&env, &lender, 1_000_000);
+            assert_eq!(result.err(), Some(Error::FeeScheduleNotSet));
+            
+            let schedule = FeeSchedule { model: FeeModel::FlatBps(9), min_fee: 0 };
+            set_fee_schedule(&env, &lender, &schedule).unwrap();
+            
+            // 0.09% of 1,000,000 = 900
+            let fee = FlashLoanManager::calculate_flash_loan_fee(&env, &lender, 1_000_000).unwrap();
+            assert_eq!(fee, 900);
+            
+            // 0.09% of 100 = 0.09, rounded up to 1
+            let fee_small = FlashLoanManager::calculate_flash_loan_fee(&env, &lender, 100).unwrap();
+            assert_eq!(fee_small, 1);
+        });
     }
 
     #[test]
     fn test_net_profit_calculation() {
         let context = FlashLoanContext {
+            provider: FlashLoanProviderType::Erc3156,
             pool: Address::generate(&Env::default()),
             token: Address::generate(&Env::default()),
             borrowed_amount: 1_000_000,
@@ -201,4 +643,152 @@ mod tests {
         // If we have less, net profit is negative
         assert_eq!(context.calculate_net_profit(1_000_000), -900);
     }
+
+    #[test]
+    fn test_begin_flash_loan_requires_received_funds() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let receiver = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let pool = Address::generate(&env);
+        let token = create_token(&env, &pool, &[(&receiver, 1_500)]);
+        
+        env.as_contract(&receiver, || {
+            let provider = FlashLoanProviderType::Erc3156;
+            
+            // Booked profit is not loan money, so only 900 arrived
+            crate::FlashLoanExecutorContract::add_profit(&env, &token, 600);
+            let result = FlashLoanManager::begin_flash_loan(&env, provider, &pool, &token, 1_000, 1);
+            assert_eq!(result.err(), Some(Error::FlashLoanNotReceived));
+            
+            // Nor can one transfer back two loans
+            FlashLoanManager::begin_flash_loan(&env, provider, &pool, &token, 900, 1).unwrap();
+            let result = FlashLoanManager::begin_flash_loan(&env, provider, &pool, &token, 1, 0);
+            assert_eq!(result.err(), Some(Error::FlashLoanNotReceived));
+            
+            let result = FlashLoanManager::begin_flash_loan(&env, provider, &pool, &token, 0, 0);
+            assert_eq!(result.err(), Some(Error::InvalidFlashLoanAmount));
+        });
+    }
+
+    #[test]
+    fn test_repay_flash_loan_transfers_to_pool() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let receiver = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let pool = Address::generate(&env);
+        let token = create_token(&env, &pool, &[(&receiver, 1_010)]);
+        
+        env.as_contract(&receiver, || {
+            let provider = FlashLoanProviderType::Erc3156;
+            let mut context = FlashLoanManager::begin_flash_loan(&env, provider, &pool, &token, 1_000, 9)
+                .unwrap();
+            FlashLoanManager::repay_flash_loan(&env, &mut context).unwrap();
+            assert!(context.is_repaid);
+            
+            // Repaying twice is a no-op
+            FlashLoanManager::repay_flash_loan(&env, &mut context).unwrap();
+        });
+        
+        let token_client = token::Client::new(&env, &token);
+        assert_eq!(token_client.balance(&pool), 1_009);
+        assert_eq!(token_client.balance(&receiver), 1);
+    }
+
+    #[test]
+    fn test_multi_flash_loan_debts_are_independent() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let receiver = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let pool = Address::generate(&env);
+        let usdc = create_token(&env, &pool, &[(&receiver, 1_000)]);
+        let xlm = create_token(&env, &pool, &[(&receiver, 5_000)]);
+        
+        env.as_contract(&receiver, || {
+            let provider = FlashLoanProviderType::Erc3156;
+            let loans = soroban_sdk::vec![
+                &env,
+                FlashLoanAsset { token: usdc.clone(), amount: 1_000, fee: 0 },
+                FlashLoanAsset { token: xlm.clone(), amount: 4_000, fee: 40 },
+            ];
+            let mut debts = FlashLoanManager::begin_multi_flash_loan(&env, provider, &pool, &loans)
+                .unwrap();
+            assert_eq!(debts.len(), 2);
+            
+            FlashLoanManager::repay_all(&env, &mut debts).unwrap();
+            FlashLoanManager::ensure_all_repaid(&env, &debts);
+        });
+        
+        assert_eq!(token::Client::new(&env, &usdc).balance(&pool), 1_000);
+        assert_eq!(token::Client::new(&env, &xlm).balance(&pool), 4_040);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #20)")]
+    fn test_unrepaid_debt_is_outstanding() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let receiver = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let pool = Address::generate(&env);
+        let token = create_token(&env, &pool, &[(&receiver, 1_000)]);
+        
+        env.as_contract(&receiver, || {
+            let provider = FlashLoanProviderType::Erc3156;
+            let debt = FlashLoanManager::begin_flash_loan(&env, provider, &pool, &token, 1_000, 0)
+                .unwrap();
+            FlashLoanManager::ensure_all_repaid(&env, &soroban_sdk::vec![&env, debt]);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #21)")]
+    fn test_multi_flash_loan_rejects_repeated_asset() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let receiver = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let pool = Address::generate(&env);
+        let xlm = create_token(&env, &pool, &[(&receiver, 1_000)]);
+        
+        // The same token cannot back two debts
+        env.as_contract(&receiver, || {
+            let loans = soroban_sdk::vec![
+                &env,
+                FlashLoanAsset { token: xlm.clone(), amount: 500, fee: 0 },
+                FlashLoanAsset { token: xlm.clone(), amount: 500, fee: 0 },
+            ];
+            let provider = FlashLoanProviderType::Erc3156;
+            let _ = FlashLoanManager::begin_multi_flash_loan(&env, provider, &pool, &loans);
+        });
+    }
+
+    #[test]
+    fn test_debt_ledger_tracks_open_debts() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let receiver = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let pool_a = Address::generate(&env);
+        let pool_b = Address::generate(&env);
+        let token = create_token(&env, &pool_a, &[(&receiver, 3_000)]);
+        
+        env.as_contract(&receiver, || {
+            let provider = FlashLoanProviderType::Erc3156;
+            assert_eq!(DebtLedger::ensure_settled(&env), Ok(()));
+            
+            let mut debt_a = FlashLoanManager::begin_flash_loan(&env, provider, &pool_a, &token, 1_000, 5)
+                .unwrap();
+            let mut debt_b = FlashLoanManager::begin_flash_loan(&env, provider, &pool_b, &token, 1_000, 0)
+                .unwrap();
+            assert_eq!(DebtLedger::open_debts(&env).len(), 2);
+            assert_eq!(DebtLedger::ensure_settled(&env), Err(Error::UnsettledDebt));
+            
+            // Repaying one lender leaves the other open
+            FlashLoanManager::repay_flash_loan(&env, &mut debt_a).unwrap();
+            assert_eq!(DebtLedger::ensure_settled(&env), Err(Error::UnsettledDebt));
+            
+            FlashLoanManager::repay_flash_loan(&env, &mut debt_b).unwrap();
+            assert_eq!(DebtLedger::ensure_settled(&env), Ok(()));
+            
+            // Clearing a debt that was never registered is rejected
+            assert_eq!(DebtLedger::clear(&env, &debt_a), Err(Error::UnsettledDebt));
+        });
+    }
 }
@@ -0,0 +1,711 @@
+use soroban_sdk::{contracttype, panic_with_error, token, Address, Bytes, Env, InvokeError, Vec};
+use crate::errors::{Error, LoanError};
+use crate::dex_interface::SoroswapPairClient;
+use crate::flash_loan::{FlashLenderClient, FlashLoanContext};
+
+/// XycLoans charges 0.08% on every loan
+const XYCLOANS_FEE_BPS: i128 = 8;
+
+/// Generic flash loan provider interface
+/// This trait defines what the executor needs to know about each lender backend:
+/// how much it charges, how much it can lend, and how it expects to be repaid
+pub trait FlashLoanProvider {
+    /// Fee charged for borrowing `amount` of `token`
+    /// Rounded up so repayment is never under-estimated
+    fn flash_fee(
+        env: &Env,
+        lender: &Address,
+        token: &Address,
+        amount: i128,
+    ) -> Result<i128, Error>;
+
+    /// Maximum amount of `token` the lender can advance
+    fn max_flash_loan(
+        env: &Env,
+        lender: &Address,
+        token: &Address,
+    ) -> Result<i128, Error>;
+
+    /// Account the debt on `leg` is repaid to, `initiator` being the
+    /// account that opened the loan
+    fn creditor(leg: &BorrowLeg, initiator: &Address) -> Address;
+
+    /// Hand principal + fee back to the creditor before the callback returns
+    fn repay(
+        env: &Env,
+        context: &FlashLoanContext,
+    ) -> Result<(), Error>;
+
+    /// Open `leg` from inside another lender's callback (used by relays)
+    fn flash_loan(
+        env: &Env,
+        leg: &BorrowLeg,
+        initiator: &Address,
+        token: &Address,
+        data: &Bytes,
+    ) -> Result<(), Error>;
+}
+
+/// Generic ERC-3156 style lender (see `FlashLender`)
+pub struct Erc3156Provider;
+
+impl FlashLoanProvider for Erc3156Provider {
+    fn flash_fee(
+        env: &Env,
+        lender: &Address,
+        token: &Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        lender_answer(FlashLenderClient::new(env, lender).try_flash_fee(token, &amount))
+    }
+
+    fn max_flash_loan(
+        env: &Env,
+        lender: &Address,
+        token: &Address,
+    ) -> Result<i128, Error> {
+        lender_answer(FlashLenderClient::new(env, lender).try_max_flash_loan(token))
+    }
+
+    fn creditor(leg: &BorrowLeg, _initiator: &Address) -> Address {
+        leg.lender.clone()
+    }
+
+    fn repay(
+        env: &Env,
+        context: &FlashLoanContext,
+    ) -> Result<(), Error> {
+        // Lender verifies its balance once the callback returns
+        transfer_to_lender(env, context);
+        Ok(())
+    }
+
+    fn flash_loan(
+        env: &Env,
+        leg: &BorrowLeg,
+        initiator: &Address,
+        token: &Address,
+        data: &Bytes,
+    ) -> Result<(), Error> {
+        FlashLenderClient::new(env, &leg.lender)
+            .flash_loan(initiator, &leg.receiver, token, &leg.amount, data);
+        Ok(())
+    }
+}
+
+/// Blend lending pool flash loans
+/// 
+/// Blend books the loan as a liability of the account that opened it
+/// (`from` in `submit_with_flash_loan`) and calls back `exec_op`. That
+/// account repays the pool with a `Repay` request in the same submit, out
+/// of its own balance, so the receiver hands the principal to it.
+pub struct BlendProvider;
+
+impl FlashLoanProvider for BlendProvider {
+    fn flash_fee(
+        _env: &Env,
+        _lender: &Address,
+        _token: &Address,
+        _amount: i128,
+    ) -> Result<i128, Error> {
+        // Blend does not charge for flash loans repaid in the same call
+        Ok(0)
+    }
+
+    fn max_flash_loan(
+        env: &Env,
+        lender: &Address,
+        token: &Address,
+    ) -> Result<i128, Error> {
+        // Unborrowed reserve liquidity sits in the pool contract
+        Ok(token::Client::new(env, token).balance(lender))
+    }
+
+    fn creditor(_leg: &BorrowLeg, initiator: &Address) -> Address {
+        initiator.clone()
+    }
+
+    fn repay(
+        env: &Env,
+        context: &FlashLoanContext,
+    ) -> Result<(), Error> {
+        transfer_to_lender(env, context);
+        Ok(())
+    }
+
+    fn flash_loan(
+        env: &Env,
+        _leg: &BorrowLeg,
+        _initiator: &Address,
+        _token: &Address,
+        _data: &Bytes,
+    ) -> Result<(), Error> {
+        // Only the bot opens these; they must be the outermost leg
+        panic_with_error!(env, LoanError::ProviderNotRelayable)
+    }
+}
+
+/// XycLoans single-asset lending pools
+/// 
+/// The pool calls back `exec_op` and pulls principal + fee with
+/// `transfer_from` once the callback returns.
+pub struct XycLoansProvider;
+
+impl FlashLoanProvider for XycLoansProvider {
+    fn flash_fee(
+        _env: &Env,
+        _lender: &Address,
+        _token: &Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        bps_fee_round_up(amount, XYCLOANS_FEE_BPS)
+    }
+
+    fn max_flash_loan(
+        env: &Env,
+        lender: &Address,
+        token: &Address,
+    ) -> Result<i128, Error> {
+        Ok(token::Client::new(env, token).balance(lender))
+    }
+
+    fn creditor(leg: &BorrowLeg, _initiator: &Address) -> Address {
+        leg.lender.clone()
+    }
+
+    fn repay(
+        env: &Env,
+        context: &FlashLoanContext,
+    ) -> Result<(), Error> {
+        // The repayment stays here until the pool pulls it
+        let token_client = token::Client::new(env, &context.token);
+        token_client.approve(
+            &env.current_contract_address(),
+            &context.pool,
+            &context.repay_amount,
+            &env.ledger().sequence(),
+        );
+        Ok(())
+    }
+
+    fn flash_loan(
+        env: &Env,
+        _leg: &BorrowLeg,
+        _initiator: &Address,
+        _token: &Address,
+        _data: &Bytes,
+    ) -> Result<(), Error> {
+        // Only the bot opens these; they must be the outermost leg
+        panic_with_error!(env, LoanError::ProviderNotRelayable)
+    }
+}
+
+/// Soroswap pair flash swaps
+///
+/// The pair sends out one of its reserves optimistically and calls back
+/// `soroswap_call`; the swap fee (0.3%) must come back on top of the
+/// principal for `k` to hold.
+pub struct SoroswapFlashSwapProvider;
+
+impl FlashLoanProvider for SoroswapFlashSwapProvider {
+    fn flash_fee(
+        _env: &Env,
+        _lender: &Address,
+        _token: &Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        soroswap_flash_fee(amount)
+    }
+
+    fn max_flash_loan(
+        env: &Env,
+        lender: &Address,
+        token: &Address,
+    ) -> Result<i128, Error> {
+        let pair = SoroswapPairClient::new(env, lender);
+        let (reserve_0, reserve_1) = pair.get_reserves();
+
+        let reserve = if &pair.token_0() == token {
+            reserve_0
+        } else if &pair.token_1() == token {
+            reserve_1
+        } else {
+            return Err(Error::InvalidTokenAddress);
+        };
+
+        // A pair can never be fully drained
+        Ok((reserve - 1).max(0))
+    }
+
+    fn creditor(leg: &BorrowLeg, _initiator: &Address) -> Address {
+        leg.lender.clone()
+    }
+
+    fn repay(
+        env: &Env,
+        context: &FlashLoanContext,
+    ) -> Result<(), Error> {
+        transfer_to_lender(env, context);
+        Ok(())
+    }
+
+    fn flash_loan(
+        env: &Env,
+        _leg: &BorrowLeg,
+        _initiator: &Address,
+        _token: &Address,
+        _data: &Bytes,
+    ) -> Result<(), Error> {
+        // Only the bot opens these; they must be the outermost leg
+        panic_with_error!(env, LoanError::ProviderNotRelayable)
+    }
+}
+
+/// Fee a Soroswap pair takes on a flash swap of `amount`
+/// 
+/// It only depends on the amount, so the pair need not be queried; it
+/// cannot be while it is calling us back.
+pub fn soroswap_flash_fee(amount: i128) -> Result<i128, Error> {
+    // repay * 997 / 1000 >= amount  =>  fee = ceil(amount * 3 / 997)
+    let numerator = amount.checked_mul(3)
+        .ok_or(Error::ArithmeticOverflow)?;
+    ceil_div(numerator, 997)
+}
+
+/// Routes a fee quote to the appropriate provider implementation
+pub fn provider_flash_fee(
+    env: &Env,
+    provider: FlashLoanProviderType,
+    lender: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<i128, Error> {
+    match provider {
+        FlashLoanProviderType::Erc3156 => Erc3156Provider::flash_fee(env, lender, token, amount),
+        FlashLoanProviderType::Blend => BlendProvider::flash_fee(env, lender, token, amount),
+        FlashLoanProviderType::XycLoans => XycLoansProvider::flash_fee(env, lender, token, amount),
+        FlashLoanProviderType::SoroswapFlashSwap => {
+            SoroswapFlashSwapProvider::flash_fee(env, lender, token, amount)
+        }
+    }
+}
+
+/// Routes a liquidity query to the appropriate provider implementation
+pub fn provider_max_flash_loan(
+    env: &Env,
+    provider: FlashLoanProviderType,
+    lender: &Address,
+    token: &Address,
+) -> Result<i128, Error> {
+    match provider {
+        FlashLoanProviderType::Erc3156 => Erc3156Provider::max_flash_loan(env, lender, token),
+        FlashLoanProviderType::Blend => BlendProvider::max_flash_loan(env, lender, token),
+        FlashLoanProviderType::XycLoans => XycLoansProvider::max_flash_loan(env, lender, token),
+        FlashLoanProviderType::SoroswapFlashSwap => {
+            SoroswapFlashSwapProvider::max_flash_loan(env, lender, token)
+        }
+    }
+}
+
+/// Routes the choice of creditor to the appropriate provider implementation
+pub fn provider_creditor(leg: &BorrowLeg, initiator: &Address) -> Address {
+    match leg.provider {
+        FlashLoanProviderType::Erc3156 => Erc3156Provider::creditor(leg, initiator),
+        FlashLoanProviderType::Blend => BlendProvider::creditor(leg, initiator),
+        FlashLoanProviderType::XycLoans => XycLoansProvider::creditor(leg, initiator),
+        FlashLoanProviderType::SoroswapFlashSwap => SoroswapFlashSwapProvider::creditor(leg, initiator),
+    }
+}
+
+/// Routes repayment to the appropriate provider implementation
+pub fn provider_repay(
+    env: &Env,
+    context: &FlashLoanContext,
+) -> Result<(), Error> {
+    match context.provider {
+        FlashLoanProviderType::Erc3156 => Erc3156Provider::repay(env, context),
+        FlashLoanProviderType::Blend => BlendProvider::repay(env, context),
+        FlashLoanProviderType::XycLoans => XycLoansProvider::repay(env, context),
+        FlashLoanProviderType::SoroswapFlashSwap => SoroswapFlashSwapProvider::repay(env, context),
+    }
+}
+
+/// Routes a relay's request to open the next leg
+pub fn provider_flash_loan(
+    env: &Env,
+    leg: &BorrowLeg,
+    initiator: &Address,
+    token: &Address,
+    data: &Bytes,
+) -> Result<(), Error> {
+    match leg.provider {
+        FlashLoanProviderType::Erc3156 => Erc3156Provider::flash_loan(env, leg, initiator, token, data),
+        FlashLoanProviderType::Blend => BlendProvider::flash_loan(env, leg, initiator, token, data),
+        FlashLoanProviderType::XycLoans => XycLoansProvider::flash_loan(env, leg, initiator, token, data),
+        FlashLoanProviderType::SoroswapFlashSwap => {
+            SoroswapFlashSwapProvider::flash_loan(env, leg, initiator, token, data)
+        }
+    }
+}
+
+/// Quote every candidate source and return the cheapest one able to lend `amount`
+pub fn cheapest_source(
+    env: &Env,
+    sources: &soroban_sdk::Vec<FlashLoanSource>,
+    token: &Address,
+    amount: i128,
+) -> Result<FlashLoanQuote, Error> {
+    let mut best: Option<FlashLoanQuote> = None;
+
+    for source in sources.iter() {
+        // A source that cannot be quoted is skipped, not fatal to the others
+        let Ok(max_amount) = provider_max_flash_loan(env, source.provider, &source.lender, token) else {
+            continue;
+        };
+        if max_amount < amount {
+            continue;
+        }
+
+        let Ok(fee) = provider_flash_fee(env, source.provider, &source.lender, token, amount) else {
+            continue;
+        };
+        if best.as_ref().is_none_or(|quote| fee < quote.fee) {
+            best = Some(FlashLoanQuote {
+                provider: source.provider,
+                lender: source.lender.clone(),
+                amount,
+                fee,
+                max_amount,
+            });
+        }
+    }
+
+    best.ok_or(Error::InsufficientLiquidity)
+}
+
+/// Split `amount` across several sources when no single lender can cover it
+/// 
+/// Fills from the lowest fee rate upwards, taking as much as each lender
+/// can advance. Each returned quote is one leg of an aggregated loan.
+pub fn plan_aggregated_borrow(
+    env: &Env,
+    sources: &Vec<FlashLoanSource>,
+    token: &Address,
+    amount: i128,
+) -> Result<Vec<FlashLoanQuote>, Error> {
+    // Quote each source for the most it could contribute
+    let mut candidates: Vec<FlashLoanQuote> = Vec::new(env);
+    for source in sources.iter() {
+        // A source that cannot be quoted is skipped, not fatal to the others
+        let Ok(max_amount) = provider_max_flash_loan(env, source.provider, &source.lender, token) else {
+            continue;
+        };
+        let size = max_amount.min(amount);
+        if size <= 0 {
+            continue;
+        }
+
+        let Ok(fee) = provider_flash_fee(env, source.provider, &source.lender, token, size) else {
+            continue;
+        };
+        candidates.push_back(FlashLoanQuote {
+            provider: source.provider,
+            lender: source.lender.clone(),
+            amount: size,
+            fee,
+            max_amount,
+        });
+    }
+
+    let mut legs: Vec<FlashLoanQuote> = Vec::new(env);
+    let mut remaining = amount;
+    while remaining > 0 {
+        // Cheapest remaining candidate by fee per unit borrowed
+        let mut best: Option<(u32, FlashLoanQuote)> = None;
+        for (i, quote) in candidates.iter().enumerate() {
+            let cheaper = best.as_ref().is_none_or(|(_, current)| {
+                quote.fee.saturating_mul(current.amount) < current.fee.saturating_mul(quote.amount)
+            });
+            if cheaper {
+                best = Some((i as u32, quote));
+            }
+        }
+
+        let (index, quote) = best.ok_or(Error::InsufficientLiquidity)?;
+        candidates.remove(index);
+
+        let size = quote.amount.min(remaining);
+        let fee = provider_flash_fee(env, quote.provider, &quote.lender, token, size)?;
+        legs.push_back(FlashLoanQuote { amount: size, fee, ..quote });
+        remaining -= size;
+    }
+
+    Ok(legs)
+}
+
+/// Unwrap a lender's reply, turning a trap or malformed answer into an error
+///
+/// A lender that cannot answer a query has nothing we could borrow.
+fn lender_answer<T, E>(
+    reply: Result<Result<T, E>, Result<Error, InvokeError>>,
+) -> Result<T, Error> {
+    match reply {
+        Ok(Ok(value)) => Ok(value),
+        Err(Ok(error)) => Err(error),
+        _ => Err(Error::InsufficientLiquidity),
+    }
+}
+
+fn transfer_to_lender(env: &Env, context: &FlashLoanContext) {
+    let token_client = token::Client::new(env, &context.token);
+    token_client.transfer(
+        &env.current_contract_address(),
+        &context.pool,
+        &context.repay_amount,
+    );
+}
+
+pub fn bps_fee_round_up(amount: i128, fee_bps: i128) -> Result<i128, Error> {
+    let numerator = amount.checked_mul(fee_bps)
+        .ok_or(Error::ArithmeticOverflow)?;
+    ceil_div(numerator, 10000)
+}
+
+fn ceil_div(numerator: i128, denominator: i128) -> Result<i128, Error> {
+    numerator.checked_add(denominator - 1)
+        .ok_or(Error::ArithmeticOverflow)?
+        .checked_div(denominator)
+        .ok_or(Error::ArithmeticOverflow)
+}
+
+/// Supported flash loan providers
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FlashLoanProviderType {
+    Erc3156,
+    Blend,
+    XycLoans,
+    SoroswapFlashSwap,
+}
+
+impl FlashLoanProviderType {
+    /// Whether the lender takes its repayment after the callback returns,
+    /// leaving it in the receiver's balance until then
+    pub fn pulls_repayment(self) -> bool {
+        self == FlashLoanProviderType::XycLoans
+    }
+}
+
+/// A lender the executor may borrow from
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlashLoanSource {
+    pub provider: FlashLoanProviderType,
+    pub lender: Address,
+}
+
+/// Fee and available liquidity reported by a source
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlashLoanQuote {
+    pub provider: FlashLoanProviderType,
+    pub lender: Address,
+    pub amount: i128, // Amount the fee was quoted for
+    pub fee: i128,
+    pub max_amount: i128,
+}
+
+/// One lender's share of an aggregated flash loan
+/// 
+/// A receiver can only sit inside one lender callback at a time, so when a
+/// loan is split across lenders every leg except the last is paid out to a
+/// relay: another deployment of this contract that forwards the funds to
+/// the last leg's receiver and opens the next leg from inside its callback.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BorrowLeg {
+    pub provider: FlashLoanProviderType,
+    pub lender: Address,
+    pub receiver: Address, // Relay, or the executor running the route for the last leg
+    pub amount: i128,
+    pub max_fee: i128, // Fee quoted off-chain; the lender may not charge more
+    pub charged_fee: Option<i128>, // Fee the relay was charged, recorded by the relay before forwarding
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::vec;
+    use crate::mocks::create_token;
+    use crate::mocks::flash_lender::{MockFlashLender, MockFlashLenderClient};
+    use crate::mocks::soroswap::{MockSoroswapPair, MockSoroswapPairClient};
+
+    #[test]
+    fn test_fees_round_up() {
+        let env = Env::default();
+        let lender = Address::generate(&env);
+        let token = Address::generate(&env);
+
+        // 0.08% of 1,000,000 = 800 exactly
+        assert_eq!(XycLoansProvider::flash_fee(&env, &lender, &token, 1_000_000).unwrap(), 800);
+        // 0.08% of 100 = 0.08, rounded up to 1
+        assert_eq!(XycLoansProvider::flash_fee(&env, &lender, &token, 100).unwrap(), 1);
+
+        // Repaying 1,000,000 + 3,010 keeps k after the 0.3% swap fee
+        let fee = SoroswapFlashSwapProvider::flash_fee(&env, &lender, &token, 1_000_000).unwrap();
+        assert_eq!(fee, 3_010);
+        assert!((1_000_000 + fee) * 997 / 1000 >= 1_000_000);
+    }
+
+    #[test]
+    fn test_cheapest_source_skips_shallow_lenders() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+
+        let cheap_lender = env.register_contract(None, MockFlashLender);
+        MockFlashLenderClient::new(&env, &cheap_lender).init(&1);
+        let pricey_lender = env.register_contract(None, MockFlashLender);
+        MockFlashLenderClient::new(&env, &pricey_lender).init(&30);
+        let free_lender = env.register_contract(None, MockFlashLender);
+        MockFlashLenderClient::new(&env, &free_lender).init(&0);
+        // A pair does not speak the lender interface, so quoting it traps
+        let not_a_lender = env.register_contract(None, MockSoroswapPair);
+
+        let token = create_token(&env, &admin, &[
+            (&cheap_lender, 10_000),
+            (&pricey_lender, 1_000_000),
+            (&free_lender, 100),
+        ]);
+
+        let sources = vec![
+            &env,
+            FlashLoanSource { provider: FlashLoanProviderType::Erc3156, lender: not_a_lender.clone() },
+            FlashLoanSource { provider: FlashLoanProviderType::Erc3156, lender: free_lender.clone() },
+            FlashLoanSource { provider: FlashLoanProviderType::Erc3156, lender: pricey_lender.clone() },
+            FlashLoanSource { provider: FlashLoanProviderType::Erc3156, lender: cheap_lender.clone() },
+        ];
+
+        // Everyone can cover 100; the broken source is skipped and the free lender wins
+        let quote = cheapest_source(&env, &sources, &token, 100).unwrap();
+        assert_eq!(quote.lender, free_lender);
+        assert_eq!(quote.fee, 0);
+
+        // Only the fee-charging lenders can cover 10,000; the 1 bps one wins
+        let quote = cheapest_source(&env, &sources, &token, 10_000).unwrap();
+        assert_eq!(quote.lender, cheap_lender);
+        assert_eq!(quote.fee, 1);
+
+        // Nobody can cover 2,000,000
+        assert_eq!(
+            cheapest_source(&env, &sources, &token, 2_000_000).err(),
+            Some(Error::InsufficientLiquidity)
+        );
+    }
+
+    #[test]
+    fn test_plan_aggregated_borrow_fills_cheapest_first() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+
+        let cheap_lender = env.register_contract(None, MockFlashLender);
+        MockFlashLenderClient::new(&env, &cheap_lender).init(&5);
+        let pricey_lender = env.register_contract(None, MockFlashLender);
+        MockFlashLenderClient::new(&env, &pricey_lender).init(&30);
+        let free_lender = env.register_contract(None, MockFlashLender);
+        MockFlashLenderClient::new(&env, &free_lender).init(&0);
+        // A pair does not speak the lender interface, so quoting it traps
+        let not_a_lender = env.register_contract(None, MockSoroswapPair);
+
+        let token = create_token(&env, &admin, &[
+            (&cheap_lender, 400_000),
+            (&pricey_lender, 1_000_000),
+            (&free_lender, 100_000),
+        ]);
+
+        let sources = vec![
+            &env,
+            FlashLoanSource { provider: FlashLoanProviderType::Erc3156, lender: pricey_lender.clone() },
+            FlashLoanSource { provider: FlashLoanProviderType::Erc3156, lender: not_a_lender.clone() },
+            FlashLoanSource { provider: FlashLoanProviderType::Erc3156, lender: cheap_lender.clone() },
+            FlashLoanSource { provider: FlashLoanProviderType::Erc3156, lender: free_lender.clone() },
+        ];
+
+        // Free liquidity, then the 5 bps lender, then the rest at 30 bps;
+        // the source that cannot be quoted is left out
+        let legs = plan_aggregated_borrow(&env, &sources, &token, 700_000).unwrap();
+        assert_eq!(legs.len(), 3);
+        assert_eq!(legs.get(0).unwrap().lender, free_lender);
+        assert_eq!(legs.get(0).unwrap().amount, 100_000);
+        assert_eq!(legs.get(1).unwrap().lender, cheap_lender);
+        assert_eq!(legs.get(1).unwrap().amount, 400_000);
+        assert_eq!(legs.get(1).unwrap().fee, 200);
+        assert_eq!(legs.get(2).unwrap().lender, pricey_lender);
+        assert_eq!(legs.get(2).unwrap().amount, 200_000);
+        assert_eq!(legs.get(2).unwrap().fee, 600);
+
+        // Combined liquidity is 1,500,000
+        assert_eq!(
+            plan_aggregated_borrow(&env, &sources, &token, 1_500_001).err(),
+            Some(Error::InsufficientLiquidity)
+        );
+    }
+
+    #[test]
+    fn test_soroswap_max_flash_loan_uses_matching_reserve() {
+        let env = Env::default();
+        let token_0 = Address::generate(&env);
+        let token_1 = Address::generate(&env);
+        let pair = env.register_contract(None, MockSoroswapPair);
+        MockSoroswapPairClient::new(&env, &pair).init(&token_0, &token_1, &5_000, &8_000);
+
+        let max = SoroswapFlashSwapProvider::max_flash_loan(&env, &pair, &token_1).unwrap();
+        assert_eq!(max, 7_999);
+
+        let other = Address::generate(&env);
+        assert_eq!(
+            SoroswapFlashSwapProvider::max_flash_loan(&env, &pair, &other).err(),
+            Some(Error::InvalidTokenAddress)
+        );
+    }
+
+    #[test]
+    fn test_blend_is_repaid_through_the_initiator() {
+        let env = Env::default();
+        let initiator = Address::generate(&env);
+        let leg = BorrowLeg {
+            provider: FlashLoanProviderType::Blend,
+            lender: Address::generate(&env),
+            receiver: Address::generate(&env),
+            amount: 1_000,
+            max_fee: 0,
+            charged_fee: None,
+        };
+        assert_eq!(provider_creditor(&leg, &initiator), initiator);
+
+        let leg = BorrowLeg { provider: FlashLoanProviderType::XycLoans, ..leg };
+        assert_eq!(provider_creditor(&leg, &initiator), leg.lender);
+        assert!(FlashLoanProviderType::XycLoans.pulls_repayment());
+        assert!(!FlashLoanProviderType::Blend.pulls_repayment());
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #22)")]
+    fn test_blend_leg_cannot_be_relayed() {
+        let env = Env::default();
+        let leg = BorrowLeg {
+            provider: FlashLoanProviderType::Blend,
+            lender: Address::generate(&env),
+            receiver: Address::generate(&env),
+            amount: 1_000,
+            max_fee: 0,
+            charged_fee: None,
+        };
+        let initiator = Address::generate(&env);
+        let token = Address::generate(&env);
+
+        let _ = provider_flash_loan(&env, &leg, &initiator, &token, &Bytes::new(&env));
+    }
+}
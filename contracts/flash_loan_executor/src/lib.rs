@@ -7,30 +7,73 @@
 //! potentially other DEX protocols.
 //! 
 //! ## How it works:
-//! 1. Contract borrows tokens via flash loan (atomic transaction)
-//! 2. Executes swaps on DEX A (e.g., Soroswap)
-//! 3. Executes reverse swap on DEX B (e.g., Phoenix)
-//! 4. Repays flash loan + fee
-//! 5. Keeps the profit
+//! 1. Bot calls a lender's `flash_loan` with this contract as receiver
+//! 2. Lender transfers the tokens and calls back `on_flash_loan`
+//! 3. Executes swaps on DEX A (e.g., Soroswap)
+//! 4. Executes reverse swap on DEX B (e.g., Phoenix)
+//! 5. Repays flash loan + fee to the lender
+//! 6. Keeps the profit
 //! 
 //! If any step fails, the entire transaction reverts.
+//! 
+//! Blend pools and xycLoans call back `exec_op` instead, running a payload
+//! staged beforehand with `stage_flash_loan`, and Soroswap pairs call back
+//! `soroswap_call` during a flash swap.
+//! 
+//! Soroban forbids re-entry, so a lender can only call back a contract it
+//! was not called from. `execute_flash_loan_arbitrage` is the one exception
+//! to the flow above: it borrows deposited vault liquidity, which needs no
+//! callback, and runs the route directly.
 
 mod errors;
 mod events;
 mod security;
 mod dex_interface;
+mod dex_adapter;
 mod flash_loan;
+mod flash_loan_provider;
+mod fee_schedule;
+mod allowlist;
+mod reserve_guard;
+mod oracle;
 mod arbitrage;
+mod route_registry;
+mod stats;
+mod optimizer;
+mod vault;
+
+#[cfg(test)]
+mod mocks;
 
-use soroban_sdk::{contract, contractimpl, Address, Env, Symbol, Vec, symbol_short};
+use soroban_sdk::{contract, contractimpl, token, vec, xdr::FromXdr, Address, Bytes, Env, Vec};
 use errors::Error;
-use security::{ReentrancyGuard, require_owner, require_not_paused, set_owner};
-use flash_loan::{FlashLoanManager, FlashLoanContext};
-use arbitrage::{ArbitrageExecutor, ArbitrageRoute, DexConfig};
+use security::{
+    ReentrancyGuard, check_deadline, check_ledger_window, require_executor, require_owner, require_not_paused,
+    set_owner,
+};
+use flash_loan::{
+    DebtLedger, ExecOpReceiver, FlashLender, FlashLoanAsset, FlashLoanContext, FlashLoanManager,
+    FlashLoanReceiver, MultiFlashLoanReceiver, SoroswapCallee,
+};
+use flash_loan_provider::{soroswap_flash_fee, FlashLoanQuote, FlashLoanSource};
+use arbitrage::{
+    ArbitrageExecutor, ArbitrageParams, ArbitrageRoute, BatchParams, BatchResult,
+    ExecutionOptions, FlashLoanPayload, PoolSplit, RouteExecution, SimulationReport, SkippedRoute,
+    SwapLeg,
+};
 use dex_interface::DexType;
+use fee_schedule::FeeSchedule;
+use oracle::{OracleAsset, OracleConfig};
+use optimizer::{OptimalTrade, TradeSizeOptimizer};
+use route_registry::{RegisteredRoute, RouteCall, RouteOverrides, AD_HOC_ROUTE_ID};
+use stats::TradeStats;
+use vault::LiquidityVault;
 
 const IS_INITIALIZED: &str = "initialized";
 const PROFIT_STORAGE: &str = "profit";
+const LAST_BATCH: &str = "last_batch";
+const LAST_OUTCOME: &str = "last_outcome";
+const STAGED_PAYLOAD: &str = "staged_payload";
 
 #[contract]
 pub struct FlashLoanExecutorContract;
@@ -53,114 +96,339 @@ impl FlashLoanExecutorContract {
         // Mark as initialized
         env.storage().instance().set(&IS_INITIALIZED, &true);
         
-        Ok(())
+        Self::finish(&env, Ok(()))
     }
     
-    /// Execute a complete flash loan arbitrage
+    /// Simulate arbitrage without executing (dry run)
     /// 
-    /// # Arguments
-    /// * `pool_address` - Address of the liquidity pool to borrow from
-    /// * `token_borrow` - Token to borrow via flash loan
-    /// * `token_intermediate` - Intermediate token for the swap route
-    /// * `amount` - Amount to borrow
-    /// * `dex_a_type` - Type of first DEX (0 = Soroswap, 1 = Phoenix)
-    /// * `dex_a_pool` - Pool address on first DEX
-    /// * `dex_b_type` - Type of second DEX
-    /// * `dex_b_pool` - Pool address on second DEX
-    /// * `min_profit_bps` - Minimum profit in basis points
-    /// * `max_slippage_bps` - Maximum slippage in basis points
-    pub fn execute_flash_loan_arbitrage(
+    /// Returns a report of the expected outputs, fees and net profit,
+    /// charging the flash loan fee from the schedule configured for `lender`
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_arbitrage(
         env: Env,
-        pool_address: Address,
         token_borrow: Address,
         token_intermediate: Address,
         amount: i128,
-        dex_a_type: u32,
+        lender: Address,
+        dex_a_type: DexType,
         dex_a_pool: Address,
-        dex_b_type: u32,
+        dex_b_type: DexType,
         dex_b_pool: Address,
-        min_profit_bps: u32,
-        max_slippage_bps: u32,
-    ) -> Result<i128, Error> {
-        // Security checks
-        require_not_paused(&env)?;
-        let _guard = ReentrancyGuard::enter(&env)?;
-        
-        // Convert DEX type integers to enum
-        let dex_a_type_enum = Self::parse_dex_type(dex_a_type)?;
-        let dex_b_type_enum = Self::parse_dex_type(dex_b_type)?;
-        
-        // === STEP 1: Request Flash Loan ===
-        let mut flash_loan_ctx = FlashLoanManager::request_flash_loan(
+    ) -> Result<SimulationReport, Error> {
+        let swaps = Self::two_hop_swaps(
             &env,
-            &pool_address,
             &token_borrow,
-            amount,
-        )?;
+            token_intermediate,
+            dex_a_type,
+            dex_a_pool,
+            dex_b_type,
+            dex_b_pool,
+        );
         
-        // === STEP 2: Execute Arbitrage ===
+        Self::simulate_route(env, token_borrow, amount, lender, swaps)
+    }
+    
+    /// Simulate an N-hop cyclic route without executing (dry run)
+    /// 
+    /// Reports the expected result of borrowing `amount` of `token_borrow`
+    /// from `lender` and trading it around `swaps`
+    /// 
+    /// # Arguments
+    /// * `token_borrow` - Token borrowed and returned by the cycle
+    /// * `amount` - Amount to borrow
+    /// * `lender` - Lender whose fee schedule prices the loan
+    /// * `swaps` - Swap legs, first selling and last buying `token_borrow`
+    pub fn simulate_route(
+        env: Env,
+        token_borrow: Address,
+        amount: i128,
+        lender: Address,
+        swaps: Vec<SwapLeg>,
+    ) -> Result<SimulationReport, Error> {
         let route = ArbitrageRoute {
-            route_id: Self::generate_route_id(&env),
-            dex_a: DexConfig {
-                dex_type: dex_a_type_enum,
-                pool_address: dex_a_pool,
-            },
-            dex_b: DexConfig {
-                dex_type: dex_b_type_enum,
-                pool_address: dex_b_pool,
-            },
-            token_borrow: token_borrow.clone(),
-            token_intermediate,
+            route_id: AD_HOC_ROUTE_ID,
+            legs: swaps,
+            token_borrow,
             amount,
-            min_profit_bps,
-            max_slippage_bps,
+            min_profit_bps: 0,
+            max_slippage_bps: 10000, // No slippage check in simulation
+            expected_outputs: Vec::new(&env),
+            deadline: env.ledger().timestamp(),
         };
         
-        let result = ArbitrageExecutor::execute_arbitrage(&env, &route, &flash_loan_ctx)?;
+        Self::finish(&env, ArbitrageExecutor::simulate_arbitrage(&env, &route, &lender))
+    }
+    
+    /// Find the borrow amount that maximizes net profit for a route
+    /// 
+    /// Uses the closed-form optimum when the route is two Soroswap pools and
+    /// a bounded golden-section search otherwise. Returns amount 0 if no
+    /// size up to `max_amount` is profitable.
+    /// 
+    /// # Arguments
+    /// * `token_borrow` - Token borrowed and returned by the cycle
+    /// * `max_amount` - Largest amount the bot is willing or able to borrow
+    /// * `lender` - Lender whose fee schedule prices the loan
+    /// * `swaps` - Swap legs, first selling and last buying `token_borrow`
+    pub fn optimize_trade_size(
+        env: Env,
+        token_borrow: Address,
+        max_amount: i128,
+        lender: Address,
+        swaps: Vec<SwapLeg>,
+    ) -> Result<OptimalTrade, Error> {
+        let route = ArbitrageRoute {
+            route_id: AD_HOC_ROUTE_ID,
+            legs: swaps,
+            token_borrow,
+            amount: max_amount,
+            min_profit_bps: 0,
+            max_slippage_bps: 10000, // No slippage check in simulation
+            expected_outputs: Vec::new(&env),
+            deadline: env.ledger().timestamp(),
+        };
         
-        // === STEP 3: Repay Flash Loan ===
-        FlashLoanManager::repay_flash_loan(&env, &mut flash_loan_ctx)?;
+        let trade = TradeSizeOptimizer::find_optimal_trade(&env, &route, &lender, max_amount);
+        Self::finish(&env, trade)
+    }
+    
+    /// Pick the cheapest flash loan source for a trade
+    /// 
+    /// Queries each candidate lender for its fee and available liquidity
+    /// and returns the lowest-fee source that can lend the full `amount`.
+    /// 
+    /// # Arguments
+    /// * `token` - Token to borrow
+    /// * `amount` - Amount to borrow
+    /// * `sources` - Candidate lenders and their provider types
+    pub fn quote_flash_loan(
+        env: Env,
+        token: Address,
+        amount: i128,
+        sources: Vec<FlashLoanSource>,
+    ) -> Result<FlashLoanQuote, Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
         
-        // === STEP 4: Store Profit ===
-        Self::add_profit(&env, &token_borrow, result.net_profit);
+        Self::finish(&env, flash_loan_provider::cheapest_source(&env, &sources, &token, amount))
+    }
+    
+    /// Split a borrow across several lenders when none can cover it alone
+    /// 
+    /// Returns one quote per leg, cheapest fee rate first. The bot turns
+    /// these into `BorrowLeg`s, routing all but the last through relays.
+    /// 
+    /// # Arguments
+    /// * `token` - Token to borrow
+    /// * `amount` - Total amount to borrow
+    /// * `sources` - Candidate lenders and their provider types
+    pub fn plan_flash_loan(
+        env: Env,
+        token: Address,
+        amount: i128,
+        sources: Vec<FlashLoanSource>,
+    ) -> Result<Vec<FlashLoanQuote>, Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
         
-        Ok(result.net_profit)
+        let legs = flash_loan_provider::plan_aggregated_borrow(&env, &sources, &token, amount);
+        Self::finish(&env, legs)
     }
     
-    /// Simulate arbitrage without executing (dry run)
+    /// Execute a two-hop flash loan arbitrage funded by the vault (owner only)
     /// 
-    /// Returns the expected net profit
-    pub fn simulate_arbitrage(
+    /// Soroban rejects contract re-entry, so an external lender cannot call
+    /// back into this contract while one of its entry points is running:
+    /// trades backed by external lenders start at the lender's `flash_loan`
+    /// with this contract as receiver (see `on_flash_loan`). This entry
+    /// point borrows deposited vault liquidity instead, paying depositors the
+    /// vault fee, and otherwise runs the route exactly like the callback.
+    /// 
+    /// # Arguments
+    /// * `initiator` - Contract owner
+    /// * `token_borrow` - Token borrowed from the vault
+    /// * `token_intermediate` - Token bought on DEX A and sold on DEX B
+    /// * `amount` - Amount to borrow and trade
+    /// * `options` - Profit and slippage thresholds, the ledger window and
+    ///   deadline the quote is valid for, and the reserves it was priced from
+    /// 
+    /// # Returns
+    /// Net profit booked by the trade
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_flash_loan_arbitrage(
         env: Env,
+        initiator: Address,
         token_borrow: Address,
         token_intermediate: Address,
         amount: i128,
-        dex_a_type: u32,
+        dex_a_type: DexType,
         dex_a_pool: Address,
-        dex_b_type: u32,
+        dex_b_type: DexType,
         dex_b_pool: Address,
+        options: ExecutionOptions,
     ) -> Result<i128, Error> {
-        let dex_a_type_enum = Self::parse_dex_type(dex_a_type)?;
-        let dex_b_type_enum = Self::parse_dex_type(dex_b_type)?;
+        // Security checks
+        Self::authorize_execution(&env, &initiator, false)?;
+        let _guard = ReentrancyGuard::enter(&env)?;
+        check_ledger_window(&env, options.not_before_ledger, options.valid_until_ledger)?;
+        check_deadline(&env, options.deadline)?;
         
-        let route = ArbitrageRoute {
-            route_id: 0,
-            dex_a: DexConfig {
-                dex_type: dex_a_type_enum,
-                pool_address: dex_a_pool,
-            },
-            dex_b: DexConfig {
-                dex_type: dex_b_type_enum,
-                pool_address: dex_b_pool,
-            },
-            token_borrow,
-            token_intermediate,
-            amount,
-            min_profit_bps: 0,
-            max_slippage_bps: 10000, // No slippage check in simulation
+        let params = ArbitrageParams {
+            legs: Vec::new(&env),
+            swaps: Self::two_hop_swaps(
+                &env,
+                &token_borrow,
+                token_intermediate,
+                dex_a_type,
+                dex_a_pool,
+                dex_b_type,
+                dex_b_pool,
+            ),
+            amount: None,
+            min_profit_bps: options.min_profit_bps,
+            max_slippage_bps: options.max_slippage_bps,
+            expected_outputs: options.expected_outputs,
+            soft_fail: false,
+            not_before_ledger: options.not_before_ledger,
+            valid_until_ledger: options.valid_until_ledger,
+            deadline: options.deadline,
+            expected_reserves: options.expected_reserves,
+            reserve_tolerance_bps: options.reserve_tolerance_bps,
+        };
+        
+        // Pools must still look like the state the trade was priced from
+        reserve_guard::check_snapshots(
+            &env,
+            &params.swaps,
+            &params.expected_reserves,
+            params.reserve_tolerance_bps,
+        )?;
+        
+        let net_profit = Self::run_on_vault_loan(&env, &token_borrow, amount, |debts| {
+            let net_profit = Self::run_route(&env, debts, AD_HOC_ROUTE_ID, params)?.net_profit();
+            Ok((net_profit, net_profit))
+        });
+        Self::finish(&env, net_profit)
+    }
+    
+    /// Execute a batch of routes funded by the vault (owner only)
+    /// 
+    /// Runs every route of `batch` on one vault loan, exactly as a batch
+    /// payload runs inside `on_flash_loan`. The vault is the only lender,
+    /// so the batch may not name any legs.
+    /// 
+    /// # Arguments
+    /// * `initiator` - Contract owner
+    /// * `token_borrow` - Token borrowed from the vault
+    /// * `amount` - Amount to borrow; no route may trade more
+    /// * `batch` - Routes to run, with their policy, window and reserve guard
+    /// 
+    /// # Returns
+    /// Per-route outcomes and the profit the batch kept
+    pub fn execute_batch(
+        env: Env,
+        initiator: Address,
+        token_borrow: Address,
+        amount: i128,
+        batch: BatchParams,
+    ) -> Result<BatchResult, Error> {
+        // Security checks
+        Self::authorize_execution(&env, &initiator, false)?;
+        let _guard = ReentrancyGuard::enter(&env)?;
+        
+        if !batch.legs.is_empty() {
+            return Err(Error::InvalidRoute);
+        }
+        let payload = FlashLoanPayload::Batch(batch);
+        payload.check_window(&env)?;
+        payload.check_reserves(&env)?;
+        
+        let result = Self::run_on_vault_loan(&env, &token_borrow, amount, |debts| match payload {
+            FlashLoanPayload::Batch(batch) => {
+                let result = Self::run_batch(&env, debts, batch)?;
+                let net_profit = result.total_profit;
+                Ok((result, net_profit))
+            }
+            _ => Err(Error::InvalidRoute),
+        });
+        Self::finish(&env, result)
+    }
+    
+    /// Execute a registered route funded by the vault (executors only)
+    /// 
+    /// Borrows `amount` of the route's first token from the vault and runs
+    /// the route owner-registered under `route_id` with it, as a
+    /// `RouteCall` payload would inside `on_flash_loan`.
+    /// 
+    /// # Arguments
+    /// * `executor` - Account authorized by the owner to run registered routes
+    /// * `route_id` - Id the route was registered under
+    /// * `amount` - Amount to borrow and trade
+    /// * `overrides` - Per-call thresholds, window and reserve guard
+    /// 
+    /// # Returns
+    /// Whether the route executed or was skipped, with its outcome
+    pub fn execute_route(
+        env: Env,
+        executor: Address,
+        route_id: u32,
+        amount: i128,
+        overrides: RouteOverrides,
+    ) -> Result<RouteExecution, Error> {
+        // Security checks
+        Self::authorize_execution(&env, &executor, true)?;
+        let _guard = ReentrancyGuard::enter(&env)?;
+        
+        let call = RouteCall { legs: Vec::new(&env), route_id, amount: Some(amount), overrides };
+        let payload = FlashLoanPayload::Registered(call);
+        payload.check_window(&env)?;
+        let (route_id, payload) = payload.resolve(&env)?;
+        payload.check_reserves(&env)?;
+        
+        let FlashLoanPayload::Single(mut params) = payload else {
+            return Err(Error::InvalidRoute);
         };
+        let token_borrow = params.swaps.get(0).ok_or(Error::RouteTooShort)?.token_in;
+        
+        // The vault lends on our own terms, so a soft-fail route is quoted
+        // before borrowing and skipping it costs no fee
+        if params.soft_fail {
+            let route = Self::params_route(route_id, &token_borrow, amount, &params);
+            let fee = LiquidityVault::flash_fee(&env, amount)?;
+            if let Some(skipped) = ArbitrageExecutor::check_or_skip(&env, &route, fee)? {
+                // Nothing was borrowed, so nothing is owed
+                let execution = RouteExecution::Skipped(SkippedRoute { flash_fee: 0, ..skipped });
+                Self::record_outcome(&env, &route, &execution)?;
+                return Self::finish(&env, Ok(execution));
+            }
+            
+            // Quoted profitable in this ledger already
+            params.soft_fail = false;
+        }
+        
+        let execution = Self::run_on_vault_loan(&env, &token_borrow, amount, |debts| {
+            let execution = Self::run_route(&env, debts, route_id, params)?;
+            let net_profit = execution.net_profit();
+            Ok((execution, net_profit))
+        });
+        Self::finish(&env, execution)
+    }
+    
+    /// Stage the payload for the next `exec_op` callback opened by `initiator`
+    /// 
+    /// Blend pools and xycLoans call back `exec_op` without forwarding any
+    /// data, so the bot stores the XDR payload here in the transaction
+    /// before the loan; the callback consumes it. Staging needs the same
+    /// authorization as running the payload.
+    pub fn stage_flash_loan(env: Env, initiator: Address, data: Bytes) -> Result<(), Error> {
+        let payload = FlashLoanPayload::decode(&env, &data)?;
+        Self::authorize_execution(&env, &initiator, payload.is_registered())?;
+        
+        env.storage().temporary().set(&(STAGED_PAYLOAD, initiator), &data);
         
-        ArbitrageExecutor::simulate_arbitrage(&env, &route)
+        Self::finish(&env, Ok(()))
     }
     
     /// Withdraw accumulated profits (owner only)
@@ -189,104 +457,2111 @@ impl FlashLoanExecutorContract {
             return Err(Error::InvalidWithdrawAmount);
         }
         
-        // TODO: Transfer tokens to recipient
-        // let token_client = TokenClient::new(&env, &token);
-        // token_client.transfer(&env.current_contract_address(), &recipient, &amount);
-        
-        // Update stored profit
+        // Update stored profit before paying it out
         Self::subtract_profit(&env, &token, amount);
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &recipient, &amount);
         
         // Emit event
         events::emit_profit_withdrawn(&env, caller, token, amount);
         
-        Ok(())
+        Self::finish(&env, Ok(()))
     }
     
-    /// Get accumulated profit for a token
-    pub fn get_profit_balance(env: Env, token: Address) -> i128 {
-        Self::get_profit(&env, &token)
+    /// Deposit idle inventory into the lending vault
+    /// 
+    /// Returns the number of vault shares minted to `depositor`
+    pub fn deposit_liquidity(
+        env: Env,
+        depositor: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        depositor.require_auth();
+        require_not_paused(&env)?;
+        
+        Self::finish(&env, LiquidityVault::deposit(&env, &depositor, &token, amount))
     }
     
-    /// Pause the contract (owner only, emergency use)
-    pub fn pause(env: Env, caller: Address) -> Result<(), Error> {
+    /// Redeem vault shares for the underlying tokens plus accrued fees
+    /// 
+    /// Returns the amount of `token` sent to `depositor`
+    pub fn withdraw_liquidity(
+        env: Env,
+        depositor: Address,
+        token: Address,
+        shares: i128,
+    ) -> Result<i128, Error> {
+        depositor.require_auth();
+        
+        Self::finish(&env, LiquidityVault::withdraw(&env, &depositor, &token, shares))
+    }
+    
+    /// Get vault shares held by a depositor
+    pub fn get_liquidity_shares(env: Env, token: Address, depositor: Address) -> i128 {
+        LiquidityVault::shares_of(&env, &token, &depositor)
+    }
+    
+    /// Set the fee charged on flash loans from the vault (owner only)
+    /// 
+    /// # Arguments
+    /// * `fee_bps` - Fee in basis points
+    pub fn set_flash_fee(env: Env, caller: Address, fee_bps: u32) -> Result<(), Error> {
         caller.require_auth();
         require_owner(&env, &caller)?;
         
-        security::pause(&env);
-        events::emit_pause_status_changed(&env, true);
+        Self::finish(&env, LiquidityVault::set_fee_bps(&env, fee_bps))
+    }
+    
+    /// Configure the fee schedule used to price loans from `lender` (owner only)
+    /// 
+    /// Simulation uses it to estimate the fee, and execution rejects a
+    /// lender that charges more than its schedule.
+    /// 
+    /// # Arguments
+    /// * `lender` - Lender the schedule applies to
+    /// * `schedule` - Flat or tiered rate plus a minimum fee
+    pub fn set_fee_schedule(
+        env: Env,
+        caller: Address,
+        lender: Address,
+        schedule: FeeSchedule,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
         
-        Ok(())
+        Self::finish(&env, fee_schedule::set_fee_schedule(&env, &lender, &schedule))
     }
     
-    /// Unpause the contract (owner only)
-    pub fn unpause(env: Env, caller: Address) -> Result<(), Error> {
+    /// Remove the fee schedule for `lender` (owner only)
+    pub fn remove_fee_schedule(env: Env, caller: Address, lender: Address) -> Result<(), Error> {
         caller.require_auth();
         require_owner(&env, &caller)?;
         
-        security::unpause(&env);
-        events::emit_pause_status_changed(&env, false);
+        fee_schedule::remove_fee_schedule(&env, &lender);
         
-        Ok(())
+        Self::finish(&env, Ok(()))
     }
     
-    // === Helper Functions ===
+    /// Get the fee schedule configured for `lender`
+    pub fn get_fee_schedule(env: Env, lender: Address) -> Option<FeeSchedule> {
+        fee_schedule::get_fee_schedule(&env, &lender)
+    }
     
-    fn parse_dex_type(type_id: u32) -> Result<DexType, Error> {
-        match type_id {
-            0 => Ok(DexType::Soroswap),
-            1 => Ok(DexType::Phoenix),
-            _ => Err(Error::InvalidRoute),
-        }
+    /// Allow or disallow routes to trade through `pool` (owner only)
+    /// 
+    /// Every pool of a route must be allowlisted before it can be simulated
+    /// or executed.
+    pub fn set_pool_allowed(
+        env: Env,
+        caller: Address,
+        pool: Address,
+        allowed: bool,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        allowlist::set_pool_allowed(&env, &pool, allowed);
+        
+        Self::finish(&env, Ok(()))
     }
     
-    fn generate_route_id(env: &Env) -> u32 {
-        // Simple counter-based ID generation
-        let key = Symbol::new(env, "route_counter");
-        let current: u32 = env.storage().instance().get(&key).unwrap_or(0);
-        let next = current + 1;
-        env.storage().instance().set(&key, &next);
-        next
+    /// Allow or disallow routes to borrow or pass through `token` (owner only)
+    pub fn set_token_allowed(
+        env: Env,
+        caller: Address,
+        token: Address,
+        allowed: bool,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        allowlist::set_token_allowed(&env, &token, allowed);
+        
+        Self::finish(&env, Ok(()))
     }
     
-    fn get_profit(env: &Env, token: &Address) -> i128 {
-        let key = (PROFIT_STORAGE, token);
-        env.storage().persistent().get(&key).unwrap_or(0)
+    /// Whether `pool` is on the allowlist
+    pub fn is_pool_allowed(env: Env, pool: Address) -> bool {
+        allowlist::is_pool_allowed(&env, &pool)
     }
     
-    fn add_profit(env: &Env, token: &Address, amount: i128) {
-        let key = (PROFIT_STORAGE, token);
-        let current = Self::get_profit(env, token);
-        let new_total = current + amount;
-        env.storage().persistent().set(&key, &new_total);
+    /// Whether `token` is on the allowlist
+    pub fn is_token_allowed(env: Env, token: Address) -> bool {
+        allowlist::is_token_allowed(&env, &token)
     }
     
-    fn subtract_profit(env: &Env, token: &Address, amount: i128) {
-        let key = (PROFIT_STORAGE, token);
-        let current = Self::get_profit(env, token);
-        let new_total = current - amount;
-        env.storage().persistent().set(&key, &new_total);
+    /// Set the router swaps on `dex_type` are sent through (owner only)
+    /// 
+    /// # Arguments
+    /// * `dex_type` - DEX whose swaps use the router
+    /// * `router` - Router contract of that DEX
+    pub fn set_dex_router(
+        env: Env,
+        caller: Address,
+        dex_type: DexType,
+        router: Address,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        dex_interface::set_dex_router(&env, dex_type, &router);
+        
+        Self::finish(&env, Ok(()))
     }
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-    use soroban_sdk::testutils::Address as _;
-
-    #[test]
-    fn test_initialization() {
-        let env = Env::default();
-        let contract_id = env.register_contract(None, FlashLoanExecutorContract);
-        let client = FlashLoanExecutorContractClient::new(&env, &contract_id);
+    
+    /// Get the router configured for `dex_type`
+    pub fn get_dex_router(env: Env, dex_type: DexType) -> Option<Address> {
+        dex_interface::get_dex_router(&env, dex_type)
+    }
+    
+    /// Register an external DEX adapter contract under `adapter_id` (owner only)
+    /// 
+    /// Routes reach the adapter with `DexType::External(adapter_id)`.
+    /// Registering over an existing id replaces its adapter.
+    /// 
+    /// # Arguments
+    /// * `adapter_id` - Id routes use to reference the adapter
+    /// * `adapter` - Contract implementing the `DexAdapter` interface
+    pub fn set_dex_adapter(
+        env: Env,
+        caller: Address,
+        adapter_id: u32,
+        adapter: Address,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
         
-        let owner = Address::generate(&env);
+        dex_adapter::set_adapter(&env, adapter_id, &adapter);
+        events::emit_dex_adapter_changed(&env, adapter_id, Some(adapter));
         
-        // Initialize
-        let result = client.initialize(&owner);
-        assert!(result.is_ok());
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Unregister the external DEX adapter under `adapter_id` (owner only)
+    pub fn remove_dex_adapter(env: Env, caller: Address, adapter_id: u32) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
         
-        // Try to initialize again (should fail)
-        let result = client.initialize(&owner);
-        assert!(result.is_err());
+        dex_adapter::remove_adapter(&env, adapter_id);
+        events::emit_dex_adapter_changed(&env, adapter_id, None);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Get the adapter contract registered under `adapter_id`
+    pub fn get_dex_adapter(env: Env, adapter_id: u32) -> Option<Address> {
+        dex_adapter::get_adapter(&env, adapter_id)
+    }
+    
+    /// List the ids of every registered external DEX adapter
+    pub fn list_dex_adapters(env: Env) -> Vec<u32> {
+        dex_adapter::list_adapters(&env)
+    }
+    
+    /// Output of `pool` for one whole unit of `token_in`, fees included
+    pub fn get_dex_price(
+        env: Env,
+        dex_type: DexType,
+        pool: Address,
+        token_in: Address,
+        token_out: Address,
+    ) -> Result<i128, Error> {
+        dex_interface::get_dex_price(&env, dex_type, &pool, &token_in, &token_out)
+    }
+    
+    /// Check every executed leg against a Reflector-compatible oracle (owner only)
+    /// 
+    /// A leg whose execution price differs from the oracle's by more than
+    /// `max_deviation_bps` reverts the trade. Every token traded must have
+    /// an oracle asset mapping while the check is enabled.
+    /// 
+    /// # Arguments
+    /// * `config` - Oracle contract, allowed deviation and maximum price age
+    pub fn set_oracle(env: Env, caller: Address, config: OracleConfig) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        Self::finish(&env, oracle::set_oracle_config(&env, &config))
+    }
+    
+    /// Disable the oracle price check (owner only)
+    pub fn remove_oracle(env: Env, caller: Address) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        oracle::remove_oracle_config(&env);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Get the oracle configuration, if the price check is enabled
+    pub fn get_oracle(env: Env) -> Option<OracleConfig> {
+        oracle::get_oracle_config(&env)
+    }
+    
+    /// Map `token` to the asset the oracle quotes it as (owner only)
+    pub fn set_oracle_asset(
+        env: Env,
+        caller: Address,
+        token: Address,
+        asset: OracleAsset,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        oracle::set_oracle_asset(&env, &token, &asset);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Remove the oracle asset mapping of `token` (owner only)
+    pub fn remove_oracle_asset(env: Env, caller: Address, token: Address) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        oracle::remove_oracle_asset(&env, &token);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Get the oracle asset mapped to `token`
+    pub fn get_oracle_asset(env: Env, token: Address) -> Option<OracleAsset> {
+        oracle::get_oracle_asset(&env, &token)
+    }
+    
+    /// Register a route executors may run by id (owner only)
+    /// 
+    /// The route is validated like any executed route, so its pools and
+    /// tokens must already be allowlisted.
+    /// 
+    /// # Arguments
+    /// * `swaps` - Legs of the cycle, starting and ending in the borrowed token
+    /// * `min_profit_bps` - Default minimum profit, may be overridden per call
+    /// * `max_slippage_bps` - Default slippage bound, may be overridden per call
+    /// 
+    /// # Returns
+    /// Id of the new route
+    pub fn register_route(
+        env: Env,
+        caller: Address,
+        swaps: Vec<SwapLeg>,
+        min_profit_bps: u32,
+        max_slippage_bps: u32,
+    ) -> Result<u32, Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        let hops = swaps.len();
+        let id = route_registry::register_route(&env, swaps, min_profit_bps, max_slippage_bps)?;
+        events::emit_route_registered(&env, id, hops);
+        
+        Self::finish(&env, Ok(id))
+    }
+    
+    /// Replace the legs and default thresholds of a registered route (owner only)
+    pub fn update_route(
+        env: Env,
+        caller: Address,
+        route_id: u32,
+        swaps: Vec<SwapLeg>,
+        min_profit_bps: u32,
+        max_slippage_bps: u32,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        let hops = swaps.len();
+        route_registry::update_route(&env, route_id, swaps, min_profit_bps, max_slippage_bps)?;
+        events::emit_route_registered(&env, route_id, hops);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Enable or disable a registered route (owner only)
+    pub fn set_route_enabled(
+        env: Env,
+        caller: Address,
+        route_id: u32,
+        enabled: bool,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        route_registry::set_route_enabled(&env, route_id, enabled)?;
+        events::emit_route_status_changed(&env, route_id, enabled);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Get a registered route by id
+    pub fn get_route(env: Env, route_id: u32) -> Option<RegisteredRoute> {
+        route_registry::get_route(&env, route_id)
+    }
+    
+    /// List every registered route, including disabled ones
+    pub fn list_routes(env: Env) -> Vec<RegisteredRoute> {
+        route_registry::list_routes(&env)
+    }
+    
+    /// Authorize or revoke an executor (owner only)
+    /// 
+    /// Executors may initiate flash loans running registered routes, but
+    /// not ad-hoc routes or batches.
+    pub fn set_executor(
+        env: Env,
+        caller: Address,
+        executor: Address,
+        authorized: bool,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        security::set_executor(&env, &executor, authorized);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Whether `executor` may run registered routes
+    pub fn is_executor(env: Env, executor: Address) -> bool {
+        security::is_executor(&env, &executor)
+    }
+    
+    /// Performance of a route since its statistics were last reset
+    /// 
+    /// Ad-hoc routes, batch routes and multi-asset routes are all counted
+    /// under id 0.
+    pub fn get_route_stats(env: Env, route_id: u32) -> TradeStats {
+        stats::get_route_stats(&env, route_id)
+    }
+    
+    /// Performance of the routes trading through the DEX pair at `pool`
+    pub fn get_pair_stats(env: Env, pool: Address) -> TradeStats {
+        stats::get_pair_stats(&env, &pool)
+    }
+    
+    /// Reset the statistics of a route (owner only)
+    pub fn reset_route_stats(env: Env, caller: Address, route_id: u32) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        stats::reset_route_stats(&env, route_id);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Reset the statistics of a DEX pair (owner only)
+    pub fn reset_pair_stats(env: Env, caller: Address, pool: Address) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        stats::reset_pair_stats(&env, &pool);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Per-route outcomes of the most recent batch run
+    /// 
+    /// Kept in temporary storage, so it is only meant to be read back by the
+    /// bot shortly after the batch transaction.
+    pub fn get_last_batch_result(env: Env) -> Option<BatchResult> {
+        env.storage().temporary().get(&LAST_BATCH)
+    }
+    
+    /// Outcome of the most recent single-route execution borrowing `token`
+    /// 
+    /// Soft-fail routes report here whether they traded or were skipped,
+    /// along with the quotes they were judged on. Kept in temporary storage.
+    pub fn get_last_outcome(env: Env, token: Address) -> Option<RouteExecution> {
+        let key = (LAST_OUTCOME, token);
+        env.storage().temporary().get(&key)
+    }
+    
+    /// Get the contract owner
+    pub fn get_owner(env: Env) -> Address {
+        security::get_owner(&env)
+    }
+    
+    /// Get accumulated profit for a token
+    pub fn get_profit_balance(env: Env, token: Address) -> i128 {
+        Self::get_profit(&env, &token)
+    }
+    
+    /// Pause the contract (owner only, emergency use)
+    pub fn pause(env: Env, caller: Address) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        security::pause(&env);
+        events::emit_pause_status_changed(&env, true);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    /// Unpause the contract (owner only)
+    pub fn unpause(env: Env, caller: Address) -> Result<(), Error> {
+        caller.require_auth();
+        require_owner(&env, &caller)?;
+        
+        security::unpause(&env);
+        events::emit_pause_status_changed(&env, false);
+        
+        Self::finish(&env, Ok(()))
+    }
+    
+    // === Helper Functions ===
+    
+    /// End-of-invocation invariant: no entry point may succeed with an open debt
+    fn finish<T>(env: &Env, result: Result<T, Error>) -> Result<T, Error> {
+        let value = result?;
+        DebtLedger::ensure_settled(env)?;
+        Ok(value)
+    }
+    
+    /// Run the payload of a flash loan this contract has just received
+    /// 
+    /// Shared by every lender callback; `data` is the XDR-encoded payload
+    /// described on `on_flash_loan`.
+    fn receive_flash_loan(
+        env: &Env,
+        initiator: &Address,
+        token: &Address,
+        amount: i128,
+        fee: i128,
+        data: &Bytes,
+    ) -> Result<(), Error> {
+        let payload = FlashLoanPayload::decode(env, data)?;
+        let legs = payload.legs().clone();
+        
+        // Security checks
+        Self::authorize_execution(env, initiator, payload.is_registered())?;
+        let _guard = ReentrancyGuard::enter(env)?;
+        payload.check_window(env)?;
+        
+        let index = FlashLoanManager::locate_leg(env, &legs, amount, fee)?;
+        if index + 1 < legs.len() {
+            // Not the last leg: forward the funds and open the next one
+            let forwarded = FlashLoanPayload::record_relay_fee(env, data, index, fee)?;
+            let relayed = FlashLoanManager::relay_flash_loan(
+                env,
+                initiator,
+                &legs,
+                index,
+                token,
+                fee,
+                &forwarded,
+            );
+            return Self::finish(env, relayed);
+        }
+        
+        // Registered routes are looked up only where they execute
+        let (route_id, payload) = payload.resolve(env)?;
+        
+        // Pools must still look like the state the trade was priced from
+        payload.check_reserves(env)?;
+        
+        // === STEP 1: Open Flash Loans (one debt per leg) ===
+        let mut debts = FlashLoanManager::begin_aggregated_flash_loan(
+            env,
+            initiator,
+            &legs,
+            token,
+            fee,
+        )?;
+        let balance_before = Self::balance_before_borrow(env, token, &debts)?;
+        
+        // === STEP 2: Execute Arbitrage ===
+        let net_profit = match payload {
+            FlashLoanPayload::Single(params) => {
+                Self::run_route(env, &debts, route_id, params)?.net_profit()
+            }
+            FlashLoanPayload::Batch(batch) => Self::run_batch(env, &debts, batch)?.total_profit,
+            FlashLoanPayload::Registered(_) => return Err(Error::InvalidRoute),
+        };
+        
+        // === STEP 3: Repay Flash Loans ===
+        FlashLoanManager::repay_all(env, &mut debts)?;
+        FlashLoanManager::ensure_all_repaid(env, &debts);
+        
+        // === STEP 4: Verify Profit Against Balances ===
+        // Repayments a lender has yet to pull are still in the balance
+        let balance_before = balance_before.checked_add(FlashLoanManager::awaiting_pull(&debts)?)
+            .ok_or(Error::ArithmeticOverflow)?;
+        let settled = Self::settle_profit(env, token, balance_before, net_profit);
+        Self::finish(env, settled)
+    }
+    
+    /// Security checks shared by every flash loan callback
+    /// 
+    /// Executors may only initiate payloads running a registered route.
+    fn authorize_execution(env: &Env, initiator: &Address, registered: bool) -> Result<(), Error> {
+        initiator.require_auth();
+        if registered {
+            require_executor(env, initiator)?;
+        } else {
+            require_owner(env, initiator)?;
+        }
+        require_not_paused(env)
+    }
+    
+    /// Run the route backed by `debts` (all in the same token)
+    /// 
+    /// The route trades `params.amount`, or everything borrowed if unset.
+    /// 
+    /// With `soft_fail` set the route is quoted first and skipped when it
+    /// misses its minimum profit. The loan is already open by then, so the
+    /// flash fee is paid from profit already booked for the token, and the
+    /// skip reverts with `InsufficientProfit` if there is not enough.
+    /// `execute_route` quotes before borrowing from the vault instead.
+    fn run_route(
+        env: &Env,
+        debts: &Vec<FlashLoanContext>,
+        route_id: u32,
+        params: ArbitrageParams,
+    ) -> Result<RouteExecution, Error> {
+        let token_borrow = debts.get(0).ok_or(Error::InvalidRoute)?.token;
+        
+        // The route may trade less than it borrowed, never more
+        let total_borrowed = FlashLoanManager::total_borrowed(debts)?;
+        let amount = params.amount.unwrap_or(total_borrowed);
+        if amount > total_borrowed {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
+        
+        let route = Self::params_route(route_id, &token_borrow, amount, &params);
+        let fee = FlashLoanManager::total_fee(debts)?;
+        
+        let execution = if params.soft_fail {
+            ArbitrageExecutor::execute_or_skip(env, &route, fee)?
+        } else {
+            RouteExecution::Executed(ArbitrageExecutor::execute_arbitrage(env, &route, fee)?)
+        };
+        
+        if let RouteExecution::Skipped(_) = &execution {
+            if Self::get_profit(env, &token_borrow) < fee {
+                return Err(Error::InsufficientProfit);
+            }
+        }
+        Self::record_outcome(env, &route, &execution)?;
+        
+        Ok(execution)
+    }
+    
+    /// Route trading `amount` of `token_borrow` as described by `params`
+    fn params_route(
+        route_id: u32,
+        token_borrow: &Address,
+        amount: i128,
+        params: &ArbitrageParams,
+    ) -> ArbitrageRoute {
+        ArbitrageRoute {
+            route_id,
+            legs: params.swaps.clone(),
+            token_borrow: token_borrow.clone(),
+            amount,
+            min_profit_bps: params.min_profit_bps,
+            max_slippage_bps: params.max_slippage_bps,
+            expected_outputs: params.expected_outputs.clone(),
+            deadline: Self::swap_deadline(params.deadline),
+        }
+    }
+    
+    /// Book the statistics of a route and keep its outcome for the caller
+    fn record_outcome(env: &Env, route: &ArbitrageRoute, execution: &RouteExecution) -> Result<(), Error> {
+        stats::record(env, route, execution)?;
+        
+        let key = (LAST_OUTCOME, route.token_borrow.clone());
+        env.storage().temporary().set(&key, execution);
+        
+        Ok(())
+    }
+    
+    /// Run every route of a batch backed by `debts` (all in the same token)
+    fn run_batch(
+        env: &Env,
+        debts: &Vec<FlashLoanContext>,
+        batch: BatchParams,
+    ) -> Result<BatchResult, Error> {
+        let token_borrow = debts.get(0).ok_or(Error::InvalidRoute)?.token;
+        let total_borrowed = FlashLoanManager::total_borrowed(debts)?;
+        
+        let mut routes: Vec<ArbitrageRoute> = Vec::new(env);
+        for route in batch.routes.iter() {
+            // Routes reuse the loan in turn, so none may trade more than it
+            if route.amount > total_borrowed {
+                return Err(Error::InvalidFlashLoanAmount);
+            }
+            routes.push_back(ArbitrageRoute {
+                route_id: AD_HOC_ROUTE_ID,
+                legs: route.swaps,
+                token_borrow: token_borrow.clone(),
+                amount: route.amount,
+                min_profit_bps: route.min_profit_bps,
+                max_slippage_bps: route.max_slippage_bps,
+                expected_outputs: route.expected_outputs,
+                deadline: Self::swap_deadline(batch.deadline),
+            });
+        }
+        
+        let fee = FlashLoanManager::total_fee(debts)?;
+        let result = ArbitrageExecutor::execute_batch(env, &routes, batch.policy, fee)?;
+        for (route, execution) in routes.iter().zip(result.outcomes.iter()) {
+            stats::record(env, &route, &execution)?;
+        }
+        env.storage().temporary().set(&LAST_BATCH, &result);
+        
+        Ok(result)
+    }
+    
+    /// Borrow `amount` of `token` from the vault, run `trade` on it and repay
+    /// 
+    /// `trade` returns its result along with the net profit it computed,
+    /// which is checked against the balance change like on a callback.
+    fn run_on_vault_loan<T>(
+        env: &Env,
+        token: &Address,
+        amount: i128,
+        trade: impl FnOnce(&Vec<FlashLoanContext>) -> Result<(T, i128), Error>,
+    ) -> Result<T, Error> {
+        // === STEP 1: Borrow From The Vault ===
+        let mut debt = LiquidityVault::borrow(env, token, amount)?;
+        let balance_before = FlashLoanManager::get_token_balance(
+            env,
+            token,
+            &env.current_contract_address(),
+        )?;
+        
+        // === STEP 2: Execute Arbitrage ===
+        let (result, net_profit) = trade(&vec![env, debt.clone()])?;
+        
+        // === STEP 3: Repay The Vault ===
+        LiquidityVault::repay(env, &mut debt, balance_before)?;
+        
+        // === STEP 4: Verify Profit Against Balances ===
+        // The fee never left the contract, it now belongs to depositors
+        let balance_before = balance_before.checked_add(debt.fee)
+            .ok_or(Error::ArithmeticOverflow)?;
+        Self::settle_profit(env, token, balance_before, net_profit)?;
+        
+        Ok(result)
+    }
+    
+    /// Swap legs of a cycle out through DEX A and back through DEX B
+    fn two_hop_swaps(
+        env: &Env,
+        token_borrow: &Address,
+        token_intermediate: Address,
+        dex_a_type: DexType,
+        dex_a_pool: Address,
+        dex_b_type: DexType,
+        dex_b_pool: Address,
+    ) -> Vec<SwapLeg> {
+        vec![
+            env,
+            SwapLeg {
+                token_in: token_borrow.clone(),
+                token_out: token_intermediate.clone(),
+                splits: vec![
+                    env,
+                    PoolSplit { dex_type: dex_a_type, pool: dex_a_pool, fraction_bps: 10000 },
+                ],
+            },
+            SwapLeg {
+                token_in: token_intermediate,
+                token_out: token_borrow.clone(),
+                splits: vec![
+                    env,
+                    PoolSplit { dex_type: dex_b_type, pool: dex_b_pool, fraction_bps: 10000 },
+                ],
+            },
+        ]
+    }
+    
+    /// Deadline handed to DEX routers: the payload's, or none at all
+    /// 
+    /// The payload's deadline has already been checked against this ledger;
+    /// routers enforce it again on every swap.
+    fn swap_deadline(deadline: Option<u64>) -> u64 {
+        deadline.unwrap_or(u64::MAX)
+    }
+    
+    /// Contract balance of `token` before `debts` were advanced to it
+    fn balance_before_borrow(
+        env: &Env,
+        token: &Address,
+        debts: &Vec<FlashLoanContext>,
+    ) -> Result<i128, Error> {
+        FlashLoanManager::get_token_balance(env, token, &env.current_contract_address())?
+            .checked_sub(FlashLoanManager::total_borrowed(debts)?)
+            .ok_or(Error::ArithmeticOverflow)
+    }
+    
+    /// Book the profit actually left in the contract once all debts are repaid
+    fn settle_profit(
+        env: &Env,
+        token: &Address,
+        balance_before: i128,
+        net_profit: i128,
+    ) -> Result<(), Error> {
+        let balance_after = FlashLoanManager::get_token_balance(
+            env,
+            token,
+            &env.current_contract_address(),
+        )?;
+        let profit = ArbitrageExecutor::verify_profit(net_profit, balance_before, balance_after)?;
+        
+        Self::add_profit(env, token, profit);
+        
+        Ok(())
+    }
+    
+    fn get_profit(env: &Env, token: &Address) -> i128 {
+        let key = (PROFIT_STORAGE, token);
+        env.storage().persistent().get(&key).unwrap_or(0)
+    }
+    
+    fn add_profit(env: &Env, token: &Address, amount: i128) {
+        let key = (PROFIT_STORAGE, token);
+        let current = Self::get_profit(env, token);
+        let new_total = current + amount;
+        env.storage().persistent().set(&key, &new_total);
+    }
+    
+    fn subtract_profit(env: &Env, token: &Address, amount: i128) {
+        let key = (PROFIT_STORAGE, token);
+        let current = Self::get_profit(env, token);
+        let new_total = current - amount;
+        env.storage().persistent().set(&key, &new_total);
+    }
+}
+
+#[contractimpl]
+impl FlashLoanReceiver for FlashLoanExecutorContract {
+    /// Flash loan callback: execute a complete flash loan arbitrage
+    /// 
+    /// Invoked by the lender after it has transferred `amount` of `token`
+    /// to this contract. The bot starts a trade by calling the lender's
+    /// `flash_loan` with this contract as receiver and an XDR-encoded
+    /// `ArbitrageParams` as `data`, `BatchParams` to run several routes
+    /// on the same loan, or a `RouteCall` to run a registered route.
+    /// 
+    /// # Arguments
+    /// * `initiator` - Account that requested the flash loan (owner, or an
+    ///   executor for registered routes)
+    /// * `token` - Borrowed token
+    /// * `amount` - Borrowed amount
+    /// * `fee` - Fee the lender expects on top of `amount`
+    /// * `data` - XDR-encoded `ArbitrageParams`, `BatchParams` or `RouteCall`
+    fn on_flash_loan(
+        env: Env,
+        initiator: Address,
+        token: Address,
+        amount: i128,
+        fee: i128,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        Self::receive_flash_loan(&env, &initiator, &token, amount, fee, &data)
+    }
+}
+
+#[contractimpl]
+impl ExecOpReceiver for FlashLoanExecutorContract {
+    /// Blend and xycLoans flash loan callback
+    /// 
+    /// Runs the payload `caller` staged with `stage_flash_loan`, which is
+    /// consumed. Blend is repaid by handing the principal to `caller`, who
+    /// repays the pool in the same submit; xycLoans pulls its repayment.
+    fn exec_op(
+        env: Env,
+        caller: Address,
+        token: Address,
+        amount: i128,
+        fee: i128,
+    ) -> Result<(), Error> {
+        let key = (STAGED_PAYLOAD, caller.clone());
+        let data: Bytes = env.storage().temporary().get(&key).ok_or(Error::InvalidRoute)?;
+        env.storage().temporary().remove(&key);
+        
+        Self::receive_flash_loan(&env, &caller, &token, amount, fee, &data)
+    }
+}
+
+#[contractimpl]
+impl SoroswapCallee for FlashLoanExecutorContract {
+    /// Soroswap flash swap callback
+    /// 
+    /// `data` is the same payload `on_flash_loan` takes. The pair is repaid
+    /// the principal plus its 0.3% swap fee.
+    fn soroswap_call(
+        env: Env,
+        sender: Address,
+        token: Address,
+        amount: i128,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        let fee = soroswap_flash_fee(amount)?;
+        Self::receive_flash_loan(&env, &sender, &token, amount, fee, &data)
+    }
+}
+
+#[contractimpl]
+impl MultiFlashLoanReceiver for FlashLoanExecutorContract {
+    /// Multi-asset flash loan callback: one arbitrage route per borrowed asset
+    /// 
+    /// Every asset is repaid independently; the invocation fails if any
+    /// debt is still outstanding once all routes have run.
+    /// 
+    /// # Arguments
+    /// * `initiator` - Account that requested the flash loan (must be owner)
+    /// * `loans` - Assets advanced by the lender with their fees
+    /// * `data` - XDR-encoded `Vec<ArbitrageParams>`, one per loan in order
+    fn on_flash_loan_multi(
+        env: Env,
+        initiator: Address,
+        loans: Vec<FlashLoanAsset>,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        let params_list = Vec::<ArbitrageParams>::from_xdr(&env, &data)
+            .map_err(|_| Error::InvalidRoute)?;
+        
+        // Security checks
+        Self::authorize_execution(&env, &initiator, false)?;
+        let _guard = ReentrancyGuard::enter(&env)?;
+        
+        if params_list.is_empty() || params_list.len() != loans.len() {
+            return Err(Error::InvalidRoute);
+        }
+        
+        // All assets come from the single lender currently calling us back,
+        // so each route has exactly one leg and it is the same for all
+        let lender = params_list.get_unchecked(0).legs.get(0).ok_or(Error::InvalidRoute)?;
+        for (loan, params) in loans.iter().zip(params_list.iter()) {
+            let leg = params.legs.get(0).ok_or(Error::InvalidRoute)?;
+            if params.legs.len() != 1
+                || leg.provider != lender.provider
+                || leg.lender != lender.lender
+            {
+                return Err(Error::InvalidRoute);
+            }
+            FlashLoanManager::locate_leg(&env, &params.legs, loan.amount, loan.fee)?;
+            check_ledger_window(&env, params.not_before_ledger, params.valid_until_ledger)?;
+            check_deadline(&env, params.deadline)?;
+        }
+        
+        // Every pool is checked before the first route trades
+        for params in params_list.iter() {
+            reserve_guard::check_snapshots(
+                &env,
+                &params.swaps,
+                &params.expected_reserves,
+                params.reserve_tolerance_bps,
+            )?;
+        }
+        
+        // === STEP 1: Open Flash Loans ===
+        let mut debts = FlashLoanManager::begin_multi_flash_loan(
+            &env,
+            lender.provider,
+            &lender.lender,
+            &loans,
+        )?;
+        
+        let mut balances_before: Vec<i128> = Vec::new(&env);
+        for debt in debts.iter() {
+            let debt_list = vec![&env, debt.clone()];
+            balances_before.push_back(Self::balance_before_borrow(&env, &debt.token, &debt_list)?);
+        }
+        
+        // === STEP 2: Execute Arbitrage ===
+        let mut results: Vec<RouteExecution> = Vec::new(&env);
+        for (debt, params) in debts.iter().zip(params_list.iter()) {
+            let execution = Self::run_route(&env, &vec![&env, debt], AD_HOC_ROUTE_ID, params)?;
+            results.push_back(execution);
+        }
+        
+        // === STEP 3: Repay Flash Loans ===
+        FlashLoanManager::repay_all(&env, &mut debts)?;
+        FlashLoanManager::ensure_all_repaid(&env, &debts);
+        
+        // === STEP 4: Verify Profit Against Balances ===
+        for (i, debt) in debts.iter().enumerate() {
+            let i = i as u32;
+            let result = results.get_unchecked(i);
+            let balance_before = balances_before.get_unchecked(i);
+            Self::settle_profit(&env, &debt.token, balance_before, result.net_profit())?;
+        }
+        
+        Self::finish(&env, Ok(()))
+    }
+}
+
+#[contractimpl]
+impl FlashLender for FlashLoanExecutorContract {
+    /// Maximum amount the vault can lend
+    fn max_flash_loan(env: Env, token: Address) -> Result<i128, Error> {
+        Self::finish(&env, Ok(LiquidityVault::total_assets(&env, &token)))
+    }
+    
+    /// Fee charged by the vault for borrowing `amount`
+    fn flash_fee(env: Env, _token: Address, amount: i128) -> Result<i128, Error> {
+        Self::finish(&env, LiquidityVault::flash_fee(&env, amount))
+    }
+    
+    /// Lend vault liquidity to `receiver` for the duration of its callback
+    /// 
+    /// # Arguments
+    /// * `initiator` - Account requesting the loan
+    /// * `receiver` - Contract implementing `on_flash_loan`
+    /// * `token` - Token to borrow
+    /// * `amount` - Amount to borrow
+    /// * `data` - Opaque payload forwarded to the receiver
+    fn flash_loan(
+        env: Env,
+        initiator: Address,
+        receiver: Address,
+        token: Address,
+        amount: i128,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        initiator.require_auth();
+        require_not_paused(&env)?;
+        
+        let result = LiquidityVault::flash_loan(&env, &initiator, &receiver, &token, amount, &data);
+        Self::finish(&env, result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{token, xdr::ToXdr};
+    use mocks::blend::{MockBlendPool, MockBlendPoolClient};
+    use mocks::flash_lender::{MockFlashLender, MockFlashLenderClient};
+    use mocks::phoenix::{MockPhoenixPool, MockPhoenixPoolClient};
+    use mocks::soroswap::{create_pair, MockSoroswapPair, MockSoroswapPairClient, MockSoroswapRouter};
+    use mocks::xycloans::{MockXycLoansPool, MockXycLoansPoolClient};
+    use flash_loan_provider::{BorrowLeg, FlashLoanProviderType};
+
+    /// Two-hop route through fresh pools, allowlisted on the executing leg's receiver
+    fn route_params(env: &Env, token: &Address, legs: Vec<BorrowLeg>) -> ArbitrageParams {
+        let executor = legs.last().unwrap().receiver;
+        let intermediate = Address::generate(env);
+        let swap = |token_in: &Address, token_out: &Address| SwapLeg {
+            token_in: token_in.clone(),
+            token_out: token_out.clone(),
+            splits: vec![
+                env,
+                PoolSplit { dex_type: DexType::Soroswap, pool: allowed_pool(env, &executor), fraction_bps: 10000 },
+            ],
+        };
+        allow_tokens(env, &executor, token, &intermediate);
+        ArbitrageParams {
+            legs,
+            swaps: vec![env, swap(token, &intermediate), swap(&intermediate, token)],
+            amount: None,
+            min_profit_bps: 0,
+            max_slippage_bps: 100,
+            expected_outputs: Vec::new(env),
+            soft_fail: false,
+            not_before_ledger: None,
+            valid_until_ledger: None,
+            deadline: None,
+            expected_reserves: Vec::new(env),
+            reserve_tolerance_bps: 0,
+        }
+    }
+
+    fn leg(env: &Env, lender: &Address, receiver: &Address, amount: i128) -> Vec<BorrowLeg> {
+        provider_leg(env, FlashLoanProviderType::Erc3156, lender, receiver, amount)
+    }
+
+    fn provider_leg(
+        env: &Env,
+        provider: FlashLoanProviderType,
+        lender: &Address,
+        receiver: &Address,
+        amount: i128,
+    ) -> Vec<BorrowLeg> {
+        vec![env, BorrowLeg {
+            provider,
+            lender: lender.clone(),
+            receiver: receiver.clone(),
+            amount,
+            max_fee: amount / 100,
+            charged_fee: None,
+        }]
+    }
+
+    /// Address allowlisted as a pool on `executor`
+    fn allowed_pool(env: &Env, executor: &Address) -> Address {
+        let pool = Address::generate(env);
+        env.as_contract(executor, || allowlist::set_pool_allowed(env, &pool, true));
+        pool
+    }
+
+    fn allow_tokens(env: &Env, executor: &Address, token_0: &Address, token_1: &Address) {
+        env.as_contract(executor, || {
+            allowlist::set_token_allowed(env, token_0, true);
+            allowlist::set_token_allowed(env, token_1, true);
+        });
+    }
+
+    /// Soroswap pair holding 1,000,000,000,000 of each token, allowlisted on `executor`
+    fn deep_pair(env: &Env, executor: &Address, token_0: &Address, token_1: &Address) -> Address {
+        let pair = env.register_contract(None, MockSoroswapPair);
+        let reserve = 1_000_000_000_000_i128;
+        MockSoroswapPairClient::new(env, &pair).init(token_0, token_1, &reserve, &reserve);
+        env.as_contract(executor, || allowlist::set_pool_allowed(env, &pair, true));
+        allow_tokens(env, executor, token_0, token_1);
+        pair
+    }
+
+    fn setup_flash_loan(env: &Env) -> (Address, Address, Address, Address, Bytes) {
+        env.mock_all_auths();
+        
+        let owner = Address::generate(env);
+        let executor = env.register_contract(None, FlashLoanExecutorContract);
+        FlashLoanExecutorContractClient::new(env, &executor).initialize(&owner);
+        
+        let lender = env.register_contract(None, MockFlashLender);
+        MockFlashLenderClient::new(env, &lender).init(&9);
+        
+        let token = mocks::create_token(env, &owner, &[(&lender, 1_000_000)]);
+        let params = route_params(env, &token, leg(env, &lender, &executor, 500_000));
+        
+        (owner, executor, lender, token, params.to_xdr(env))
+    }
+
+    /// Profitable triangular Soroswap cycle from `token`, allowlisted on `executor`
+    ///
+    /// Soroswap has one pair per token pair, so the cycle runs through two
+    /// fresh tokens `x` and `y`; `x` is 10% cheaper in the first pair than
+    /// through `y`. Returns the router and the swap legs.
+    fn soroswap_cycle(env: &Env, owner: &Address, executor: &Address, token: &Address) -> (Address, Vec<SwapLeg>) {
+        let client = FlashLoanExecutorContractClient::new(env, executor);
+        let router = env.register_contract(None, MockSoroswapRouter);
+        client.set_dex_router(owner, &DexType::Soroswap, &router);
+        
+        let x = mocks::create_token(env, owner, &[]);
+        let y = mocks::create_token(env, owner, &[]);
+        let pair_a = create_pair(env, &router, token, &x, 100_000_000, 110_000_000);
+        let pair_b = create_pair(env, &router, &x, &y, 100_000_000, 100_000_000);
+        let pair_c = create_pair(env, &router, &y, token, 100_000_000, 100_000_000);
+        allow_tokens(env, executor, token, &x);
+        allow_tokens(env, executor, &y, token);
+        for pair in [&pair_a, &pair_b, &pair_c] {
+            client.set_pool_allowed(owner, pair, &true);
+        }
+        
+        let swap = |pool: &Address, token_in: &Address, token_out: &Address| SwapLeg {
+            token_in: token_in.clone(),
+            token_out: token_out.clone(),
+            splits: vec![env, PoolSplit { dex_type: DexType::Soroswap, pool: pool.clone(), fraction_bps: 10000 }],
+        };
+        let swaps = vec![env, swap(&pair_a, token, &x), swap(&pair_b, &x, &y), swap(&pair_c, &y, token)];
+        (router, swaps)
+    }
+
+    /// Vault holding 1,000,000 of `token` and a profitable two-hop route for it
+    ///
+    /// The route buys `x` on Soroswap, where it is 10% cheaper, and sells it
+    /// on Phoenix. Both pools are allowlisted on `executor`.
+    fn vault_route(env: &Env, owner: &Address, executor: &Address, token: &Address) -> Vec<SwapLeg> {
+        let client = FlashLoanExecutorContractClient::new(env, executor);
+        let router = env.register_contract(None, MockSoroswapRouter);
+        client.set_dex_router(owner, &DexType::Soroswap, &router);
+        
+        let depositor = Address::generate(env);
+        token::StellarAssetClient::new(env, token).mint(&depositor, &1_000_000);
+        client.deposit_liquidity(&depositor, token, &1_000_000);
+        
+        let x = mocks::create_token(env, owner, &[]);
+        let pair = create_pair(env, &router, token, &x, 100_000_000, 110_000_000);
+        let pool = env.register_contract(None, MockPhoenixPool);
+        MockPhoenixPoolClient::new(env, &pool).init(&x, token, &100_000_000, &100_000_000, &30, &0);
+        token::StellarAssetClient::new(env, token).mint(&pool, &100_000_000);
+        allow_tokens(env, executor, token, &x);
+        client.set_pool_allowed(owner, &pair, &true);
+        client.set_pool_allowed(owner, &pool, &true);
+        
+        FlashLoanExecutorContract::two_hop_swaps(env, token, x, DexType::Soroswap, pair, DexType::Phoenix, pool)
+    }
+
+    /// Options for `execute_flash_loan_arbitrage` with no window, deadline
+    /// or reserve snapshot
+    fn execution_options(env: &Env) -> ExecutionOptions {
+        ExecutionOptions {
+            min_profit_bps: 0,
+            max_slippage_bps: 100,
+            expected_outputs: Vec::new(env),
+            not_before_ledger: None,
+            valid_until_ledger: None,
+            deadline: None,
+            expected_reserves: Vec::new(env),
+            reserve_tolerance_bps: 0,
+        }
+    }
+
+    /// Pool of the only split of leg `index`
+    fn leg_pool(swaps: &Vec<SwapLeg>, index: u32) -> Address {
+        swaps.get_unchecked(index).splits.get_unchecked(0).pool
+    }
+
+    #[test]
+    fn test_initialization() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, FlashLoanExecutorContract);
+        let client = FlashLoanExecutorContractClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        
+        // Initialize
+        let result = client.try_initialize(&owner);
+        assert!(result.is_ok());
+        
+        // Try to initialize again (should fail)
+        let result = client.try_initialize(&owner);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_flash_loan_rejects_non_owner_initiator() {
+        let env = Env::default();
+        let (_owner, executor, lender, token, data) = setup_flash_loan(&env);
+        let stranger = Address::generate(&env);
+        
+        let result = MockFlashLenderClient::new(&env, &lender)
+            .try_flash_loan(&stranger, &executor, &token, &500_000, &data);
+        assert!(result.is_err());
+        
+        let token_client = token::Client::new(&env, &token);
+        assert_eq!(token_client.balance(&lender), 1_000_000);
+        assert_eq!(token_client.balance(&executor), 0);
+    }
+
+    #[test]
+    fn test_failed_arbitrage_reverts_flash_loan() {
+        let env = Env::default();
+        let (owner, executor, lender, token, data) = setup_flash_loan(&env);
+        
+        // The route's pools are bare addresses rather than deployed pairs, so
+        // the first swap fails inside the callback and the whole loan unwinds
+        let result = MockFlashLenderClient::new(&env, &lender)
+            .try_flash_loan(&owner, &executor, &token, &500_000, &data);
+        assert!(result.is_err());
+        
+        let token_client = token::Client::new(&env, &token);
+        assert_eq!(token_client.balance(&lender), 1_000_000);
+        assert_eq!(token_client.balance(&executor), 0);
+        assert_eq!(
+            FlashLoanExecutorContractClient::new(&env, &executor).get_profit_balance(&token),
+            0
+        );
+    }
+
+    #[test]
+    fn test_failed_arbitrage_reverts_multi_asset_flash_loan() {
+        let env = Env::default();
+        let (owner, executor, lender, usdc, _data) = setup_flash_loan(&env);
+        let xlm = mocks::create_token(&env, &owner, &[(&lender, 1_000_000)]);
+        
+        let usdc_params = route_params(&env, &usdc, leg(&env, &lender, &executor, 100_000));
+        let xlm_params = route_params(&env, &xlm, leg(&env, &lender, &executor, 200_000));
+        let tokens = vec![&env, usdc.clone(), xlm.clone()];
+        let amounts = vec![&env, 100_000_i128, 200_000_i128];
+        let lender_client = MockFlashLenderClient::new(&env, &lender);
+        
+        // One route per asset is required
+        let data = vec![&env, usdc_params.clone()].to_xdr(&env);
+        let result = lender_client.try_flash_loan_multi(&owner, &executor, &tokens, &amounts, &data);
+        assert!(result.is_err());
+        
+        let data = vec![&env, usdc_params, xlm_params].to_xdr(&env);
+        let result = lender_client.try_flash_loan_multi(&owner, &executor, &tokens, &amounts, &data);
+        assert!(result.is_err());
+        
+        assert_eq!(token::Client::new(&env, &usdc).balance(&lender), 1_000_000);
+        assert_eq!(token::Client::new(&env, &xlm).balance(&lender), 1_000_000);
+        assert_eq!(token::Client::new(&env, &xlm).balance(&executor), 0);
+    }
+
+    #[test]
+    fn test_failed_batch_reverts_flash_loan() {
+        let env = Env::default();
+        let (owner, executor, lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        
+        let params = route_params(&env, &token, leg(&env, &lender, &executor, 500_000));
+        let batch_route = |amount: i128| arbitrage::BatchRoute {
+            swaps: params.swaps.clone(),
+            amount,
+            min_profit_bps: 0,
+            max_slippage_bps: 100,
+            expected_outputs: Vec::new(&env),
+        };
+        let mut batch = BatchParams {
+            legs: params.legs.clone(),
+            routes: vec![&env, batch_route(200_000), batch_route(500_000)],
+            policy: arbitrage::BatchPolicy::AllOrNothing,
+            not_before_ledger: None,
+            valid_until_ledger: None,
+            deadline: None,
+            expected_reserves: Vec::new(&env),
+            reserve_tolerance_bps: 0,
+        };
+        
+        // The pools are bare addresses rather than deployed pairs, so the
+        // first route fails and the whole batch unwinds
+        let result = MockFlashLenderClient::new(&env, &lender)
+            .try_flash_loan(&owner, &executor, &token, &500_000, &batch.clone().to_xdr(&env));
+        assert!(result.is_err());
+        assert_eq!(token::Client::new(&env, &token).balance(&lender), 1_000_000);
+        assert_eq!(client.get_last_batch_result(), None);
+        
+        // No route may trade more than the loan it reuses
+        batch.routes = vec![&env, batch_route(500_001)];
+        let result = MockFlashLenderClient::new(&env, &lender)
+            .try_flash_loan(&owner, &executor, &token, &500_000, &batch.to_xdr(&env));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_soft_fail_skips_stale_route() {
+        let env = Env::default();
+        let (owner, executor, lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let intermediate = Address::generate(&env);
+        
+        // Round trip through two equal pools can only lose to their fees
+        let swap = |token_in: &Address, token_out: &Address| SwapLeg {
+            token_in: token_in.clone(),
+            token_out: token_out.clone(),
+            splits: vec![&env, PoolSplit {
+                dex_type: DexType::Soroswap,
+                pool: deep_pair(&env, &executor, token_in, token_out),
+                fraction_bps: 10000,
+            }],
+        };
+        let mut params = route_params(&env, &token, leg(&env, &lender, &executor, 500_000));
+        params.swaps = vec![&env, swap(&token, &intermediate), swap(&intermediate, &token)];
+        params.soft_fail = true;
+        
+        // Skipping still owes the 450 fee, which needs booked profit
+        let result = MockFlashLenderClient::new(&env, &lender)
+            .try_flash_loan(&owner, &executor, &token, &500_000, &params.clone().to_xdr(&env));
+        assert!(result.is_err());
+        
+        token::StellarAssetClient::new(&env, &token).mint(&executor, &1_000);
+        env.as_contract(&executor, || FlashLoanExecutorContract::add_profit(&env, &token, 1_000));
+        
+        // Outside its ledger window the payload fails before quoting
+        env.ledger().with_mut(|ledger| ledger.sequence_number = 100);
+        params.valid_until_ledger = Some(99);
+        let result = MockFlashLenderClient::new(&env, &lender)
+            .try_flash_loan(&owner, &executor, &token, &500_000, &params.clone().to_xdr(&env));
+        assert!(result.is_err());
+        assert_eq!(client.get_last_outcome(&token), None);
+        
+        params.not_before_ledger = Some(100);
+        params.valid_until_ledger = Some(100);
+        
+        // A pool that moved more than 1% since it was priced aborts the trade
+        let pool = params.swaps.get_unchecked(0).splits.get_unchecked(0).pool;
+        let reserve = 1_000_000_000_000_i128;
+        let moved = reserve_guard::ReserveSnapshot { pool, reserve_in: reserve * 98 / 100, reserve_out: reserve };
+        params.expected_reserves = vec![&env, moved.clone()];
+        params.reserve_tolerance_bps = 100;
+        let result = MockFlashLenderClient::new(&env, &lender)
+            .try_flash_loan(&owner, &executor, &token, &500_000, &params.clone().to_xdr(&env));
+        assert!(result.is_err());
+        
+        params.expected_reserves = vec![&env, reserve_guard::ReserveSnapshot { reserve_in: reserve, ..moved }];
+        MockFlashLenderClient::new(&env, &lender)
+            .flash_loan(&owner, &executor, &token, &500_000, &params.to_xdr(&env));
+        assert_eq!(client.get_profit_balance(&token), 550);
+        assert_eq!(token::Client::new(&env, &token).balance(&lender), 1_000_450);
+        
+        match client.get_last_outcome(&token) {
+            Some(RouteExecution::Skipped(skipped)) => {
+                assert_eq!(skipped.reason, arbitrage::SkipReason::NoProfit);
+                assert_eq!(skipped.flash_fee, 450);
+                assert_eq!(skipped.leg_outputs.len(), 2);
+                assert!(skipped.expected_profit < 0);
+            }
+            other => panic!("expected a skipped route, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_profitable_arbitrage_through_soroswap() {
+        let env = Env::default();
+        let (owner, executor, lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let (_router, swaps) = soroswap_cycle(&env, &owner, &executor, &token);
+        let mut params = route_params(&env, &token, leg(&env, &lender, &executor, 500_000));
+        params.swaps = swaps.clone();
+        
+        MockFlashLenderClient::new(&env, &lender)
+            .flash_loan(&owner, &executor, &token, &500_000, &params.to_xdr(&env));
+        
+        // Lender got its fee, the rest stayed with the executor as profit
+        let token_client = token::Client::new(&env, &token);
+        assert_eq!(token_client.balance(&lender), 1_000_450);
+        let profit = client.get_profit_balance(&token);
+        assert!(profit > 0);
+        assert_eq!(token_client.balance(&executor), profit);
+        
+        match client.get_last_outcome(&token) {
+            Some(RouteExecution::Executed(result)) => assert_eq!(result.net_profit, profit),
+            other => panic!("expected an executed route, got {:?}", other),
+        }
+        assert_eq!(client.get_route_stats(&0).executions, 1);
+        assert_eq!(client.get_pair_stats(&leg_pool(&swaps, 0)).volume, 500_000);
+    }
+
+    #[test]
+    fn test_expected_outputs_catch_a_pool_pushed_before_the_loan() {
+        let env = Env::default();
+        let (owner, executor, lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let (router, swaps) = soroswap_cycle(&env, &owner, &executor, &token);
+        let mut params = route_params(&env, &token, leg(&env, &lender, &executor, 500_000));
+        params.swaps = swaps.clone();
+        let lender_client = MockFlashLenderClient::new(&env, &lender);
+        let router_client = mocks::soroswap::MockSoroswapRouterClient::new(&env, &router);
+        
+        // The bot prices every leg before submitting
+        let mut path = vec![&env, token.clone()];
+        for swap in swaps.iter() {
+            path.push_back(swap.token_out);
+        }
+        let mut expected_outputs = router_client.router_get_amounts_out(&500_000, &path);
+        expected_outputs.pop_front();
+        
+        // One expectation per leg or none at all
+        params.expected_outputs = vec![&env, expected_outputs.get_unchecked(0)];
+        let result = lender_client.try_flash_loan(&owner, &executor, &token, &500_000, &params.clone().to_xdr(&env));
+        assert!(result.is_err());
+        
+        // A sandwich buys x first, so the first leg fills about 4% worse
+        let attacker = Address::generate(&env);
+        let pair = MockSoroswapPairClient::new(&env, &leg_pool(&swaps, 0));
+        let x_out = router_client
+            .router_get_amounts_out(&2_000_000, &vec![&env, token.clone(), path.get_unchecked(1)])
+            .get_unchecked(1);
+        token::StellarAssetClient::new(&env, &token).mint(&pair.address, &2_000_000);
+        if pair.token_0() == token {
+            pair.swap(&0, &x_out, &attacker);
+        } else {
+            pair.swap(&x_out, &0, &attacker);
+        }
+        
+        params.expected_outputs = expected_outputs.clone();
+        let result = lender_client.try_flash_loan(&owner, &executor, &token, &500_000, &params.clone().to_xdr(&env));
+        assert!(result.is_err());
+        assert_eq!(client.get_last_outcome(&token), None);
+        
+        // Quoting in the transaction itself would accept the pushed price
+        params.expected_outputs = Vec::new(&env);
+        lender_client.flash_loan(&owner, &executor, &token, &500_000, &params.to_xdr(&env));
+        match client.get_last_outcome(&token) {
+            Some(RouteExecution::Executed(result)) => {
+                let first_leg = result.leg_outputs.get_unchecked(0);
+                assert!(first_leg < expected_outputs.get_unchecked(0) * 97 / 100);
+            }
+            other => panic!("expected an executed route, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_payload_deadline_bounds_the_swaps() {
+        let env = Env::default();
+        let (owner, executor, lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let (router, swaps) = soroswap_cycle(&env, &owner, &executor, &token);
+        env.ledger().with_mut(|ledger| ledger.timestamp = 1_000);
+        
+        let mut params = route_params(&env, &token, leg(&env, &lender, &executor, 500_000));
+        params.swaps = swaps.clone();
+        let lender_client = MockFlashLenderClient::new(&env, &lender);
+        
+        // A quote that expired a second ago fails before trading
+        params.deadline = Some(999);
+        let result = lender_client.try_flash_loan(&owner, &executor, &token, &500_000, &params.clone().to_xdr(&env));
+        assert!(result.is_err());
+        assert_eq!(token::Client::new(&env, &token).balance(&lender), 1_000_000);
+        
+        // The router enforces the same deadline on the swap itself
+        let path = vec![&env, token.clone(), swaps.get_unchecked(0).token_out];
+        let result = mocks::soroswap::MockSoroswapRouterClient::new(&env, &router)
+            .try_swap_exact_tokens_for_tokens(&1_000, &0, &path, &owner, &999);
+        assert!(result.is_err());
+        
+        // Up to and including the deadline the route runs
+        params.deadline = Some(1_000);
+        lender_client.flash_loan(&owner, &executor, &token, &500_000, &params.to_xdr(&env));
+        assert!(client.get_profit_balance(&token) > 0);
+    }
+
+    #[test]
+    fn test_entry_point_borrows_from_vault() {
+        let env = Env::default();
+        let (owner, executor, _lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let swaps = vault_route(&env, &owner, &executor, &token);
+        client.set_flash_fee(&owner, &9);
+        
+        let execute = |initiator: &Address, amount: i128| client.try_execute_flash_loan_arbitrage(
+            initiator,
+            &token,
+            &swaps.get_unchecked(0).token_out,
+            &amount,
+            &DexType::Soroswap,
+            &leg_pool(&swaps, 0),
+            &DexType::Phoenix,
+            &leg_pool(&swaps, 1),
+            &execution_options(&env),
+        );
+        
+        // Only the owner may trade, and no more than the vault holds
+        assert!(execute(&Address::generate(&env), 500_000).is_err());
+        assert_eq!(execute(&owner, 1_000_001), Err(Ok(Error::InsufficientLiquidity)));
+        
+        let profit = execute(&owner, 500_000).unwrap().unwrap();
+        assert!(profit > 0);
+        assert_eq!(client.get_profit_balance(&token), profit);
+        
+        // Depositors earned the 450 fee and the trade's gain stayed as profit
+        assert_eq!(client.max_flash_loan(&token), 1_000_450);
+        assert_eq!(token::Client::new(&env, &token).balance(&executor), 1_000_450 + profit);
+        assert_eq!(client.get_route_stats(&0).executions, 1);
+    }
+
+    #[test]
+    fn test_entry_point_rejects_an_expired_quote() {
+        let env = Env::default();
+        let (owner, executor, _lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let swaps = vault_route(&env, &owner, &executor, &token);
+        env.ledger().with_mut(|ledger| {
+            ledger.sequence_number = 100;
+            ledger.timestamp = 1_000;
+        });
+        
+        let execute = |options: &ExecutionOptions| client.try_execute_flash_loan_arbitrage(
+            &owner,
+            &token,
+            &swaps.get_unchecked(0).token_out,
+            &500_000,
+            &DexType::Soroswap,
+            &leg_pool(&swaps, 0),
+            &DexType::Phoenix,
+            &leg_pool(&swaps, 1),
+            options,
+        );
+        
+        let window = ExecutionOptions { valid_until_ledger: Some(99), ..execution_options(&env) };
+        assert_eq!(execute(&window), Err(Ok(Error::OpportunityExpired)));
+        let window = ExecutionOptions { not_before_ledger: Some(101), ..execution_options(&env) };
+        assert_eq!(execute(&window), Err(Ok(Error::OpportunityNotYetValid)));
+        let deadline = ExecutionOptions { deadline: Some(999), ..execution_options(&env) };
+        assert_eq!(execute(&deadline), Err(Ok(Error::OpportunityExpired)));
+        
+        // Nothing was borrowed or traded
+        assert_eq!(client.max_flash_loan(&token), 1_000_000);
+        assert_eq!(client.get_route_stats(&0).executions, 0);
+        
+        let valid = ExecutionOptions {
+            not_before_ledger: Some(100),
+            valid_until_ledger: Some(100),
+            deadline: Some(1_000),
+            ..execution_options(&env)
+        };
+        assert!(execute(&valid).unwrap().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_entry_point_rejects_moved_reserves() {
+        let env = Env::default();
+        let (owner, executor, _lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let swaps = vault_route(&env, &owner, &executor, &token);
+        let pair = leg_pool(&swaps, 0);
+        
+        let execute = |options: &ExecutionOptions| client.try_execute_flash_loan_arbitrage(
+            &owner,
+            &token,
+            &swaps.get_unchecked(0).token_out,
+            &500_000,
+            &DexType::Soroswap,
+            &pair,
+            &DexType::Phoenix,
+            &leg_pool(&swaps, 1),
+            options,
+        );
+        
+        // Priced from a pool holding 2% less of `token` than it does now
+        let moved = reserve_guard::ReserveSnapshot {
+            pool: pair.clone(),
+            reserve_in: 98_000_000,
+            reserve_out: 110_000_000,
+        };
+        let mut options = ExecutionOptions {
+            expected_reserves: vec![&env, moved.clone()],
+            reserve_tolerance_bps: 100,
+            ..execution_options(&env)
+        };
+        assert_eq!(execute(&options), Err(Ok(Error::ReservesMoved)));
+        assert_eq!(client.max_flash_loan(&token), 1_000_000);
+        
+        options.expected_reserves = vec![&env, reserve_guard::ReserveSnapshot { reserve_in: 100_000_000, ..moved }];
+        assert!(execute(&options).unwrap().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_batch_entry_point_borrows_from_vault() {
+        let env = Env::default();
+        let (owner, executor, lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let swaps = vault_route(&env, &owner, &executor, &token);
+        
+        let batch_route = |amount: i128| arbitrage::BatchRoute {
+            swaps: swaps.clone(),
+            amount,
+            min_profit_bps: 0,
+            max_slippage_bps: 100,
+            expected_outputs: Vec::new(&env),
+        };
+        let mut batch = BatchParams {
+            legs: Vec::new(&env),
+            routes: vec![&env, batch_route(200_000), batch_route(300_000)],
+            policy: arbitrage::BatchPolicy::AllOrNothing,
+            not_before_ledger: None,
+            valid_until_ledger: None,
+            deadline: None,
+            expected_reserves: Vec::new(&env),
+            reserve_tolerance_bps: 0,
+        };
+        
+        // Only the owner may run a batch
+        let stranger = Address::generate(&env);
+        assert!(client.try_execute_batch(&stranger, &token, &300_000, &batch).is_err());
+        
+        // Both routes reuse the one 300,000 loan
+        let result = client.execute_batch(&owner, &token, &300_000, &batch);
+        assert_eq!(result.outcomes.len(), 2);
+        assert!(result.total_profit > 0);
+        assert_eq!(client.get_profit_balance(&token), result.total_profit);
+        assert_eq!(client.get_last_batch_result(), Some(result));
+        
+        // No route may trade more than the loan, and only the vault lends here
+        batch.routes = vec![&env, batch_route(300_001)];
+        let result = client.try_execute_batch(&owner, &token, &300_000, &batch);
+        assert_eq!(result, Err(Ok(Error::InvalidFlashLoanAmount)));
+        batch.routes = vec![&env, batch_route(300_000)];
+        batch.legs = leg(&env, &lender, &executor, 300_000);
+        let result = client.try_execute_batch(&owner, &token, &300_000, &batch);
+        assert_eq!(result, Err(Ok(Error::InvalidRoute)));
+    }
+
+    #[test]
+    fn test_executor_runs_registered_route_only() {
+        let env = Env::default();
+        let (owner, executor, lender, token, data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let lender_client = MockFlashLenderClient::new(&env, &lender);
+        let bot = Address::generate(&env);
+        let intermediate = Address::generate(&env);
+        
+        let swap = |token_in: &Address, token_out: &Address| SwapLeg {
+            token_in: token_in.clone(),
+            token_out: token_out.clone(),
+            splits: vec![&env, PoolSplit {
+                dex_type: DexType::Soroswap,
+                pool: deep_pair(&env, &executor, token_in, token_out),
+                fraction_bps: 10000,
+            }],
+        };
+        let swaps = vec![&env, swap(&token, &intermediate), swap(&intermediate, &token)];
+        let route_id = client.register_route(&owner, &swaps, &0, &100);
+        assert_eq!(client.list_routes().len(), 1);
+        
+        // Skipping the unprofitable round trip pays the fee from booked profit
+        token::StellarAssetClient::new(&env, &token).mint(&executor, &1_000);
+        env.as_contract(&executor, || FlashLoanExecutorContract::add_profit(&env, &token, 1_000));
+        let call = route_registry::RouteCall {
+            legs: leg(&env, &lender, &executor, 500_000),
+            route_id,
+            amount: None,
+            overrides: route_registry::RouteOverrides {
+                min_profit_bps: None,
+                max_slippage_bps: None,
+                expected_outputs: Vec::new(&env),
+                soft_fail: true,
+                not_before_ledger: None,
+                valid_until_ledger: None,
+                deadline: None,
+                expected_reserves: Vec::new(&env),
+                reserve_tolerance_bps: 0,
+            },
+        };
+        
+        // Only authorized executors may run it
+        let result = lender_client.try_flash_loan(&bot, &executor, &token, &500_000, &call.clone().to_xdr(&env));
+        assert!(result.is_err());
+        
+        client.set_executor(&owner, &bot, &true);
+        assert!(client.is_executor(&bot));
+        lender_client.flash_loan(&bot, &executor, &token, &500_000, &call.clone().to_xdr(&env));
+        assert_eq!(client.get_profit_balance(&token), 550);
+        match client.get_last_outcome(&token) {
+            Some(RouteExecution::Skipped(skipped)) => assert_eq!(skipped.route_id, route_id),
+            other => panic!("expected a skipped route, got {:?}", other),
+        }
+        let stats = client.get_route_stats(&route_id);
+        assert_eq!((stats.executions, stats.skipped, stats.fees_paid), (0, 1, 450));
+        
+        // Executors cannot submit ad-hoc routes
+        let result = lender_client.try_flash_loan(&bot, &executor, &token, &500_000, &data);
+        assert!(result.is_err());
+        
+        // Statistics are reset by the owner only
+        assert!(client.try_reset_route_stats(&bot, &route_id).is_err());
+        client.reset_route_stats(&owner, &route_id);
+        assert_eq!(client.get_route_stats(&route_id).skipped, 0);
+        
+        // Nor run a disabled route
+        client.set_route_enabled(&owner, &route_id, &false);
+        assert!(!client.get_route(&route_id).unwrap().enabled);
+        let result = lender_client.try_flash_loan(&bot, &executor, &token, &500_000, &call.to_xdr(&env));
+        assert!(result.is_err());
+        assert_eq!(client.get_profit_balance(&token), 550);
+    }
+
+    #[test]
+    fn test_executor_runs_registered_route_by_id() {
+        let env = Env::default();
+        let (owner, executor, lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let bot = Address::generate(&env);
+        client.set_executor(&owner, &bot, &true);
+        let swaps = vault_route(&env, &owner, &executor, &token);
+        let pair = leg_pool(&swaps, 0);
+        
+        let route_id = client.register_route(&owner, &swaps, &0, &100);
+        let overrides = route_registry::RouteOverrides {
+            min_profit_bps: None,
+            max_slippage_bps: None,
+            expected_outputs: Vec::new(&env),
+            soft_fail: false,
+            not_before_ledger: None,
+            valid_until_ledger: None,
+            deadline: None,
+            expected_reserves: Vec::new(&env),
+            reserve_tolerance_bps: 0,
+        };
+        
+        // Only executors may call it, and not for a route never registered
+        let stranger = Address::generate(&env);
+        assert!(client.try_execute_route(&stranger, &route_id, &300_000, &overrides).is_err());
+        let result = client.try_execute_route(&bot, &(route_id + 1), &300_000, &overrides);
+        assert_eq!(result, Err(Ok(Error::RouteNotFound)));
+        
+        match client.execute_route(&bot, &route_id, &300_000, &overrides) {
+            RouteExecution::Executed(result) => {
+                assert!(result.net_profit > 0);
+                assert_eq!(client.get_profit_balance(&token), result.net_profit);
+            }
+            other => panic!("expected an executed route, got {:?}", other),
+        }
+        assert_eq!(client.get_pair_stats(&pair).volume, 300_000);
+        
+        // A flash loan payload may trade less than it borrows, never more
+        let mut call = route_registry::RouteCall {
+            legs: leg(&env, &lender, &executor, 500_000),
+            route_id,
+            amount: Some(200_000),
+            overrides,
+        };
+        let lender_client = MockFlashLenderClient::new(&env, &lender);
+        lender_client.flash_loan(&bot, &executor, &token, &500_000, &call.clone().to_xdr(&env));
+        assert_eq!(client.get_pair_stats(&pair).volume, 500_000);
+        assert_eq!(client.get_route_stats(&route_id).executions, 2);
+        
+        call.amount = Some(500_001);
+        let result = lender_client.try_flash_loan(&bot, &executor, &token, &500_000, &call.to_xdr(&env));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_soft_fail_route_skips_before_borrowing_from_the_vault() {
+        let env = Env::default();
+        let (owner, executor, _lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let bot = Address::generate(&env);
+        client.set_executor(&owner, &bot, &true);
+        client.set_flash_fee(&owner, &30);
+        let depositor = Address::generate(&env);
+        token::StellarAssetClient::new(&env, &token).mint(&depositor, &1_000_000);
+        client.deposit_liquidity(&depositor, &token, &1_000_000);
+        
+        // Round trip through two equal pools can only lose to their fees
+        let intermediate = Address::generate(&env);
+        let swap = |token_in: &Address, token_out: &Address| SwapLeg {
+            token_in: token_in.clone(),
+            token_out: token_out.clone(),
+            splits: vec![&env, PoolSplit {
+                dex_type: DexType::Soroswap,
+                pool: deep_pair(&env, &executor, token_in, token_out),
+                fraction_bps: 10000,
+            }],
+        };
+        let swaps = vec![&env, swap(&token, &intermediate), swap(&intermediate, &token)];
+        let route_id = client.register_route(&owner, &swaps, &0, &100);
+        let overrides = route_registry::RouteOverrides {
+            min_profit_bps: None,
+            max_slippage_bps: None,
+            expected_outputs: Vec::new(&env),
+            soft_fail: true,
+            not_before_ledger: None,
+            valid_until_ledger: None,
+            deadline: None,
+            expected_reserves: Vec::new(&env),
+            reserve_tolerance_bps: 0,
+        };
+        
+        // The quote counts the vault's 1_500 fee, but with no profit booked
+        // to pay it the skip must not borrow at all
+        match client.execute_route(&bot, &route_id, &500_000, &overrides) {
+            RouteExecution::Skipped(skipped) => {
+                assert_eq!(skipped.reason, arbitrage::SkipReason::NoProfit);
+                assert_eq!(skipped.flash_fee, 0);
+                assert!(skipped.expected_profit < -1_500);
+            }
+            other => panic!("expected a skipped route, got {:?}", other),
+        }
+        assert_eq!(client.max_flash_loan(&token), 1_000_000);
+        assert_eq!(client.get_profit_balance(&token), 0);
+        assert!(matches!(client.get_last_outcome(&token), Some(RouteExecution::Skipped(_))));
+        let stats = client.get_route_stats(&route_id);
+        assert_eq!((stats.executions, stats.skipped, stats.fees_paid), (0, 1, 0));
+    }
+
+    #[test]
+    fn test_failed_arbitrage_reverts_every_aggregated_leg() {
+        let env = Env::default();
+        let (owner, executor, lender_b, token, _data) = setup_flash_loan(&env);
+        
+        // A second deployment with the same owner relays the first leg
+        let relay = env.register_contract(None, FlashLoanExecutorContract);
+        FlashLoanExecutorContractClient::new(&env, &relay).initialize(&owner);
+        let lender_a = env.register_contract(None, MockFlashLender);
+        MockFlashLenderClient::new(&env, &lender_a).init(&5);
+        token::StellarAssetClient::new(&env, &token).mint(&lender_a, &300_000);
+        
+        let mut legs = leg(&env, &lender_a, &relay, 300_000);
+        legs.append(&leg(&env, &lender_b, &executor, 700_000));
+        let data = route_params(&env, &token, legs).to_xdr(&env);
+        
+        // The relay forwards leg A and opens leg B; the swap then fails in
+        // the executor and both loans unwind together
+        let result = MockFlashLenderClient::new(&env, &lender_a)
+            .try_flash_loan(&owner, &relay, &token, &300_000, &data);
+        assert!(result.is_err());
+        
+        let token_client = token::Client::new(&env, &token);
+        assert_eq!(token_client.balance(&lender_a), 300_000);
+        assert_eq!(token_client.balance(&lender_b), 1_000_000);
+        assert_eq!(token_client.balance(&relay), 0);
+        assert_eq!(token_client.balance(&executor), 0);
+        
+        // A leg for a different amount than was advanced is rejected
+        let mut legs = leg(&env, &lender_a, &relay, 250_000);
+        legs.append(&leg(&env, &lender_b, &executor, 700_000));
+        let data = route_params(&env, &token, legs).to_xdr(&env);
+        let result = MockFlashLenderClient::new(&env, &lender_a)
+            .try_flash_loan(&owner, &relay, &token, &300_000, &data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_relay_is_repaid_the_fee_it_was_charged() {
+        let env = Env::default();
+        let (owner, executor, lender_b, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let (_router, swaps) = soroswap_cycle(&env, &owner, &executor, &token);
+        
+        let relay = env.register_contract(None, FlashLoanExecutorContract);
+        FlashLoanExecutorContractClient::new(&env, &relay).initialize(&owner);
+        let lender_a = env.register_contract(None, MockFlashLender);
+        MockFlashLenderClient::new(&env, &lender_a).init(&5);
+        token::StellarAssetClient::new(&env, &token).mint(&lender_a, &300_000);
+        
+        let mut legs = leg(&env, &lender_a, &relay, 300_000);
+        legs.append(&leg(&env, &lender_b, &executor, 700_000));
+        let mut params = route_params(&env, &token, legs);
+        params.swaps = swaps;
+        
+        MockFlashLenderClient::new(&env, &lender_a)
+            .flash_loan(&owner, &relay, &token, &300_000, &params.to_xdr(&env));
+        
+        // Lender A charged 150 against a 3,000 cap; the relay is repaid
+        // exactly that and keeps nothing
+        let token_client = token::Client::new(&env, &token);
+        assert_eq!(token_client.balance(&lender_a), 300_150);
+        assert_eq!(token_client.balance(&lender_b), 1_000_630);
+        assert_eq!(token_client.balance(&relay), 0);
+        let profit = client.get_profit_balance(&token);
+        assert!(profit > 0);
+        assert_eq!(token_client.balance(&executor), profit);
+    }
+
+    #[test]
+    fn test_blend_loan_runs_the_staged_payload() {
+        let env = Env::default();
+        let (owner, executor, _lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let (_router, swaps) = soroswap_cycle(&env, &owner, &executor, &token);
+        
+        let pool = env.register_contract(None, MockBlendPool);
+        token::StellarAssetClient::new(&env, &token).mint(&pool, &1_000_000);
+        let legs = provider_leg(&env, FlashLoanProviderType::Blend, &pool, &executor, 500_000);
+        let mut params = route_params(&env, &token, legs);
+        params.swaps = swaps;
+        let blend = MockBlendPoolClient::new(&env, &pool);
+        
+        // Blend forwards no payload, so nothing runs until one is staged
+        assert!(blend.try_flash_loan(&owner, &executor, &token, &500_000).is_err());
+        
+        client.stage_flash_loan(&owner, &params.to_xdr(&env));
+        blend.flash_loan(&owner, &executor, &token, &500_000);
+        
+        // The owner repaid the pool with the principal handed back to them
+        let token_client = token::Client::new(&env, &token);
+        assert_eq!(token_client.balance(&pool), 1_000_000);
+        assert_eq!(token_client.balance(&owner), 0);
+        let profit = client.get_profit_balance(&token);
+        assert!(profit > 0);
+        assert_eq!(token_client.balance(&executor), profit);
+        
+        // The staged payload was consumed
+        assert!(blend.try_flash_loan(&owner, &executor, &token, &500_000).is_err());
+    }
+
+    #[test]
+    fn test_xycloans_pulls_its_repayment_after_the_callback() {
+        let env = Env::default();
+        let (owner, executor, _lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let (_router, swaps) = soroswap_cycle(&env, &owner, &executor, &token);
+        
+        let pool = env.register_contract(None, MockXycLoansPool);
+        MockXycLoansPoolClient::new(&env, &pool).init(&token);
+        token::StellarAssetClient::new(&env, &token).mint(&pool, &1_000_000);
+        let legs = provider_leg(&env, FlashLoanProviderType::XycLoans, &pool, &executor, 500_000);
+        let mut params = route_params(&env, &token, legs);
+        params.swaps = swaps;
+        
+        client.stage_flash_loan(&owner, &params.to_xdr(&env));
+        MockXycLoansPoolClient::new(&env, &pool).borrow(&owner, &executor, &500_000);
+        
+        // 0.08% fee; the profit booked excludes the repayment pulled afterwards
+        let token_client = token::Client::new(&env, &token);
+        assert_eq!(token_client.balance(&pool), 1_000_400);
+        let profit = client.get_profit_balance(&token);
+        assert!(profit > 0);
+        assert_eq!(token_client.balance(&executor), profit);
+    }
+
+    #[test]
+    fn test_soroswap_flash_swap_funds_the_route() {
+        let env = Env::default();
+        let (owner, executor, _lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let (router, swaps) = soroswap_cycle(&env, &owner, &executor, &token);
+        
+        // The lending pair is on the stack during the callback, so the
+        // route cannot trade through it
+        let z = mocks::create_token(&env, &owner, &[]);
+        let pair = create_pair(&env, &router, &token, &z, 10_000_000, 10_000_000);
+        let legs = provider_leg(&env, FlashLoanProviderType::SoroswapFlashSwap, &pair, &executor, 500_000);
+        let mut params = route_params(&env, &token, legs);
+        params.swaps = swaps;
+        
+        MockSoroswapPairClient::new(&env, &pair)
+            .flash_swap(&owner, &token, &500_000, &executor, &params.to_xdr(&env));
+        
+        // ceil(500,000 * 3 / 997) = 1,505 keeps k
+        let token_client = token::Client::new(&env, &token);
+        assert_eq!(token_client.balance(&pair), 10_001_505);
+        let profit = client.get_profit_balance(&token);
+        assert!(profit > 0);
+        assert_eq!(token_client.balance(&executor), profit);
+    }
+
+    #[test]
+    fn test_fee_schedule_prices_simulation() {
+        let env = Env::default();
+        let (owner, executor, lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let stranger = Address::generate(&env);
+        let intermediate = Address::generate(&env);
+        let pool_a = deep_pair(&env, &executor, &token, &intermediate);
+        let pool_b = deep_pair(&env, &executor, &intermediate, &token);
+        
+        // Simulation refuses to guess a fee for an unknown lender
+        let result = client.try_simulate_arbitrage(
+            &token, &intermediate, &1_000_000, &lender, &DexType::Soroswap, &pool_a, &DexType::Soroswap, &pool_b,
+        );
+        assert!(result.is_err());
+        
+        let schedule = FeeSchedule {
+            model: fee_schedule::FeeModel::FlatBps(9),
+            min_fee: 1_000,
+        };
+        assert!(client.try_set_fee_schedule(&stranger, &lender, &schedule).is_err());
+        client.set_fee_schedule(&owner, &lender, &schedule);
+        assert_eq!(client.get_fee_schedule(&lender), Some(schedule));
+        
+        // Round trip through two equal pools: 1,000,000 -> 996,999 -> 994,007,
+        // less the 1,000 minimum fee
+        let report = client.simulate_arbitrage(
+            &token, &intermediate, &1_000_000, &lender, &DexType::Soroswap, &pool_a, &DexType::Soroswap, &pool_b,
+        );
+        assert_eq!(report.net_profit, -6_993);
+        assert_eq!(report.gross_profit, -5_993);
+        assert_eq!(report.flash_loan_fee, 1_000);
+        assert_eq!(report.legs.len(), 2);
+        
+        let first = report.legs.get(0).unwrap();
+        assert_eq!(first.amount_out, 996_999);
+        assert_eq!(first.dex_fee, 3_000);
+        let split = first.splits.get(0).unwrap();
+        assert_eq!((split.reserve_in, split.reserve_out), (1_000_000_000_000, 1_000_000_000_000));
+        
+        client.remove_fee_schedule(&owner, &lender);
+        assert_eq!(client.get_fee_schedule(&lender), None);
+    }
+
+    #[test]
+    fn test_simulate_multi_hop_route() {
+        let env = Env::default();
+        let (owner, executor, lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let schedule = FeeSchedule {
+            model: fee_schedule::FeeModel::FlatBps(9),
+            min_fee: 0,
+        };
+        client.set_fee_schedule(&owner, &lender, &schedule);
+        
+        let xlm = Address::generate(&env);
+        let aqua = Address::generate(&env);
+        let swap = |token_in: &Address, token_out: &Address| SwapLeg {
+            token_in: token_in.clone(),
+            token_out: token_out.clone(),
+            splits: vec![&env, PoolSplit {
+                dex_type: DexType::Soroswap,
+                pool: deep_pair(&env, &executor, token_in, token_out),
+                fraction_bps: 10000,
+            }],
+        };
+        
+        // 1,000,000 -> 996,999 -> 994,007 -> 991,023, less the 900 fee
+        let cycle = vec![&env, swap(&token, &xlm), swap(&xlm, &aqua), swap(&aqua, &token)];
+        assert_eq!(client.simulate_route(&token, &1_000_000, &lender, &cycle).net_profit, -9_877);
+        
+        // With a shallow middle pool (10,000,000 a side) the whole 996,999
+        // walks far down one curve: 904,136 out, 901,422 back
+        let shallow_pool = || {
+            let pool = env.register_contract(None, MockSoroswapPair);
+            MockSoroswapPairClient::new(&env, &pool).init(&xlm, &aqua, &10_000_000, &10_000_000);
+            client.set_pool_allowed(&owner, &pool, &true);
+            pool
+        };
+        let shallow_split = |pool: Address, fraction_bps: u32| PoolSplit {
+            dex_type: DexType::Soroswap,
+            pool,
+            fraction_bps,
+        };
+        let mut middle = swap(&xlm, &aqua);
+        middle.splits = vec![&env, shallow_split(shallow_pool(), 10000)];
+        let cycle = vec![&env, swap(&token, &xlm), middle, swap(&aqua, &token)];
+        let unsplit = client.simulate_route(&token, &1_000_000, &lender, &cycle);
+        assert_eq!(unsplit.legs.get(1).unwrap().amount_out, 904_136);
+        assert_eq!(unsplit.net_profit, -99_478);
+        
+        // Splitting it 40/60 across two such pools moves each curve less
+        let mut middle = swap(&xlm, &aqua);
+        middle.splits = vec![
+            &env,
+            shallow_split(shallow_pool(), 4000),
+            shallow_split(shallow_pool(), 6000),
+        ];
+        let cycle = vec![&env, swap(&token, &xlm), middle, swap(&aqua, &token)];
+        let split = client.simulate_route(&token, &1_000_000, &lender, &cycle);
+        let split_leg = split.legs.get(1).unwrap();
+        assert_eq!(split_leg.splits.get(0).unwrap().amount_in, 398_799);
+        assert_eq!(split_leg.splits.get(0).unwrap().amount_out, 382_398);
+        assert_eq!(split_leg.splits.get(1).unwrap().amount_in, 598_200);
+        assert_eq!(split_leg.splits.get(1).unwrap().amount_out, 562_837);
+        assert_eq!(split_leg.amount_out, 945_235);
+        assert_eq!(split.net_profit, -58_502);
+        assert!(split.net_profit > unsplit.net_profit);
+        
+        // A route that does not return to the borrowed token is rejected
+        let open = vec![&env, swap(&token, &xlm), swap(&xlm, &aqua)];
+        assert!(client.try_simulate_route(&token, &1_000_000, &lender, &open).is_err());
+        
+        // A pool declared on the wrong DEX cannot be quoted
+        let mut mislabeled = swap(&xlm, &token);
+        mislabeled.splits.set(0, PoolSplit { dex_type: DexType::Aquarius, ..mislabeled.splits.get(0).unwrap() });
+        let mislabeled = vec![&env, swap(&token, &xlm), mislabeled];
+        assert!(client.try_simulate_route(&token, &1_000_000, &lender, &mislabeled).is_err());
+    }
+
+    #[test]
+    fn test_simulation_reports_price_impact() {
+        let env = Env::default();
+        let (owner, executor, lender, token, _data) = setup_flash_loan(&env);
+        let client = FlashLoanExecutorContractClient::new(&env, &executor);
+        let schedule = FeeSchedule {
+            model: fee_schedule::FeeModel::FlatBps(9),
+            min_fee: 0,
+        };
+        client.set_fee_schedule(&owner, &lender, &schedule);
+        
+        // A 1,000,000 trade into a pool holding 10,000,000 of each side
+        let xlm = Address::generate(&env);
+        let shallow = env.register_contract(None, MockSoroswapPair);
+        MockSoroswapPairClient::new(&env, &shallow).init(&token, &xlm, &10_000_000, &10_000_000);
+        client.set_pool_allowed(&owner, &shallow, &true);
+        let deep = deep_pair(&env, &executor, &xlm, &token);
+        
+        let report = client.simulate_arbitrage(
+            &token, &xlm, &1_000_000, &lender, &DexType::Soroswap, &shallow, &DexType::Soroswap, &deep,
+        );
+        
+        // 997,000 at the pre-trade price, 906,610 after walking the curve
+        let first = report.legs.get(0).unwrap();
+        assert_eq!(first.amount_out, 906_610);
+        assert_eq!(first.price_impact_bps, 906);
+        assert_eq!(first.splits.get(0).unwrap().spot_amount_out, 997_000);
+        
+        // The deep pool barely moves
+        assert_eq!(report.legs.get(1).unwrap().price_impact_bps, 0);
+        assert_eq!(report.amount_in, 1_000_000);
+        assert_eq!(report.amount_out, report.legs.get(1).unwrap().amount_out);
+        assert_eq!(report.net_profit, report.amount_out - 1_000_000 - 900);
+        
+        // Only the owner can take a pool off the allowlist, which stops
+        // routes through it
+        let stranger = Address::generate(&env);
+        assert!(client.try_set_pool_allowed(&stranger, &shallow, &false).is_err());
+        client.set_pool_allowed(&owner, &shallow, &false);
+        assert!(!client.is_pool_allowed(&shallow));
+        let result = client.try_simulate_arbitrage(
+            &token, &xlm, &1_000_000, &lender, &DexType::Soroswap, &shallow, &DexType::Soroswap, &deep,
+        );
+        assert_eq!(result, Err(Ok(Error::PoolNotAllowed)));
     }
 }
@@ -0,0 +1,164 @@
+use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env, Symbol, Vec};
+use crate::dex_interface::{constant_product_output, stable_swap_output, AquariusPool, StablePool};
+
+const POOL_TYPE: Symbol = symbol_short!("pool_type");
+const TOKENS: Symbol = symbol_short!("tokens");
+const RESERVES: Symbol = symbol_short!("reserves");
+const FEE: Symbol = symbol_short!("fee");
+const AMP: Symbol = symbol_short!("amp");
+
+/// Aquarius pool stand-in, pricing as a constant-product or stableswap
+/// pool depending on the `pool_type` it was created with
+#[contract]
+pub struct MockAquariusPool;
+
+#[contractimpl]
+impl MockAquariusPool {
+    pub fn init(
+        env: Env,
+        pool_type: Symbol,
+        tokens: Vec<Address>,
+        reserves: Vec<u128>,
+        fee_fraction: u32,
+        a: u128,
+    ) {
+        env.storage().instance().set(&POOL_TYPE, &pool_type);
+        env.storage().instance().set(&TOKENS, &tokens);
+        env.storage().instance().set(&RESERVES, &reserves);
+        env.storage().instance().set(&FEE, &fee_fraction);
+        env.storage().instance().set(&AMP, &a);
+    }
+}
+
+#[contractimpl]
+impl AquariusPool for MockAquariusPool {
+    fn pool_type(env: Env) -> Symbol {
+        env.storage().instance().get(&POOL_TYPE).unwrap()
+    }
+
+    fn get_tokens(env: Env) -> Vec<Address> {
+        env.storage().instance().get(&TOKENS).unwrap()
+    }
+
+    fn get_reserves(env: Env) -> Vec<u128> {
+        env.storage().instance().get(&RESERVES).unwrap()
+    }
+
+    fn get_fee_fraction(env: Env) -> u32 {
+        env.storage().instance().get(&FEE).unwrap()
+    }
+
+    fn a(env: Env) -> u128 {
+        env.storage().instance().get(&AMP).unwrap()
+    }
+
+    fn swap(env: Env, user: Address, in_idx: u32, out_idx: u32, in_amount: u128, out_min: u128) -> u128 {
+        user.require_auth();
+
+        let mut reserves = Self::get_reserves(env.clone());
+        let mut balances: Vec<i128> = Vec::new(&env);
+        for reserve in reserves.iter() {
+            balances.push_back(reserve as i128);
+        }
+        let fee = Self::get_fee_fraction(env.clone());
+        let amount_out = if Self::pool_type(env.clone()) == Symbol::new(&env, "stable") {
+            let mut decimals: Vec<u32> = Vec::new(&env);
+            for token in Self::get_tokens(env.clone()).iter() {
+                decimals.push_back(token::Client::new(&env, &token).decimals());
+            }
+            let pool = StablePool { balances, decimals, amp: Self::a(env.clone()) as i128, fee_bps: fee };
+            stable_swap_output(&env, &pool, in_idx, out_idx, in_amount as i128).unwrap()
+        } else {
+            let reserve_in = balances.get_unchecked(in_idx);
+            let reserve_out = balances.get_unchecked(out_idx);
+            constant_product_output(in_amount as i128, reserve_in, reserve_out, fee).unwrap()
+        } as u128;
+        if amount_out < out_min {
+            panic!("out amount below min");
+        }
+
+        let this = env.current_contract_address();
+        let tokens = Self::get_tokens(env.clone());
+        let token_in = token::Client::new(&env, &tokens.get_unchecked(in_idx));
+        let token_out = token::Client::new(&env, &tokens.get_unchecked(out_idx));
+        token_in.transfer(&user, &this, &(in_amount as i128));
+        token_out.transfer(&this, &user, &(amount_out as i128));
+
+        reserves.set(in_idx, reserves.get_unchecked(in_idx) + in_amount);
+        reserves.set(out_idx, reserves.get_unchecked(out_idx) - amount_out);
+        env.storage().instance().set(&RESERVES, &reserves);
+
+        amount_out
+    }
+}
+
+pub use router::{MockAquariusRouter, MockAquariusRouterClient};
+
+/// Separate module since the router and pool both export `swap`
+mod router {
+    use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Map, Vec};
+    use crate::dex_interface::{AquariusPoolClient, AquariusRouter};
+
+    /// Aquarius router stand-in indexing pools registered with `add_pool`
+    #[contract]
+    pub struct MockAquariusRouter;
+
+    #[contractimpl]
+    impl MockAquariusRouter {
+        pub fn add_pool(env: Env, pool: Address) {
+            let tokens = AquariusPoolClient::new(&env, &pool).get_tokens();
+            let mut pools = Self::get_pools(env.clone(), tokens.clone());
+            let index = BytesN::from_array(&env, &[pools.len() as u8; 32]);
+            pools.set(index, pool);
+            env.storage().instance().set(&tokens, &pools);
+        }
+    }
+
+    #[contractimpl]
+    impl AquariusRouter for MockAquariusRouter {
+        fn get_pools(env: Env, tokens: Vec<Address>) -> Map<BytesN<32>, Address> {
+            env.storage().instance().get(&tokens).unwrap_or(Map::new(&env))
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        fn swap(
+            env: Env,
+            user: Address,
+            tokens: Vec<Address>,
+            token_in: Address,
+            token_out: Address,
+            pool_index: BytesN<32>,
+            in_amount: u128,
+            out_min: u128,
+        ) -> u128 {
+            user.require_auth();
+
+            let pools = Self::get_pools(env.clone(), tokens.clone());
+            let pool = pools.get(pool_index).expect("pool not found");
+            let in_idx = tokens.first_index_of(&token_in).unwrap();
+            let out_idx = tokens.first_index_of(&token_out).unwrap();
+            AquariusPoolClient::new(&env, &pool).swap(&user, &in_idx, &out_idx, &in_amount, &out_min)
+        }
+    }
+}
+
+/// Deploy a pool of `pool_type` holding its reserves as balances, registered
+/// with `router`
+pub fn create_pool(
+    env: &Env,
+    router: &Address,
+    pool_type: &str,
+    tokens: &Vec<Address>,
+    reserves: &Vec<u128>,
+    fee_fraction: u32,
+    a: u128,
+) -> Address {
+    let pool = env.register_contract(None, MockAquariusPool);
+    MockAquariusPoolClient::new(env, &pool)
+        .init(&Symbol::new(env, pool_type), tokens, reserves, &fee_fraction, &a);
+    for (token, reserve) in tokens.iter().zip(reserves.iter()) {
+        token::StellarAssetClient::new(env, &token).mint(&pool, &(reserve as i128));
+    }
+    MockAquariusRouterClient::new(env, router).add_pool(&pool);
+    pool
+}
@@ -0,0 +1,33 @@
+use soroban_sdk::{contract, contractimpl, token, Address, Env};
+use crate::flash_loan::ExecOpReceiverClient;
+
+/// Blend pool stand-in for `submit_with_flash_loan`
+/// 
+/// Lends out its own token balance free of charge to `contract`, calls
+/// back `exec_op`, then processes a `Repay` request for the full amount,
+/// which pulls the repayment from `from` like the real pool.
+#[contract]
+pub struct MockBlendPool;
+
+#[contractimpl]
+impl MockBlendPool {
+    pub fn flash_loan(env: Env, from: Address, contract: Address, asset: Address, amount: i128) {
+        from.require_auth();
+
+        let pool = env.current_contract_address();
+        let token_client = token::Client::new(&env, &asset);
+        let balance_before = token_client.balance(&pool);
+        if amount > balance_before {
+            panic!("insufficient liquidity");
+        }
+        token_client.transfer(&pool, &contract, &amount);
+
+        ExecOpReceiverClient::new(&env, &contract).exec_op(&from, &asset, &amount, &0);
+
+        // Repay request: the liability is settled from `from`'s balance
+        token_client.transfer(&from, &pool, &amount);
+        if token_client.balance(&pool) < balance_before {
+            panic!("flash loan not repaid");
+        }
+    }
+}
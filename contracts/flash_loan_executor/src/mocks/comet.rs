@@ -0,0 +1,106 @@
+use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env, Symbol, Vec};
+use crate::dex_interface::{weighted_output, weighted_spot_price, CometPool};
+
+const TOKENS: Symbol = symbol_short!("tokens");
+const WEIGHTS: Symbol = symbol_short!("weights");
+const BALANCES: Symbol = symbol_short!("balances");
+const SWAP_FEE: Symbol = symbol_short!("swap_fee");
+
+/// Comet weighted pool stand-in
+///
+/// `weights` are normalized and, like `swap_fee`, scaled by `COMET_SCALE`.
+#[contract]
+pub struct MockCometPool;
+
+#[contractimpl]
+impl MockCometPool {
+    pub fn init(env: Env, tokens: Vec<Address>, weights: Vec<i128>, balances: Vec<i128>, swap_fee: i128) {
+        env.storage().instance().set(&TOKENS, &tokens);
+        env.storage().instance().set(&WEIGHTS, &weights);
+        env.storage().instance().set(&BALANCES, &balances);
+        env.storage().instance().set(&SWAP_FEE, &swap_fee);
+    }
+}
+
+impl MockCometPool {
+    fn index(env: &Env, token: &Address) -> u32 {
+        Self::get_tokens(env.clone()).first_index_of(token).unwrap()
+    }
+}
+
+#[contractimpl]
+impl CometPool for MockCometPool {
+    fn get_tokens(env: Env) -> Vec<Address> {
+        env.storage().instance().get(&TOKENS).unwrap()
+    }
+
+    fn get_balance(env: Env, token: Address) -> i128 {
+        let balances: Vec<i128> = env.storage().instance().get(&BALANCES).unwrap();
+        balances.get_unchecked(Self::index(&env, &token))
+    }
+
+    fn get_normalized_weight(env: Env, token: Address) -> i128 {
+        let weights: Vec<i128> = env.storage().instance().get(&WEIGHTS).unwrap();
+        weights.get_unchecked(Self::index(&env, &token))
+    }
+
+    fn get_swap_fee(env: Env) -> i128 {
+        env.storage().instance().get(&SWAP_FEE).unwrap()
+    }
+
+    fn get_spot_price(env: Env, token_in: Address, token_out: Address) -> i128 {
+        weighted_spot_price(
+            Self::get_balance(env.clone(), token_in.clone()),
+            Self::get_normalized_weight(env.clone(), token_in),
+            Self::get_balance(env.clone(), token_out.clone()),
+            Self::get_normalized_weight(env.clone(), token_out),
+            Self::get_swap_fee(env),
+        )
+        .unwrap()
+    }
+
+    fn swap_exact_amount_in(
+        env: Env,
+        token_in: Address,
+        token_amount_in: i128,
+        token_out: Address,
+        min_amount_out: i128,
+        max_price: i128,
+        user: Address,
+    ) -> (i128, i128) {
+        user.require_auth();
+        if Self::get_spot_price(env.clone(), token_in.clone(), token_out.clone()) > max_price {
+            panic!("spot price above max price");
+        }
+
+        let amount_out = weighted_output(
+            Self::get_balance(env.clone(), token_in.clone()),
+            Self::get_normalized_weight(env.clone(), token_in.clone()),
+            Self::get_balance(env.clone(), token_out.clone()),
+            Self::get_normalized_weight(env.clone(), token_out.clone()),
+            token_amount_in,
+            Self::get_swap_fee(env.clone()),
+        )
+        .unwrap();
+        if amount_out < min_amount_out {
+            panic!("amount out below minimum");
+        }
+
+        let this = env.current_contract_address();
+        token::Client::new(&env, &token_in).transfer(&user, &this, &token_amount_in);
+        token::Client::new(&env, &token_out).transfer(&this, &user, &amount_out);
+
+        let mut balances: Vec<i128> = env.storage().instance().get(&BALANCES).unwrap();
+        let (in_idx, out_idx) = (Self::index(&env, &token_in), Self::index(&env, &token_out));
+        balances.set(in_idx, balances.get_unchecked(in_idx) + token_amount_in);
+        balances.set(out_idx, balances.get_unchecked(out_idx) - amount_out);
+        env.storage().instance().set(&BALANCES, &balances);
+
+        let spot_price_after = Self::get_spot_price(env, token_in, token_out);
+        if spot_price_after > max_price {
+            panic!("spot price after swap above max price");
+        }
+
+        (amount_out, spot_price_after)
+    }
+}
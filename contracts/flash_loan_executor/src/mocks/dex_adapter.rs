@@ -0,0 +1,61 @@
+use soroban_sdk::{contract, contractimpl, token, Address, Env};
+use crate::dex_adapter::DexAdapter;
+use crate::dex_interface::{constant_product_output, constant_product_spot_output, DexQuote, SoroswapPairClient};
+
+/// External adapter stand-in bridging to Soroswap-style pairs directly,
+/// without their router
+#[contract]
+pub struct MockDexAdapter;
+
+impl MockDexAdapter {
+    /// Reserves of `pool` as (`token_a`, `token_b`)
+    fn reserves(env: &Env, pool: &Address, token_a: &Address) -> (i128, i128) {
+        let pair = SoroswapPairClient::new(env, pool);
+        let (reserve_0, reserve_1) = pair.get_reserves();
+        if &pair.token_0() == token_a {
+            (reserve_0, reserve_1)
+        } else {
+            (reserve_1, reserve_0)
+        }
+    }
+}
+
+#[contractimpl]
+impl DexAdapter for MockDexAdapter {
+    fn quote(env: Env, pool: Address, token_in: Address, _token_out: Address, amount_in: i128) -> DexQuote {
+        let (reserve_in, reserve_out) = Self::reserves(&env, &pool, &token_in);
+        let amount_out = constant_product_output(amount_in, reserve_in, reserve_out, 30).unwrap();
+        let spot_amount_out = constant_product_spot_output(amount_in, reserve_in, reserve_out, 30).unwrap();
+        DexQuote { amount_out, reserve_in, reserve_out, fee_bps: 30, spot_amount_out }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn swap(
+        env: Env,
+        pool: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_amount_out: i128,
+        to: Address,
+        _deadline: u64,
+    ) -> i128 {
+        let amount_out = Self::quote(env.clone(), pool.clone(), token_in.clone(), token_out, amount_in).amount_out;
+        if amount_out < min_amount_out {
+            panic!("amount out below minimum");
+        }
+
+        let pair = SoroswapPairClient::new(&env, &pool);
+        token::Client::new(&env, &token_in).transfer(&env.current_contract_address(), &pool, &amount_in);
+        if pair.token_0() == token_in {
+            pair.swap(&0, &amount_out, &to);
+        } else {
+            pair.swap(&amount_out, &0, &to);
+        }
+        amount_out
+    }
+
+    fn get_reserves(env: Env, pool: Address, token_a: Address, _token_b: Address) -> (i128, i128) {
+        Self::reserves(&env, &pool, &token_a)
+    }
+}
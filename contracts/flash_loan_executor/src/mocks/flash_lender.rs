@@ -0,0 +1,106 @@
+use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Bytes, Env, Symbol, Vec};
+use crate::errors::Error;
+use crate::flash_loan::{
+    FlashLender, FlashLoanAsset, FlashLoanReceiverClient, MultiFlashLender,
+    MultiFlashLoanReceiverClient,
+};
+
+const FEE_BPS: Symbol = symbol_short!("fee_bps");
+
+/// Minimal ERC-3156 style lender
+/// 
+/// Lends out its own token balance, calls back the receiver and reverts
+/// unless principal + fee has been returned by the time the callback ends.
+#[contract]
+pub struct MockFlashLender;
+
+#[contractimpl]
+impl MockFlashLender {
+    pub fn init(env: Env, fee_bps: u32) {
+        env.storage().instance().set(&FEE_BPS, &fee_bps);
+    }
+}
+
+#[contractimpl]
+impl FlashLender for MockFlashLender {
+    fn max_flash_loan(env: Env, token: Address) -> Result<i128, Error> {
+        Ok(token::Client::new(&env, &token).balance(&env.current_contract_address()))
+    }
+
+    fn flash_fee(env: Env, _token: Address, amount: i128) -> Result<i128, Error> {
+        let fee_bps: u32 = env.storage().instance().get(&FEE_BPS).unwrap_or(0);
+        Ok(amount * fee_bps as i128 / 10000)
+    }
+
+    fn flash_loan(
+        env: Env,
+        initiator: Address,
+        receiver: Address,
+        token: Address,
+        amount: i128,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        initiator.require_auth();
+
+        let lender = env.current_contract_address();
+        let token_client = token::Client::new(&env, &token);
+        let balance_before = token_client.balance(&lender);
+        if amount > balance_before {
+            panic!("insufficient liquidity");
+        }
+
+        let fee = Self::flash_fee(env.clone(), token.clone(), amount)?;
+        token_client.transfer(&lender, &receiver, &amount);
+
+        FlashLoanReceiverClient::new(&env, &receiver)
+            .on_flash_loan(&initiator, &token, &amount, &fee, &data);
+
+        if token_client.balance(&lender) < balance_before + fee {
+            panic!("flash loan not repaid");
+        }
+
+        Ok(())
+    }
+}
+
+#[contractimpl]
+impl MultiFlashLender for MockFlashLender {
+    fn flash_loan_multi(
+        env: Env,
+        initiator: Address,
+        receiver: Address,
+        tokens: Vec<Address>,
+        amounts: Vec<i128>,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        initiator.require_auth();
+
+        let lender = env.current_contract_address();
+        let mut loans = Vec::new(&env);
+        let mut balances_before = Vec::new(&env);
+        for (token, amount) in tokens.iter().zip(amounts.iter()) {
+            let token_client = token::Client::new(&env, &token);
+            let balance_before = token_client.balance(&lender);
+            if amount > balance_before {
+                panic!("insufficient liquidity");
+            }
+
+            let fee = Self::flash_fee(env.clone(), token.clone(), amount)?;
+            token_client.transfer(&lender, &receiver, &amount);
+            loans.push_back(FlashLoanAsset { token, amount, fee });
+            balances_before.push_back(balance_before);
+        }
+
+        MultiFlashLoanReceiverClient::new(&env, &receiver)
+            .on_flash_loan_multi(&initiator, &loans, &data);
+
+        for (loan, balance_before) in loans.iter().zip(balances_before.iter()) {
+            let balance_after = token::Client::new(&env, &loan.token).balance(&lender);
+            if balance_after < balance_before + loan.fee {
+                panic!("flash loan not repaid");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Bytes, Env, Symbol};
+use crate::errors::Error;
+use crate::flash_loan::FlashLoanReceiver;
+
+const LENDER: Symbol = symbol_short!("lender");
+const REPAY: Symbol = symbol_short!("repay");
+
+/// Flash loan receiver that either repays in full or keeps the funds
+#[contract]
+pub struct MockFlashReceiver;
+
+#[contractimpl]
+impl MockFlashReceiver {
+    pub fn init(env: Env, lender: Address, repay: bool) {
+        env.storage().instance().set(&LENDER, &lender);
+        env.storage().instance().set(&REPAY, &repay);
+    }
+}
+
+#[contractimpl]
+impl FlashLoanReceiver for MockFlashReceiver {
+    fn on_flash_loan(
+        env: Env,
+        _initiator: Address,
+        token: Address,
+        amount: i128,
+        fee: i128,
+        _data: Bytes,
+    ) -> Result<(), Error> {
+        let repay: bool = env.storage().instance().get(&REPAY).unwrap();
+        if repay {
+            let lender: Address = env.storage().instance().get(&LENDER).unwrap();
+            token::Client::new(&env, &token)
+                .transfer(&env.current_contract_address(), &lender, &(amount + fee));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+//! Stand-in contracts for the external protocols the executor talks to.
+//! 
+//! These implement the same interfaces as the real deployments so the
+//! executor can be exercised end-to-end with soroban testutils.
+
+pub mod aquarius;
+pub mod blend;
+pub mod comet;
+pub mod dex_adapter;
+pub mod flash_lender;
+pub mod flash_receiver;
+pub mod phoenix;
+pub mod reflector;
+pub mod soroswap;
+pub mod xycloans;
+
+use soroban_sdk::{token, Address, Env};
+
+/// Deploy a Stellar asset contract and mint `amount` to each holder
+pub fn create_token(env: &Env, admin: &Address, holders: &[(&Address, i128)]) -> Address {
+    let token = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    let admin_client = token::StellarAssetClient::new(env, &token);
+    for (holder, amount) in holders {
+        admin_client.mint(holder, amount);
+    }
+    token
+}
@@ -0,0 +1,125 @@
+use soroban_sdk::{contract, contractimpl, symbol_short, token, vec, Address, Env, Symbol};
+use crate::dex_interface::{
+    constant_product_output, stable_swap_output, PhoenixAsset, PhoenixPool, PhoenixPoolResponse,
+    PhoenixSimulateSwapResponse, StablePool,
+};
+
+const TOKEN_A: Symbol = symbol_short!("token_a");
+const TOKEN_B: Symbol = symbol_short!("token_b");
+const RESERVES: Symbol = symbol_short!("reserves");
+const FEE_BPS: Symbol = symbol_short!("fee_bps");
+const AMP: Symbol = symbol_short!("amp");
+
+/// Phoenix pool stand-in: an XYK pool, or a stable pool when created with
+/// a non-zero amplification
+///
+/// Like Phoenix, the commission is taken from the output.
+#[contract]
+pub struct MockPhoenixPool;
+
+#[contractimpl]
+impl MockPhoenixPool {
+    pub fn init(
+        env: Env,
+        token_a: Address,
+        token_b: Address,
+        reserve_a: i128,
+        reserve_b: i128,
+        fee_bps: u32,
+        amp: i128,
+    ) {
+        env.storage().instance().set(&TOKEN_A, &token_a);
+        env.storage().instance().set(&TOKEN_B, &token_b);
+        env.storage().instance().set(&RESERVES, &(reserve_a, reserve_b));
+        env.storage().instance().set(&FEE_BPS, &fee_bps);
+        env.storage().instance().set(&AMP, &amp);
+    }
+}
+
+#[contractimpl]
+impl PhoenixPool for MockPhoenixPool {
+    fn query_pool_info(env: Env) -> PhoenixPoolResponse {
+        let (reserve_a, reserve_b): (i128, i128) = env.storage().instance().get(&RESERVES).unwrap();
+        PhoenixPoolResponse {
+            asset_a: PhoenixAsset { address: env.storage().instance().get(&TOKEN_A).unwrap(), amount: reserve_a },
+            asset_b: PhoenixAsset { address: env.storage().instance().get(&TOKEN_B).unwrap(), amount: reserve_b },
+            asset_lp_share: PhoenixAsset { address: env.current_contract_address(), amount: 0 },
+            stake_address: env.current_contract_address(),
+        }
+    }
+
+    fn simulate_swap(env: Env, offer_asset: Address, offer_amount: i128) -> PhoenixSimulateSwapResponse {
+        let info = Self::query_pool_info(env.clone());
+        let (offer, ask) = if offer_asset == info.asset_a.address {
+            (info.asset_a, info.asset_b)
+        } else {
+            (info.asset_b, info.asset_a)
+        };
+        let (reserve_in, reserve_out) = (offer.amount, ask.amount);
+
+        let amp: i128 = env.storage().instance().get(&AMP).unwrap();
+        let gross = if amp > 0 {
+            let pool = StablePool {
+                balances: vec![&env, reserve_in, reserve_out],
+                decimals: vec![
+                    &env,
+                    token::Client::new(&env, &offer.address).decimals(),
+                    token::Client::new(&env, &ask.address).decimals(),
+                ],
+                amp,
+                fee_bps: 0,
+            };
+            stable_swap_output(&env, &pool, 0, 1, offer_amount).unwrap()
+        } else {
+            constant_product_output(offer_amount, reserve_in, reserve_out, 0).unwrap()
+        };
+        let fee_bps: u32 = env.storage().instance().get(&FEE_BPS).unwrap();
+        let commission_amount = gross * fee_bps as i128 / 10000;
+
+        PhoenixSimulateSwapResponse {
+            ask_amount: gross - commission_amount,
+            commission_amount,
+            spread_amount: 0,
+            total_return: gross,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn swap(
+        env: Env,
+        sender: Address,
+        offer_asset: Address,
+        offer_amount: i128,
+        ask_asset_min_amount: Option<i128>,
+        _max_spread_bps: Option<i64>,
+        deadline: Option<u64>,
+        _max_allowed_fee_bps: Option<i64>,
+    ) -> i128 {
+        sender.require_auth();
+        if deadline.is_some_and(|deadline| env.ledger().timestamp() > deadline) {
+            panic!("deadline exceeded");
+        }
+
+        let ask_amount = Self::simulate_swap(env.clone(), offer_asset.clone(), offer_amount).ask_amount;
+        if ask_asset_min_amount.is_some_and(|min_amount| ask_amount < min_amount) {
+            panic!("ask amount below minimum");
+        }
+
+        let this = env.current_contract_address();
+        let token_a: Address = env.storage().instance().get(&TOKEN_A).unwrap();
+        let token_b: Address = env.storage().instance().get(&TOKEN_B).unwrap();
+        let ask_asset = if offer_asset == token_a { token_b } else { token_a.clone() };
+        token::Client::new(&env, &offer_asset).transfer(&sender, &this, &offer_amount);
+        token::Client::new(&env, &ask_asset).transfer(&this, &sender, &ask_amount);
+
+        let (reserve_a, reserve_b): (i128, i128) = env.storage().instance().get(&RESERVES).unwrap();
+        let reserves = if offer_asset == token_a {
+            (reserve_a + offer_amount, reserve_b - ask_amount)
+        } else {
+            (reserve_a - ask_amount, reserve_b + offer_amount)
+        };
+        env.storage().instance().set(&RESERVES, &reserves);
+
+        ask_amount
+    }
+}
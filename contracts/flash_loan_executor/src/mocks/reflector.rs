@@ -0,0 +1,24 @@
+use soroban_sdk::{contract, contractimpl, Env};
+use crate::oracle::{OracleAsset, PriceData, Reflector};
+
+/// Reflector stand-in returning prices set by the test, with 14 decimals
+#[contract]
+pub struct MockReflector;
+
+#[contractimpl]
+impl MockReflector {
+    pub fn set_price(env: Env, asset: OracleAsset, price: i128, timestamp: u64) {
+        env.storage().instance().set(&asset, &PriceData { price, timestamp });
+    }
+}
+
+#[contractimpl]
+impl Reflector for MockReflector {
+    fn decimals(_env: Env) -> u32 {
+        14
+    }
+
+    fn lastprice(env: Env, asset: OracleAsset) -> Option<PriceData> {
+        env.storage().instance().get(&asset)
+    }
+}
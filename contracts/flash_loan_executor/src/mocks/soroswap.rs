@@ -0,0 +1,194 @@
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, token, vec, Address, Bytes, Env, Symbol, Vec,
+};
+use crate::dex_interface::{
+    constant_product_output, SoroswapPair, SoroswapPairClient, SoroswapRouter,
+};
+use crate::flash_loan::SoroswapCalleeClient;
+
+const TOKEN_0: Symbol = symbol_short!("token_0");
+const TOKEN_1: Symbol = symbol_short!("token_1");
+const RESERVES: Symbol = symbol_short!("reserves");
+
+/// Soroswap pair stand-in exposing the pair interface
+/// 
+/// Reserves are taken as given by `init`; pairs that are swapped through
+/// must also hold them as token balances (see `create_pair`).
+#[contract]
+pub struct MockSoroswapPair;
+
+#[contractimpl]
+impl MockSoroswapPair {
+    pub fn init(env: Env, token_0: Address, token_1: Address, reserve_0: i128, reserve_1: i128) {
+        env.storage().instance().set(&TOKEN_0, &token_0);
+        env.storage().instance().set(&TOKEN_1, &token_1);
+        env.storage().instance().set(&RESERVES, &(reserve_0, reserve_1));
+    }
+
+    /// Flash swap: send `amount` of `token` to `to`, call it back with
+    /// `data`, then check `k` like `swap`
+    pub fn flash_swap(env: Env, sender: Address, token: Address, amount: i128, to: Address, data: Bytes) {
+        sender.require_auth();
+
+        let this = env.current_contract_address();
+        let (amount_0_out, amount_1_out) = if token == Self::token_0(env.clone()) {
+            (amount, 0)
+        } else if token == Self::token_1(env.clone()) {
+            (0, amount)
+        } else {
+            panic!("token not in pair");
+        };
+        token::Client::new(&env, &token).transfer(&this, &to, &amount);
+
+        SoroswapCalleeClient::new(&env, &to).soroswap_call(&sender, &token, &amount, &data);
+
+        Self::check_k(&env, amount_0_out, amount_1_out);
+    }
+}
+
+impl MockSoroswapPair {
+    /// Check `k` with the 0.3% fee charged on whatever was paid in, then
+    /// sync the reserves to the balances
+    fn check_k(env: &Env, amount_0_out: i128, amount_1_out: i128) {
+        let this = env.current_contract_address();
+        let (reserve_0, reserve_1) = Self::get_reserves(env.clone());
+        let balance_0 = token::Client::new(env, &Self::token_0(env.clone())).balance(&this);
+        let balance_1 = token::Client::new(env, &Self::token_1(env.clone())).balance(&this);
+        let amount_0_in = (balance_0 - (reserve_0 - amount_0_out)).max(0);
+        let amount_1_in = (balance_1 - (reserve_1 - amount_1_out)).max(0);
+
+        let adjusted_0 = balance_0 * 1000 - amount_0_in * 3;
+        let adjusted_1 = balance_1 * 1000 - amount_1_in * 3;
+        if adjusted_0 * adjusted_1 < reserve_0 * reserve_1 * 1_000_000 {
+            panic!("k decreased");
+        }
+
+        env.storage().instance().set(&RESERVES, &(balance_0, balance_1));
+    }
+}
+
+#[contractimpl]
+impl SoroswapPair for MockSoroswapPair {
+    fn token_0(env: Env) -> Address {
+        env.storage().instance().get(&TOKEN_0).unwrap()
+    }
+
+    fn token_1(env: Env) -> Address {
+        env.storage().instance().get(&TOKEN_1).unwrap()
+    }
+
+    fn get_reserves(env: Env) -> (i128, i128) {
+        env.storage().instance().get(&RESERVES).unwrap()
+    }
+
+    /// Send out the requested amounts, then check `k` with the 0.3% fee
+    /// charged on whatever was paid in, like the real pair
+    fn swap(env: Env, amount_0_out: i128, amount_1_out: i128, to: Address) {
+        let this = env.current_contract_address();
+        if amount_0_out > 0 {
+            token::Client::new(&env, &Self::token_0(env.clone())).transfer(&this, &to, &amount_0_out);
+        }
+        if amount_1_out > 0 {
+            token::Client::new(&env, &Self::token_1(env.clone())).transfer(&this, &to, &amount_1_out);
+        }
+
+        Self::check_k(&env, amount_0_out, amount_1_out);
+    }
+}
+
+/// Soroswap router stand-in trading through pairs registered with `add_pair`
+#[contract]
+pub struct MockSoroswapRouter;
+
+#[contractimpl]
+impl MockSoroswapRouter {
+    pub fn add_pair(env: Env, pair: Address) {
+        let client = SoroswapPairClient::new(&env, &pair);
+        let (token_0, token_1) = (client.token_0(), client.token_1());
+        env.storage().instance().set(&(token_0.clone(), token_1.clone()), &pair);
+        env.storage().instance().set(&(token_1, token_0), &pair);
+    }
+}
+
+#[contractimpl]
+impl SoroswapRouter for MockSoroswapRouter {
+    fn router_pair_for(env: Env, token_a: Address, token_b: Address) -> Address {
+        env.storage().instance().get(&(token_a, token_b)).expect("pair not found")
+    }
+
+    fn router_get_amounts_out(env: Env, amount_in: i128, path: Vec<Address>) -> Vec<i128> {
+        let mut amounts = vec![&env, amount_in];
+        for i in 0..path.len() - 1 {
+            let (token_in, token_out) = (path.get_unchecked(i), path.get_unchecked(i + 1));
+            let pair_address = Self::router_pair_for(env.clone(), token_in.clone(), token_out);
+            let pair = SoroswapPairClient::new(&env, &pair_address);
+            let (reserve_0, reserve_1) = pair.get_reserves();
+            let (reserve_in, reserve_out) = if pair.token_0() == token_in {
+                (reserve_0, reserve_1)
+            } else {
+                (reserve_1, reserve_0)
+            };
+            let amount = amounts.last_unchecked();
+            amounts.push_back(constant_product_output(amount, reserve_in, reserve_out, 30).unwrap());
+        }
+        amounts
+    }
+
+    fn swap_exact_tokens_for_tokens(
+        env: Env,
+        amount_in: i128,
+        amount_out_min: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<i128> {
+        if env.ledger().timestamp() > deadline {
+            panic!("expired");
+        }
+        let amounts = Self::router_get_amounts_out(env.clone(), amount_in, path.clone());
+        if amounts.last_unchecked() < amount_out_min {
+            panic!("insufficient output amount");
+        }
+
+        // Input goes straight from the caller into the first pair
+        let first_pair =
+            Self::router_pair_for(env.clone(), path.get_unchecked(0), path.get_unchecked(1));
+        token::Client::new(&env, &path.get_unchecked(0)).transfer(&to, &first_pair, &amount_in);
+
+        for i in 0..path.len() - 1 {
+            let (token_in, token_out) = (path.get_unchecked(i), path.get_unchecked(i + 1));
+            let pair_address = Self::router_pair_for(env.clone(), token_in, token_out.clone());
+            let pair = SoroswapPairClient::new(&env, &pair_address);
+            let amount_out = amounts.get_unchecked(i + 1);
+            let recipient = if i + 2 < path.len() {
+                Self::router_pair_for(env.clone(), token_out.clone(), path.get_unchecked(i + 2))
+            } else {
+                to.clone()
+            };
+            if pair.token_0() == token_out {
+                pair.swap(&amount_out, &0, &recipient);
+            } else {
+                pair.swap(&0, &amount_out, &recipient);
+            }
+        }
+
+        amounts
+    }
+}
+
+/// Deploy a pair holding its reserves as balances, registered with `router`
+pub fn create_pair(
+    env: &Env,
+    router: &Address,
+    token_0: &Address,
+    token_1: &Address,
+    reserve_0: i128,
+    reserve_1: i128,
+) -> Address {
+    let pair = env.register_contract(None, MockSoroswapPair);
+    MockSoroswapPairClient::new(env, &pair).init(token_0, token_1, &reserve_0, &reserve_1);
+    token::StellarAssetClient::new(env, token_0).mint(&pair, &reserve_0);
+    token::StellarAssetClient::new(env, token_1).mint(&pair, &reserve_1);
+    MockSoroswapRouterClient::new(env, router).add_pair(&pair);
+    pair
+}
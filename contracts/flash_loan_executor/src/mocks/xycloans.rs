@@ -0,0 +1,33 @@
+use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env, Symbol};
+use crate::flash_loan::ExecOpReceiverClient;
+
+const TOKEN: Symbol = symbol_short!("token");
+const FEE_BPS: i128 = 8;
+
+/// XycLoans pool stand-in
+/// 
+/// Lends out its token balance to `receiver`, calls back `exec_op` and
+/// pulls principal + 0.08% with `transfer_from` once it returns.
+#[contract]
+pub struct MockXycLoansPool;
+
+#[contractimpl]
+impl MockXycLoansPool {
+    pub fn init(env: Env, token: Address) {
+        env.storage().instance().set(&TOKEN, &token);
+    }
+
+    pub fn borrow(env: Env, caller: Address, receiver: Address, amount: i128) {
+        caller.require_auth();
+
+        let pool = env.current_contract_address();
+        let token: Address = env.storage().instance().get(&TOKEN).unwrap();
+        let token_client = token::Client::new(&env, &token);
+        let fee = (amount * FEE_BPS + 9_999) / 10_000;
+        token_client.transfer(&pool, &receiver, &amount);
+
+        ExecOpReceiverClient::new(&env, &receiver).exec_op(&caller, &token, &amount, &fee);
+
+        token_client.transfer_from(&pool, &receiver, &pool, &(amount + fee));
+    }
+}
@@ -0,0 +1,405 @@
+use soroban_sdk::{contracttype, Address, Env};
+use crate::arbitrage::{ArbitrageExecutor, ArbitrageRoute};
+use crate::dex_interface::{DexInterface, DexType, SoroswapDex};
+use crate::errors::Error;
+use crate::fee_schedule::get_fee_schedule;
+
+/// Upper bound on golden-section steps, each costing one route simulation
+const MAX_SEARCH_ITERATIONS: u32 = 32;
+
+/// Search stops once the bracket is narrower than this share of the max borrow
+const SEARCH_TOLERANCE_BPS: i128 = 1;
+
+/// Soroswap pairs keep 997/1000 of the input (0.3% fee)
+const CPMM_FEE_NUMERATOR: i128 = 997;
+const CPMM_FEE_DENOMINATOR: i128 = 1000;
+
+/// Best borrow size found for a route
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OptimalTrade {
+    pub amount: i128, // 0 when no size up to the max borrow is profitable
+    pub net_profit: i128,
+}
+
+/// Trade Size Optimizer
+///
+/// Finds the borrow amount that maximizes net profit for a route. Two
+/// constant-product legs have a closed-form optimum; any other route is
+/// searched with a bounded golden-section search, which relies on profit
+/// being unimodal in the borrow size (true for AMM curves with a linear fee).
+pub struct TradeSizeOptimizer;
+
+impl TradeSizeOptimizer {
+    /// Find the most profitable borrow amount in `1..=max_amount`
+    ///
+    /// `route.amount` is ignored; every candidate is priced with
+    /// `ArbitrageExecutor::simulate_arbitrage` and `lender`'s fee schedule.
+    pub fn find_optimal_trade(
+        env: &Env,
+        route: &ArbitrageRoute,
+        lender: &Address,
+        max_amount: i128,
+    ) -> Result<OptimalTrade, Error> {
+        if max_amount <= 0 {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
+
+        let mut best = OptimalTrade { amount: 0, net_profit: 0 };
+
+        match Self::closed_form_amount(env, route, lender, max_amount)? {
+            Some(amount) => {
+                if amount > 0 {
+                    Self::evaluate(env, route, lender, amount, &mut best)?;
+                }
+            }
+            None => Self::golden_section_search(env, route, lender, max_amount, &mut best)?,
+        }
+
+        Ok(best)
+    }
+
+    /// Optimum for two single-pool Soroswap legs, clamped to `max_amount`
+    ///
+    /// Returns `None` when the route has another shape or the numbers are
+    /// too large for the closed form, in which case the caller searches.
+    fn closed_form_amount(
+        env: &Env,
+        route: &ArbitrageRoute,
+        lender: &Address,
+        max_amount: i128,
+    ) -> Result<Option<i128>, Error> {
+        if route.legs.len() != 2 {
+            return Ok(None);
+        }
+
+        let leg_a = route.legs.get_unchecked(0);
+        let leg_b = route.legs.get_unchecked(1);
+        if leg_a.splits.len() != 1 || leg_b.splits.len() != 1 {
+            return Ok(None);
+        }
+
+        let split_a = leg_a.splits.get_unchecked(0);
+        let split_b = leg_b.splits.get_unchecked(0);
+        if split_a.dex_type != DexType::Soroswap || split_b.dex_type != DexType::Soroswap {
+            return Ok(None);
+        }
+
+        let (reserve_a_in, reserve_a_out) =
+            SoroswapDex::get_reserves(env, &split_a.pool, &leg_a.token_in, &leg_a.token_out)?;
+        let (reserve_b_in, reserve_b_out) =
+            SoroswapDex::get_reserves(env, &split_b.pool, &leg_b.token_in, &leg_b.token_out)?;
+
+        let schedule = get_fee_schedule(env, lender).ok_or(Error::FeeScheduleNotSet)?;
+
+        // Price with the rate at the max size, then again at the size found,
+        // so a tiered schedule settles on the tier the trade actually falls in
+        let mut fee_bps = schedule.rate_bps(max_amount)?;
+        let mut amount = 0;
+        for _ in 0..2 {
+            amount = match Self::cpmm_optimum(
+                reserve_a_in,
+                reserve_a_out,
+                reserve_b_in,
+                reserve_b_out,
+                fee_bps,
+            ) {
+                Some(amount) => amount.min(max_amount),
+                None => return Ok(None),
+            };
+            if amount <= 0 {
+                return Ok(Some(0));
+            }
+            fee_bps = schedule.rate_bps(amount)?;
+        }
+
+        Ok(Some(amount))
+    }
+
+    /// Profit-maximizing input for a borrow -> X -> borrow cycle over two pools
+    ///
+    /// The two pools compose into one virtual constant-product pool with
+    /// reserves `e_in = D*a1*b2 / (D*b2 + G*b1)` and
+    /// `e_out = G*b1*a2 / (D*b2 + G*b1)`, where `G/D` is the swap fee factor.
+    /// Setting the marginal output equal to `1 + fee` gives
+    /// `x = (sqrt(G*D*e_in*e_out / (1 + fee)) - D*e_in) / G`.
+    fn cpmm_optimum(
+        reserve_a_in: i128,
+        reserve_a_out: i128,
+        reserve_b_in: i128,
+        reserve_b_out: i128,
+        fee_bps: u32,
+    ) -> Option<i128> {
+        let g = CPMM_FEE_NUMERATOR;
+        let d = CPMM_FEE_DENOMINATOR;
+        let fee_scale = 10000 + fee_bps as i128;
+
+        let denominator = d.checked_mul(reserve_b_in)?
+            .checked_add(g.checked_mul(reserve_a_out)?)?;
+        if denominator <= 0 {
+            return Some(0);
+        }
+        let e_in = d.checked_mul(reserve_a_in)?
+            .checked_mul(reserve_b_in)?
+            .checked_div(denominator)?;
+        let e_out = g.checked_mul(reserve_a_out)?
+            .checked_mul(reserve_b_out)?
+            .checked_div(denominator)?;
+
+        // Marginal rate at zero size must beat the flash fee
+        if g.checked_mul(e_out)?.checked_mul(10000)? <= d.checked_mul(e_in)?.checked_mul(fee_scale)? {
+            return Some(0);
+        }
+
+        let product = g.checked_mul(d)?
+            .checked_mul(e_in)?
+            .checked_mul(e_out)?
+            .checked_div(fee_scale)?
+            .checked_mul(10000)?;
+        let amount = isqrt(product)
+            .checked_sub(d.checked_mul(e_in)?)?
+            .checked_div(g)?;
+
+        Some(amount.max(0))
+    }
+
+    /// Narrow `1..=max_amount` around the most profitable size
+    fn golden_section_search(
+        env: &Env,
+        route: &ArbitrageRoute,
+        lender: &Address,
+        max_amount: i128,
+        best: &mut OptimalTrade,
+    ) -> Result<(), Error> {
+        let tolerance = (max_amount / 10000 * SEARCH_TOLERANCE_BPS).max(1);
+
+        let mut low: i128 = 1;
+        let mut high = max_amount;
+        let mut x1 = golden_point(low, high, 382)?;
+        let mut x2 = golden_point(low, high, 618)?;
+        let mut f1 = Self::evaluate(env, route, lender, x1, best)?;
+        let mut f2 = Self::evaluate(env, route, lender, x2, best)?;
+
+        for _ in 0..MAX_SEARCH_ITERATIONS {
+            if high - low <= tolerance {
+                break;
+            }
+
+            if f1 < f2 {
+                // Optimum lies above x1
+                low = x1;
+                x1 = x2;
+                f1 = f2;
+                x2 = golden_point(low, high, 618)?;
+                f2 = Self::evaluate(env, route, lender, x2, best)?;
+            } else {
+                // Optimum lies below x2
+                high = x2;
+                x2 = x1;
+                f2 = f1;
+                x1 = golden_point(low, high, 382)?;
+                f1 = Self::evaluate(env, route, lender, x1, best)?;
+            }
+        }
+
+        // Profit may still be rising at the cap
+        Self::evaluate(env, route, lender, max_amount, best)?;
+
+        Ok(())
+    }
+
+    /// Net profit at `amount`, recording it if it beats `best`
+    fn evaluate(
+        env: &Env,
+        route: &ArbitrageRoute,
+        lender: &Address,
+        amount: i128,
+        best: &mut OptimalTrade,
+    ) -> Result<i128, Error> {
+        let mut candidate = route.clone();
+        candidate.amount = amount;
+
+        let net_profit = ArbitrageExecutor::simulate_arbitrage(env, &candidate, lender)?.net_profit;
+        if net_profit > best.net_profit {
+            *best = OptimalTrade { amount, net_profit };
+        }
+
+        Ok(net_profit)
+    }
+}
+
+/// Point `permille` of the way from `low` to `high`
+fn golden_point(low: i128, high: i128, permille: i128) -> Result<i128, Error> {
+    let offset = (high - low)
+        .checked_mul(permille)
+        .ok_or(Error::ArithmeticOverflow)?
+        / 1000;
+    Ok(low + offset)
+}
+
+/// Integer square root (floor) by Newton's method
+fn isqrt(n: i128) -> i128 {
+    if n < 2 {
+        return n.max(0);
+    }
+
+    let mut x = n;
+    let mut y = n / 2 + 1;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{vec, Vec};
+    use crate::allowlist;
+    use crate::arbitrage::{PoolSplit, SwapLeg};
+    use crate::fee_schedule::{set_fee_schedule, FeeModel, FeeSchedule};
+    use crate::mocks::soroswap::{MockSoroswapPair, MockSoroswapPairClient};
+
+    fn pair(env: &Env, token_0: &Address, token_1: &Address, reserve_0: i128, reserve_1: i128) -> Address {
+        let pair = env.register_contract(None, MockSoroswapPair);
+        MockSoroswapPairClient::new(env, &pair).init(token_0, token_1, &reserve_0, &reserve_1);
+        pair
+    }
+
+    fn leg(env: &Env, token_in: &Address, token_out: &Address, pools: &[(&Address, u32)]) -> SwapLeg {
+        let mut splits: Vec<PoolSplit> = Vec::new(env);
+        for (pool, fraction_bps) in pools {
+            splits.push_back(PoolSplit { dex_type: DexType::Soroswap, pool: (*pool).clone(), fraction_bps: *fraction_bps });
+        }
+        SwapLeg { token_in: token_in.clone(), token_out: token_out.clone(), splits }
+    }
+
+    fn route(legs: Vec<SwapLeg>, token_borrow: &Address) -> ArbitrageRoute {
+        let expected_outputs = Vec::new(legs.env());
+        ArbitrageRoute {
+            route_id: 0,
+            legs,
+            token_borrow: token_borrow.clone(),
+            amount: 0,
+            min_profit_bps: 0,
+            max_slippage_bps: 10000,
+            expected_outputs,
+            deadline: 0,
+        }
+    }
+
+    /// Allowlist every token and pool the route touches
+    fn allow(env: &Env, executor: &Address, route: &ArbitrageRoute) {
+        env.as_contract(executor, || {
+            allowlist::set_token_allowed(env, &route.token_borrow, true);
+            for leg in route.legs.iter() {
+                allowlist::set_token_allowed(env, &leg.token_out, true);
+                for split in leg.splits.iter() {
+                    allowlist::set_pool_allowed(env, &split.pool, true);
+                }
+            }
+        });
+    }
+
+    fn profit_at(env: &Env, route: &ArbitrageRoute, lender: &Address, amount: i128) -> i128 {
+        let mut best = OptimalTrade { amount: 0, net_profit: 0 };
+        TradeSizeOptimizer::evaluate(env, route, lender, amount, &mut best).unwrap()
+    }
+
+    #[test]
+    fn test_isqrt() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(1_000_000_000_000), 1_000_000);
+    }
+
+    #[test]
+    fn test_closed_form_matches_local_maximum() {
+        let env = Env::default();
+        let executor = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let lender = Address::generate(&env);
+        let usdc = Address::generate(&env);
+        let xlm = Address::generate(&env);
+
+        // XLM trades at 1.00 USDC on pool A and 1.10 USDC on pool B
+        let pool_a = pair(&env, &usdc, &xlm, 1_000_000_000, 1_000_000_000);
+        let pool_b = pair(&env, &xlm, &usdc, 1_000_000_000, 1_100_000_000);
+        let cycle = route(
+            vec![&env, leg(&env, &usdc, &xlm, &[(&pool_a, 10000)]), leg(&env, &xlm, &usdc, &[(&pool_b, 10000)])],
+            &usdc,
+        );
+        // Same price as pool A
+        let pool_c = pair(&env, &usdc, &xlm, 1_000_000_000, 1_000_000_000);
+        let flat = route(
+            vec![&env, leg(&env, &usdc, &xlm, &[(&pool_a, 10000)]), leg(&env, &xlm, &usdc, &[(&pool_c, 10000)])],
+            &usdc,
+        );
+        allow(&env, &executor, &cycle);
+        allow(&env, &executor, &flat);
+
+        env.as_contract(&executor, || {
+            let schedule = FeeSchedule { model: FeeModel::FlatBps(9), min_fee: 0 };
+            set_fee_schedule(&env, &lender, &schedule).unwrap();
+
+            let best = TradeSizeOptimizer::find_optimal_trade(&env, &cycle, &lender, 1_000_000_000).unwrap();
+            assert!(best.amount > 0);
+            assert!(best.net_profit > 0);
+
+            // Neither a slightly smaller nor a slightly larger trade does better
+            let step = best.amount / 100;
+            assert!(profit_at(&env, &cycle, &lender, best.amount - step) <= best.net_profit);
+            assert!(profit_at(&env, &cycle, &lender, best.amount + step) <= best.net_profit);
+
+            // A cap below the optimum is binding
+            let capped = TradeSizeOptimizer::find_optimal_trade(&env, &cycle, &lender, 1_000_000).unwrap();
+            assert_eq!(capped.amount, 1_000_000);
+
+            // No price gap: nothing to trade
+            let none = TradeSizeOptimizer::find_optimal_trade(&env, &flat, &lender, 1_000_000_000).unwrap();
+            assert_eq!(none, OptimalTrade { amount: 0, net_profit: 0 });
+        });
+    }
+
+    #[test]
+    fn test_search_handles_split_routes() {
+        let env = Env::default();
+        env.budget().reset_unlimited();
+        let executor = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let lender = Address::generate(&env);
+        let usdc = Address::generate(&env);
+        let xlm = Address::generate(&env);
+
+        // Same liquidity as the closed-form test, with pool A split in two
+        let pool_a1 = pair(&env, &usdc, &xlm, 500_000_000, 500_000_000);
+        let pool_a2 = pair(&env, &xlm, &usdc, 500_000_000, 500_000_000);
+        let pool_b = pair(&env, &xlm, &usdc, 1_000_000_000, 1_100_000_000);
+        let cycle = route(
+            vec![
+                &env,
+                leg(&env, &usdc, &xlm, &[(&pool_a1, 5000), (&pool_a2, 5000)]),
+                leg(&env, &xlm, &usdc, &[(&pool_b, 10000)]),
+            ],
+            &usdc,
+        );
+        allow(&env, &executor, &cycle);
+
+        env.as_contract(&executor, || {
+            let schedule = FeeSchedule { model: FeeModel::FlatBps(9), min_fee: 0 };
+            set_fee_schedule(&env, &lender, &schedule).unwrap();
+
+            let max_amount = 1_000_000_000;
+            let best = TradeSizeOptimizer::find_optimal_trade(&env, &cycle, &lender, max_amount).unwrap();
+            assert!(best.net_profit > 0);
+
+            // At least as good as any point on a coarse grid
+            for i in 1..=20 {
+                let amount = max_amount * i / 20;
+                assert!(profit_at(&env, &cycle, &lender, amount) <= best.net_profit);
+            }
+        });
+    }
+}
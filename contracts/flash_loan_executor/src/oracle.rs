@@ -0,0 +1,233 @@
+use soroban_sdk::{contractclient, contracttype, token, Address, Env, Symbol};
+use crate::errors::Error;
+
+const ORACLE_CONFIG: &str = "oracle_config";
+const ORACLE_ASSET: &str = "oracle_asset";
+
+/// Asset identifier used by Reflector (SEP-40) oracles
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OracleAsset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// Price record returned by a Reflector oracle
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128, // Scaled by the oracle's `decimals`
+    pub timestamp: u64,
+}
+
+/// Read-only subset of the Reflector oracle interface
+#[contractclient(name = "ReflectorClient")]
+#[allow(dead_code)]
+pub trait Reflector {
+    fn decimals(env: Env) -> u32;
+    fn lastprice(env: Env, asset: OracleAsset) -> Option<PriceData>;
+}
+
+/// Oracle used to sanity-check execution prices (owner configured)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleConfig {
+    pub oracle: Address,
+    pub max_deviation_bps: u32, // Allowed gap between execution and oracle price, DEX fees included
+    pub max_age: u64, // Oldest usable price, in seconds
+}
+
+/// Oracle configuration, if the price check is enabled
+pub fn get_oracle_config(env: &Env) -> Option<OracleConfig> {
+    env.storage().instance().get(&ORACLE_CONFIG)
+}
+
+/// Enable the price check with `config` (owner only)
+pub fn set_oracle_config(env: &Env, config: &OracleConfig) -> Result<(), Error> {
+    if config.max_deviation_bps > 10000 {
+        return Err(Error::InvalidOracleDeviation);
+    }
+    env.storage().instance().set(&ORACLE_CONFIG, config);
+    Ok(())
+}
+
+/// Disable the price check (owner only)
+pub fn remove_oracle_config(env: &Env) {
+    env.storage().instance().remove(&ORACLE_CONFIG);
+}
+
+/// Oracle asset that prices `token`, if mapped
+pub fn get_oracle_asset(env: &Env, token: &Address) -> Option<OracleAsset> {
+    let key = (ORACLE_ASSET, token);
+    env.storage().persistent().get(&key)
+}
+
+/// Map `token` to the asset the oracle quotes it as (owner only)
+pub fn set_oracle_asset(env: &Env, token: &Address, asset: &OracleAsset) {
+    let key = (ORACLE_ASSET, token);
+    env.storage().persistent().set(&key, asset);
+}
+
+/// Forget the oracle asset for `token` (owner only)
+pub fn remove_oracle_asset(env: &Env, token: &Address) {
+    let key = (ORACLE_ASSET, token);
+    env.storage().persistent().remove(&key);
+}
+
+/// Reject a leg whose execution price strays too far from the oracle
+///
+/// The fair output is `amount_in` valued at the oracle price of `token_in`
+/// and converted back at the price of `token_out`, adjusting for the two
+/// tokens' decimals. Does nothing while no oracle is configured.
+pub fn check_leg_price(
+    env: &Env,
+    token_in: &Address,
+    token_out: &Address,
+    amount_in: i128,
+    amount_out: i128,
+) -> Result<(), Error> {
+    let config = match get_oracle_config(env) {
+        Some(config) => config,
+        None => return Ok(()),
+    };
+
+    let price_in = oracle_price(env, &config, token_in)?;
+    let price_out = oracle_price(env, &config, token_out)?;
+
+    let fair_out = fair_output(
+        amount_in,
+        price_in,
+        price_out,
+        token::Client::new(env, token_in).decimals(),
+        token::Client::new(env, token_out).decimals(),
+    )?;
+
+    if deviation_bps(fair_out, amount_out)? > config.max_deviation_bps as i128 {
+        return Err(Error::PriceDeviationTooHigh);
+    }
+
+    Ok(())
+}
+
+/// Latest fresh oracle price of `token`
+fn oracle_price(env: &Env, config: &OracleConfig, token: &Address) -> Result<i128, Error> {
+    let asset = get_oracle_asset(env, token).ok_or(Error::OracleAssetNotMapped)?;
+    let data = ReflectorClient::new(env, &config.oracle)
+        .lastprice(&asset)
+        .ok_or(Error::OraclePriceUnavailable)?;
+
+    if data.price <= 0 {
+        return Err(Error::OraclePriceUnavailable);
+    }
+
+    let age = env.ledger().timestamp().saturating_sub(data.timestamp);
+    if age > config.max_age {
+        return Err(Error::OraclePriceStale);
+    }
+
+    Ok(data.price)
+}
+
+/// `amount_in` converted to `token_out` units at oracle prices
+fn fair_output(
+    amount_in: i128,
+    price_in: i128,
+    price_out: i128,
+    decimals_in: u32,
+    decimals_out: u32,
+) -> Result<i128, Error> {
+    let scale_in = 10i128.checked_pow(decimals_in).ok_or(Error::ArithmeticOverflow)?;
+    let scale_out = 10i128.checked_pow(decimals_out).ok_or(Error::ArithmeticOverflow)?;
+
+    amount_in.checked_mul(price_in)
+        .ok_or(Error::ArithmeticOverflow)?
+        .checked_mul(scale_out)
+        .ok_or(Error::ArithmeticOverflow)?
+        .checked_div(price_out.checked_mul(scale_in).ok_or(Error::ArithmeticOverflow)?)
+        .ok_or(Error::ArithmeticOverflow)
+}
+
+/// Relative gap between `fair` and `actual`, in basis points of `fair`
+fn deviation_bps(fair: i128, actual: i128) -> Result<i128, Error> {
+    if fair <= 0 {
+        return Err(Error::OraclePriceUnavailable);
+    }
+
+    actual.checked_sub(fair)
+        .ok_or(Error::ArithmeticOverflow)?
+        .checked_abs()
+        .ok_or(Error::ArithmeticOverflow)?
+        .checked_mul(10000)
+        .ok_or(Error::ArithmeticOverflow)?
+        .checked_div(fair)
+        .ok_or(Error::ArithmeticOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use crate::mocks::create_token;
+    use crate::mocks::reflector::{MockReflector, MockReflectorClient};
+
+    #[test]
+    fn test_fair_output_and_deviation() {
+        // 1,000 of a 2-decimal token at $2 is worth 20 of a 7-decimal $1 token
+        assert_eq!(fair_output(1_000, 200, 100, 2, 7), Ok(200_000_000));
+
+        assert_eq!(deviation_bps(1_000_000, 990_000), Ok(100));
+        assert_eq!(deviation_bps(1_000_000, 1_020_000), Ok(200));
+        assert_eq!(deviation_bps(0, 1), Err(Error::OraclePriceUnavailable));
+    }
+
+    #[test]
+    fn test_leg_price_checked_against_oracle() {
+        let env = Env::default();
+        env.ledger().with_mut(|ledger| ledger.timestamp = 10_000);
+        let contract = env.register_contract(None, crate::FlashLoanExecutorContract);
+        let admin = Address::generate(&env);
+        let usdc = create_token(&env, &admin, &[]);
+        let xlm = create_token(&env, &admin, &[]);
+
+        // XLM at $0.10, USDC at $1.00 (14 decimals)
+        let oracle = env.register_contract(None, MockReflector);
+        let reflector = MockReflectorClient::new(&env, &oracle);
+        let xlm_asset = OracleAsset::Stellar(xlm.clone());
+        let usdc_asset = OracleAsset::Other(Symbol::new(&env, "USD"));
+        reflector.set_price(&xlm_asset, &10_000_000_000_000, &9_900);
+        reflector.set_price(&usdc_asset, &100_000_000_000_000, &9_900);
+
+        env.as_contract(&contract, || {
+            // Disabled until configured
+            assert_eq!(check_leg_price(&env, &usdc, &xlm, 1_000, 1), Ok(()));
+
+            let config = OracleConfig { oracle: oracle.clone(), max_deviation_bps: 10_001, max_age: 300 };
+            assert_eq!(set_oracle_config(&env, &config), Err(Error::InvalidOracleDeviation));
+            
+            let config = OracleConfig { oracle: oracle.clone(), max_deviation_bps: 100, max_age: 300 };
+            set_oracle_config(&env, &config).unwrap();
+            assert_eq!(
+                check_leg_price(&env, &usdc, &xlm, 1_000, 10_000),
+                Err(Error::OracleAssetNotMapped)
+            );
+
+            set_oracle_asset(&env, &usdc, &usdc_asset);
+            set_oracle_asset(&env, &xlm, &xlm_asset);
+
+            // 1,000 USDC is fairly worth 10,000 XLM; within 1% passes
+            assert_eq!(check_leg_price(&env, &usdc, &xlm, 1_000, 9_950), Ok(()));
+            assert_eq!(
+                check_leg_price(&env, &usdc, &xlm, 1_000, 9_800),
+                Err(Error::PriceDeviationTooHigh)
+            );
+            assert_eq!(check_leg_price(&env, &xlm, &usdc, 10_000, 995), Ok(()));
+
+            // Prices older than five minutes are not trusted
+            env.ledger().with_mut(|ledger| ledger.timestamp = 10_300);
+            assert_eq!(
+                check_leg_price(&env, &usdc, &xlm, 1_000, 9_950),
+                Err(Error::OraclePriceStale)
+            );
+        });
+    }
+}
@@ -0,0 +1,136 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+use crate::arbitrage::{PoolSplit, SwapLeg};
+use crate::dex_interface::get_dex_reserves;
+use crate::errors::Error;
+
+/// Pool reserves the caller priced an opportunity from
+///
+/// Reserves are ordered as the route trades the pool, matching
+/// `reserve_in` / `reserve_out` in the simulation report.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReserveSnapshot {
+    pub pool: Address,
+    pub reserve_in: i128,
+    pub reserve_out: i128,
+}
+
+/// Abort unless every snapshotted pool is still within `tolerance_bps` of
+/// the reserves the caller expected
+///
+/// `legs` are all the swap legs about to run. Called before any swap, so a
+/// pool moved by a front-running trade stops the whole execution rather
+/// than just the leg that trades it.
+pub fn check_snapshots(
+    env: &Env,
+    legs: &Vec<SwapLeg>,
+    snapshots: &Vec<ReserveSnapshot>,
+    tolerance_bps: u32,
+) -> Result<(), Error> {
+    if tolerance_bps > 10000 {
+        return Err(Error::InvalidReserveTolerance);
+    }
+
+    for snapshot in snapshots.iter() {
+        let (leg, split) = find_pool(legs, &snapshot.pool).ok_or(Error::SnapshotPoolNotInRoute)?;
+        let (reserve_in, reserve_out) = get_dex_reserves(
+            env,
+            split.dex_type,
+            &split.pool,
+            &leg.token_in,
+            &leg.token_out,
+        )?;
+
+        if !within_band(snapshot.reserve_in, reserve_in, tolerance_bps)?
+            || !within_band(snapshot.reserve_out, reserve_out, tolerance_bps)?
+        {
+            return Err(Error::ReservesMoved);
+        }
+    }
+
+    Ok(())
+}
+
+/// Leg and split that trade `pool`
+fn find_pool(legs: &Vec<SwapLeg>, pool: &Address) -> Option<(SwapLeg, PoolSplit)> {
+    for leg in legs.iter() {
+        for split in leg.splits.iter() {
+            if &split.pool == pool {
+                return Some((leg, split));
+            }
+        }
+    }
+    None
+}
+
+/// Whether `live` differs from `expected` by at most `tolerance_bps` of `expected`
+fn within_band(expected: i128, live: i128, tolerance_bps: u32) -> Result<bool, Error> {
+    let deviation = live.checked_sub(expected)
+        .ok_or(Error::ArithmeticOverflow)?
+        .checked_abs()
+        .ok_or(Error::ArithmeticOverflow)?
+        .checked_mul(10000)
+        .ok_or(Error::ArithmeticOverflow)?;
+    let band = expected.checked_mul(tolerance_bps as i128)
+        .ok_or(Error::ArithmeticOverflow)?;
+
+    Ok(deviation <= band)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::vec;
+    use crate::dex_interface::DexType;
+    use crate::mocks::soroswap::{MockSoroswapPair, MockSoroswapPairClient};
+
+    #[test]
+    fn test_within_band() {
+        // 1% of 1,000,000 either way
+        assert_eq!(within_band(1_000_000, 1_010_000, 100), Ok(true));
+        assert_eq!(within_band(1_000_000, 990_000, 100), Ok(true));
+        assert_eq!(within_band(1_000_000, 1_010_001, 100), Ok(false));
+        assert_eq!(within_band(1_000_000, 1_000_000, 0), Ok(true));
+        assert_eq!(within_band(1_000_000, 999_999, 0), Ok(false));
+    }
+
+    #[test]
+    fn test_moved_pool_aborts() {
+        let env = Env::default();
+        let usdc = Address::generate(&env);
+        let xlm = Address::generate(&env);
+        let pair = |reserve_usdc: i128, reserve_xlm: i128| {
+            let pair = env.register_contract(None, MockSoroswapPair);
+            MockSoroswapPairClient::new(&env, &pair).init(&usdc, &xlm, &reserve_usdc, &reserve_xlm);
+            pair
+        };
+        let pool_a = pair(1_000_000, 2_000_000);
+        let pool_b = pair(1_000_000, 2_100_000);
+
+        let leg = |token_in: &Address, token_out: &Address, pool: &Address| SwapLeg {
+            token_in: token_in.clone(),
+            token_out: token_out.clone(),
+            splits: vec![&env, PoolSplit { dex_type: DexType::Soroswap, pool: pool.clone(), fraction_bps: 10000 }],
+        };
+        let legs = vec![&env, leg(&usdc, &xlm, &pool_a), leg(&xlm, &usdc, &pool_b)];
+        let snapshot = |pool: &Address, reserve_in: i128, reserve_out: i128| ReserveSnapshot {
+            pool: pool.clone(),
+            reserve_in,
+            reserve_out,
+        };
+
+        // Second leg sells XLM, so its reserves read (XLM, USDC)
+        let expected = vec![&env, snapshot(&pool_a, 1_000_000, 2_000_000), snapshot(&pool_b, 2_100_000, 1_000_000)];
+        assert_eq!(check_snapshots(&env, &legs, &expected, 0), Ok(()));
+
+        // Pool B was priced at 2,000,000 XLM; 5% away passes a 5% band only
+        let stale = vec![&env, snapshot(&pool_b, 2_000_000, 1_000_000)];
+        assert_eq!(check_snapshots(&env, &legs, &stale, 100), Err(Error::ReservesMoved));
+        assert_eq!(check_snapshots(&env, &legs, &stale, 500), Ok(()));
+
+        let unknown = vec![&env, snapshot(&Address::generate(&env), 1, 1)];
+        assert_eq!(check_snapshots(&env, &legs, &unknown, 100), Err(Error::SnapshotPoolNotInRoute));
+        assert_eq!(check_snapshots(&env, &legs, &expected, 10_001), Err(Error::InvalidReserveTolerance));
+    }
+}
@@ -0,0 +1,260 @@
+use soroban_sdk::{contracttype, Env, Vec};
+use crate::arbitrage::{ArbitrageExecutor, ArbitrageParams, ArbitrageRoute, SwapLeg};
+use crate::errors::Error;
+use crate::flash_loan_provider::BorrowLeg;
+use crate::reserve_guard::ReserveSnapshot;
+
+const REGISTERED_ROUTE: &str = "route";
+const ROUTE_IDS: &str = "route_ids";
+const ROUTE_COUNTER: &str = "route_counter";
+
+/// Route id used for ad-hoc routes that were not registered
+pub const AD_HOC_ROUTE_ID: u32 = 0;
+
+/// Route registered by the owner for executors to run by id
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegisteredRoute {
+    pub id: u32,
+    pub swaps: Vec<SwapLeg>, // Pools, tokens and DEX types of the cycle
+    pub min_profit_bps: u32, // Default, may be overridden per call
+    pub max_slippage_bps: u32, // Default, may be overridden per call
+    pub enabled: bool,
+}
+
+/// Per-call adjustments to a registered route
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RouteOverrides {
+    pub min_profit_bps: Option<u32>,
+    pub max_slippage_bps: Option<u32>,
+    pub expected_outputs: Vec<i128>,
+    pub soft_fail: bool,
+    pub not_before_ledger: Option<u32>,
+    pub valid_until_ledger: Option<u32>,
+    pub deadline: Option<u64>,
+    pub expected_reserves: Vec<ReserveSnapshot>,
+    pub reserve_tolerance_bps: u32,
+}
+
+/// Flash loan `data` running a registered route
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RouteCall {
+    pub legs: Vec<BorrowLeg>, // Lenders backing the trade, outermost first
+    pub route_id: u32,
+    pub amount: Option<i128>, // Trade size, at most the loan; the whole loan if None
+    pub overrides: RouteOverrides,
+}
+
+/// Registered route with `id`, if any
+pub fn get_route(env: &Env, id: u32) -> Option<RegisteredRoute> {
+    let key = (REGISTERED_ROUTE, id);
+    env.storage().persistent().get(&key)
+}
+
+/// Every registered route, enabled or not, in registration order
+pub fn list_routes(env: &Env) -> Vec<RegisteredRoute> {
+    let mut routes: Vec<RegisteredRoute> = Vec::new(env);
+    for id in route_ids(env).iter() {
+        if let Some(route) = get_route(env, id) {
+            routes.push_back(route);
+        }
+    }
+    routes
+}
+
+/// Validate and store a new route under a fresh id (owner only)
+pub fn register_route(
+    env: &Env,
+    swaps: Vec<SwapLeg>,
+    min_profit_bps: u32,
+    max_slippage_bps: u32,
+) -> Result<u32, Error> {
+    let id = next_route_id(env)?;
+    let route = RegisteredRoute { id, swaps, min_profit_bps, max_slippage_bps, enabled: true };
+    validate(env, &route)?;
+
+    set_route(env, &route);
+    let mut ids = route_ids(env);
+    ids.push_back(id);
+    env.storage().instance().set(&ROUTE_IDS, &ids);
+
+    Ok(id)
+}
+
+/// Replace the legs and default thresholds of a route (owner only)
+pub fn update_route(
+    env: &Env,
+    id: u32,
+    swaps: Vec<SwapLeg>,
+    min_profit_bps: u32,
+    max_slippage_bps: u32,
+) -> Result<(), Error> {
+    let mut route = get_route(env, id).ok_or(Error::RouteNotFound)?;
+    route.swaps = swaps;
+    route.min_profit_bps = min_profit_bps;
+    route.max_slippage_bps = max_slippage_bps;
+    validate(env, &route)?;
+
+    set_route(env, &route);
+    Ok(())
+}
+
+/// Enable or disable a route without forgetting it (owner only)
+pub fn set_route_enabled(env: &Env, id: u32, enabled: bool) -> Result<(), Error> {
+    let mut route = get_route(env, id).ok_or(Error::RouteNotFound)?;
+    route.enabled = enabled;
+    set_route(env, &route);
+    Ok(())
+}
+
+/// Expand a call into the route id and the params it executes with
+pub fn resolve(env: &Env, call: RouteCall) -> Result<(u32, ArbitrageParams), Error> {
+    let route = get_route(env, call.route_id).ok_or(Error::RouteNotFound)?;
+    if !route.enabled {
+        return Err(Error::RouteDisabled);
+    }
+
+    let overrides = call.overrides;
+    let params = ArbitrageParams {
+        legs: call.legs,
+        swaps: route.swaps,
+        amount: call.amount,
+        min_profit_bps: overrides.min_profit_bps.unwrap_or(route.min_profit_bps),
+        max_slippage_bps: overrides.max_slippage_bps.unwrap_or(route.max_slippage_bps),
+        expected_outputs: overrides.expected_outputs,
+        soft_fail: overrides.soft_fail,
+        not_before_ledger: overrides.not_before_ledger,
+        valid_until_ledger: overrides.valid_until_ledger,
+        deadline: overrides.deadline,
+        expected_reserves: overrides.expected_reserves,
+        reserve_tolerance_bps: overrides.reserve_tolerance_bps,
+    };
+
+    Ok((route.id, params))
+}
+
+/// Registered routes must pass the same checks as any executed route
+fn validate(env: &Env, route: &RegisteredRoute) -> Result<(), Error> {
+    let token_borrow = route.swaps.get(0).ok_or(Error::RouteTooShort)?.token_in;
+    let probe = ArbitrageRoute {
+        route_id: route.id,
+        legs: route.swaps.clone(),
+        token_borrow,
+        amount: 1, // Size is chosen per call
+        min_profit_bps: route.min_profit_bps,
+        max_slippage_bps: route.max_slippage_bps,
+        expected_outputs: Vec::new(env),
+        deadline: 0,
+    };
+    ArbitrageExecutor::validate_route(env, &probe)
+}
+
+fn set_route(env: &Env, route: &RegisteredRoute) {
+    let key = (REGISTERED_ROUTE, route.id);
+    env.storage().persistent().set(&key, route);
+}
+
+fn route_ids(env: &Env) -> Vec<u32> {
+    env.storage().instance().get(&ROUTE_IDS).unwrap_or(Vec::new(env))
+}
+
+/// Ids start at 1; 0 is reserved for ad-hoc routes
+fn next_route_id(env: &Env) -> Result<u32, Error> {
+    let current: u32 = env.storage().instance().get(&ROUTE_COUNTER).unwrap_or(AD_HOC_ROUTE_ID);
+    let next = current.checked_add(1).ok_or(Error::ArithmeticOverflow)?;
+    env.storage().instance().set(&ROUTE_COUNTER, &next);
+    Ok(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{vec, Address};
+    use crate::allowlist;
+    use crate::arbitrage::PoolSplit;
+    use crate::dex_interface::DexType;
+    use crate::FlashLoanExecutorContract;
+
+    /// Two-hop cycle through allowlisted pools, starting from `token`
+    fn cycle(env: &Env, token: &Address) -> Vec<SwapLeg> {
+        let intermediate = Address::generate(env);
+        let swap = |token_in: &Address, token_out: &Address| {
+            let pool = Address::generate(env);
+            allowlist::set_pool_allowed(env, &pool, true);
+            SwapLeg {
+                token_in: token_in.clone(),
+                token_out: token_out.clone(),
+                splits: vec![env, PoolSplit { dex_type: DexType::Soroswap, pool, fraction_bps: 10000 }],
+            }
+        };
+        allowlist::set_token_allowed(env, token, true);
+        allowlist::set_token_allowed(env, &intermediate, true);
+        vec![env, swap(token, &intermediate), swap(&intermediate, token)]
+    }
+
+    fn overrides(env: &Env) -> RouteOverrides {
+        RouteOverrides {
+            min_profit_bps: None,
+            max_slippage_bps: Some(20),
+            expected_outputs: Vec::new(env),
+            soft_fail: false,
+            not_before_ledger: None,
+            valid_until_ledger: Some(50),
+            deadline: None,
+            expected_reserves: Vec::new(env),
+            reserve_tolerance_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_register_update_and_disable() {
+        let env = Env::default();
+        let contract = env.register_contract(None, FlashLoanExecutorContract);
+        env.as_contract(&contract, || {
+            let token = Address::generate(&env);
+            let first = register_route(&env, cycle(&env, &token), 10, 50).unwrap();
+            let second = register_route(&env, cycle(&env, &token), 0, 100).unwrap();
+            assert_eq!((first, second), (1, 2));
+            assert_eq!(list_routes(&env).len(), 2);
+
+            // Updates are validated like registrations
+            let mut open = cycle(&env, &token);
+            open.pop_back();
+            assert_eq!(update_route(&env, first, open, 10, 50), Err(Error::RouteTooShort));
+            assert_eq!(update_route(&env, first, cycle(&env, &token), 10, 10_001), Err(Error::InvalidSlippage));
+            assert_eq!(update_route(&env, 9, cycle(&env, &token), 10, 50), Err(Error::RouteNotFound));
+            update_route(&env, first, cycle(&env, &token), 25, 50).unwrap();
+            assert_eq!(get_route(&env, first).unwrap().min_profit_bps, 25);
+
+            set_route_enabled(&env, second, false).unwrap();
+            let call = RouteCall { legs: Vec::new(&env), route_id: second, amount: None, overrides: overrides(&env) };
+            assert_eq!(resolve(&env, call), Err(Error::RouteDisabled));
+
+            // Disabled routes are still listed
+            assert_eq!(list_routes(&env).len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_resolve_applies_overrides() {
+        let env = Env::default();
+        let contract = env.register_contract(None, FlashLoanExecutorContract);
+        env.as_contract(&contract, || {
+            let token = Address::generate(&env);
+            let swaps = cycle(&env, &token);
+            let id = register_route(&env, swaps.clone(), 10, 50).unwrap();
+
+            let call = RouteCall { legs: Vec::new(&env), route_id: id, amount: Some(400), overrides: overrides(&env) };
+            let (route_id, params) = resolve(&env, call).unwrap();
+            assert_eq!(route_id, id);
+            assert_eq!(params.swaps, swaps);
+            assert_eq!(params.amount, Some(400));
+            assert_eq!(params.min_profit_bps, 10);
+            assert_eq!(params.max_slippage_bps, 20);
+            assert_eq!(params.valid_until_ledger, Some(50));
+        });
+    }
+}
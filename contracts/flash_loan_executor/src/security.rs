@@ -4,6 +4,7 @@ use crate::errors::Error;
 const REENTRANCY_KEY: &str = "reentrancy_guard";
 const OWNER_KEY: &str = "owner";
 const PAUSED_KEY: &str = "paused";
+const EXECUTOR_KEY: &str = "executor";
 
 /// Reentrancy guard implementation
 pub struct ReentrancyGuard<'a> {
@@ -47,6 +48,32 @@ pub fn require_owner(env: &Env, caller: &Address) -> Result<(), Error> {
     Ok(())
 }
 
+/// Check the caller is the owner or an authorized executor
+pub fn require_executor(env: &Env, caller: &Address) -> Result<(), Error> {
+    let key = (EXECUTOR_KEY, caller);
+    if env.storage().persistent().has(&key) {
+        return Ok(());
+    }
+    
+    require_owner(env, caller)
+}
+
+/// Whether `executor` may run registered routes
+pub fn is_executor(env: &Env, executor: &Address) -> bool {
+    let key = (EXECUTOR_KEY, executor);
+    env.storage().persistent().has(&key)
+}
+
+/// Authorize or revoke an executor (owner only)
+pub fn set_executor(env: &Env, executor: &Address, authorized: bool) {
+    let key = (EXECUTOR_KEY, executor);
+    if authorized {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
 /// Set the contract owner (only during initialization)
 pub fn set_owner(env: &Env, owner: &Address) {
     env.storage().instance().set(&OWNER_KEY, owner);
@@ -79,6 +106,36 @@ pub fn unpause(env: &Env) {
     env.storage().instance().set(&PAUSED_KEY, &false);
 }
 
+/// Check the current ledger falls inside an optional execution window
+/// 
+/// Both bounds are inclusive ledger sequence numbers.
+pub fn check_ledger_window(
+    env: &Env,
+    not_before_ledger: Option<u32>,
+    valid_until_ledger: Option<u32>,
+) -> Result<(), Error> {
+    let sequence = env.ledger().sequence();
+    
+    if not_before_ledger.is_some_and(|first| sequence < first) {
+        return Err(Error::OpportunityNotYetValid);
+    }
+    
+    if valid_until_ledger.is_some_and(|last| sequence > last) {
+        return Err(Error::OpportunityExpired);
+    }
+    
+    Ok(())
+}
+
+/// Check the current ledger timestamp has not passed an optional deadline
+pub fn check_deadline(env: &Env, deadline: Option<u64>) -> Result<(), Error> {
+    if deadline.is_some_and(|deadline| env.ledger().timestamp() > deadline) {
+        return Err(Error::OpportunityExpired);
+    }
+    
+    Ok(())
+}
+
 /// Validate slippage tolerance
 /// Returns true if actual_output meets minimum requirements
 pub fn check_slippage(
@@ -86,10 +143,14 @@ pub fn check_slippage(
     actual_output: i128,
     max_slippage_bps: u32, // basis points (1 bps = 0.01%)
 ) -> Result<(), Error> {
+    if max_slippage_bps > 10000 {
+        return Err(Error::InvalidSlippage);
+    }
+    
     // Calculate minimum acceptable output
     // min_output = expected * (10000 - slippage_bps) / 10000
     let min_output = expected_output
-        .checked_mul((10000 - max_slippage_bps as i128))
+        .checked_mul(10000 - max_slippage_bps as i128)
         .ok_or(Error::ArithmeticOverflow)?
         .checked_div(10000)
         .ok_or(Error::ArithmeticOverflow)?;
@@ -129,6 +190,22 @@ mod tests {
         
         // Actual 989 should fail
         assert!(check_slippage(1000, 989, 100).is_err());
+        
+        // Tolerance above 100% is rejected rather than accepting anything
+        assert_eq!(check_slippage(1000, 0, 10_001), Err(Error::InvalidSlippage));
+    }
+
+    #[test]
+    fn test_ledger_window() {
+        use soroban_sdk::testutils::Ledger;
+        
+        let env = Env::default();
+        env.ledger().with_mut(|ledger| ledger.sequence_number = 100);
+        
+        assert_eq!(check_ledger_window(&env, None, None), Ok(()));
+        assert_eq!(check_ledger_window(&env, Some(100), Some(100)), Ok(()));
+        assert_eq!(check_ledger_window(&env, Some(101), None), Err(Error::OpportunityNotYetValid));
+        assert_eq!(check_ledger_window(&env, None, Some(99)), Err(Error::OpportunityExpired));
     }
 
     #[test]
@@ -0,0 +1,239 @@
+use soroban_sdk::{contracttype, Address, Env};
+use crate::arbitrage::{ArbitrageExecutor, ArbitrageResult, ArbitrageRoute, RouteExecution, SkippedRoute};
+use crate::errors::Error;
+
+const ROUTE_STATS: &str = "route_stats";
+const PAIR_STATS: &str = "pair_stats";
+
+/// Cumulative performance of a route or a DEX pair
+/// 
+/// Profit figures of a pair are those of the routes that traded through
+/// it, so a pair appearing in losing routes shows it.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TradeStats {
+    pub executions: u32,
+    pub skipped: u32, // Soft-fail skips, which still paid the flash fee
+    pub volume: i128, // Borrowed amount for routes, amount swapped in for pairs
+    pub gross_profit: i128,
+    pub net_profit: i128,
+    pub fees_paid: i128,
+    pub best_profit: i128, // Highest net profit of a single execution
+    pub worst_profit: i128, // Lowest net profit of a single execution
+    pub last_ledger: u32,
+}
+
+impl TradeStats {
+    fn record_executed(
+        &mut self,
+        env: &Env,
+        volume: i128,
+        result: &ArbitrageResult,
+    ) -> Result<(), Error> {
+        if self.executions == 0 {
+            self.best_profit = result.net_profit;
+            self.worst_profit = result.net_profit;
+        } else {
+            self.best_profit = self.best_profit.max(result.net_profit);
+            self.worst_profit = self.worst_profit.min(result.net_profit);
+        }
+
+        self.executions = self.executions.checked_add(1).ok_or(Error::ArithmeticOverflow)?;
+        self.volume = self.volume.checked_add(volume).ok_or(Error::ArithmeticOverflow)?;
+        self.gross_profit = self.gross_profit.checked_add(result.gross_profit)
+            .ok_or(Error::ArithmeticOverflow)?;
+        self.net_profit = self.net_profit.checked_add(result.net_profit)
+            .ok_or(Error::ArithmeticOverflow)?;
+        self.fees_paid = self.fees_paid.checked_add(result.fees_paid)
+            .ok_or(Error::ArithmeticOverflow)?;
+        self.last_ledger = env.ledger().sequence();
+
+        Ok(())
+    }
+
+    fn record_skipped(&mut self, env: &Env, skipped: &SkippedRoute) -> Result<(), Error> {
+        self.skipped = self.skipped.checked_add(1).ok_or(Error::ArithmeticOverflow)?;
+        self.net_profit = self.net_profit.checked_sub(skipped.flash_fee)
+            .ok_or(Error::ArithmeticOverflow)?;
+        self.fees_paid = self.fees_paid.checked_add(skipped.flash_fee)
+            .ok_or(Error::ArithmeticOverflow)?;
+        self.last_ledger = env.ledger().sequence();
+
+        Ok(())
+    }
+}
+
+/// Statistics of the route with `route_id` (ad-hoc routes share id 0)
+pub fn get_route_stats(env: &Env, route_id: u32) -> TradeStats {
+    let key = (ROUTE_STATS, route_id);
+    env.storage().persistent().get(&key).unwrap_or_default()
+}
+
+/// Statistics of the DEX pair (pool) at `pool`
+pub fn get_pair_stats(env: &Env, pool: &Address) -> TradeStats {
+    let key = (PAIR_STATS, pool);
+    env.storage().persistent().get(&key).unwrap_or_default()
+}
+
+/// Forget the statistics of a route (owner only)
+pub fn reset_route_stats(env: &Env, route_id: u32) {
+    let key = (ROUTE_STATS, route_id);
+    env.storage().persistent().remove(&key);
+}
+
+/// Forget the statistics of a DEX pair (owner only)
+pub fn reset_pair_stats(env: &Env, pool: &Address) {
+    let key = (PAIR_STATS, pool);
+    env.storage().persistent().remove(&key);
+}
+
+/// Add the outcome of `route` to its statistics and those of its pairs
+/// 
+/// Skipped routes never reached the pools, so only the route is charged
+/// for their fee.
+pub fn record(env: &Env, route: &ArbitrageRoute, execution: &RouteExecution) -> Result<(), Error> {
+    let mut stats = get_route_stats(env, route.route_id);
+
+    match execution {
+        RouteExecution::Executed(result) => {
+            stats.record_executed(env, route.amount, result)?;
+
+            // Each leg trades what the previous one returned
+            let mut amount_in = result.amount_in;
+            for (i, leg) in route.legs.iter().enumerate() {
+                let amounts = ArbitrageExecutor::split_amounts(env, amount_in, &leg.splits)?;
+                for (split, amount) in leg.splits.iter().zip(amounts.iter()) {
+                    let mut pair = get_pair_stats(env, &split.pool);
+                    pair.record_executed(env, amount, result)?;
+                    set_pair_stats(env, &split.pool, &pair);
+                }
+                amount_in = result.leg_outputs.get(i as u32).ok_or(Error::InvalidRoute)?;
+            }
+        }
+        RouteExecution::Skipped(skipped) => stats.record_skipped(env, skipped)?,
+    }
+
+    let key = (ROUTE_STATS, route.route_id);
+    env.storage().persistent().set(&key, &stats);
+
+    Ok(())
+}
+
+fn set_pair_stats(env: &Env, pool: &Address, stats: &TradeStats) {
+    let key = (PAIR_STATS, pool);
+    env.storage().persistent().set(&key, stats);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{vec, Vec};
+    use crate::arbitrage::{PoolSplit, SkipReason, SwapLeg};
+    use crate::dex_interface::DexType;
+    use crate::FlashLoanExecutorContract;
+
+    fn result(leg_outputs: Vec<i128>, net_profit: i128) -> RouteExecution {
+        RouteExecution::Executed(ArbitrageResult {
+            success: true,
+            amount_in: 1_000,
+            amount_out: leg_outputs.last().unwrap(),
+            leg_outputs,
+            gross_profit: net_profit + 10,
+            net_profit,
+            fees_paid: 10,
+        })
+    }
+
+    /// Route selling through two pools and buying back through a third
+    fn route(env: &Env, pools: &[Address; 3]) -> ArbitrageRoute {
+        let token = Address::generate(env);
+        let intermediate = Address::generate(env);
+        let split = |pool: &Address, fraction_bps: u32| PoolSplit {
+            dex_type: DexType::Soroswap,
+            pool: pool.clone(),
+            fraction_bps,
+        };
+        ArbitrageRoute {
+            route_id: 7,
+            legs: vec![
+                env,
+                SwapLeg {
+                    token_in: token.clone(),
+                    token_out: intermediate.clone(),
+                    splits: vec![env, split(&pools[0], 6000), split(&pools[1], 4000)],
+                },
+                SwapLeg {
+                    token_in: intermediate,
+                    token_out: token.clone(),
+                    splits: vec![env, split(&pools[2], 10000)],
+                },
+            ],
+            token_borrow: token,
+            amount: 1_000,
+            min_profit_bps: 0,
+            max_slippage_bps: 100,
+            expected_outputs: Vec::new(env),
+            deadline: 0,
+        }
+    }
+
+    #[test]
+    fn test_record_tracks_best_worst_and_skips() {
+        let env = Env::default();
+        let contract = env.register_contract(None, FlashLoanExecutorContract);
+        let pools = [Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+        let route = route(&env, &pools);
+
+        env.as_contract(&contract, || {
+            record(&env, &route, &result(vec![&env, 2_000, 1_050], 40)).unwrap();
+            env.ledger().with_mut(|ledger| ledger.sequence_number = 12);
+            record(&env, &route, &result(vec![&env, 2_000, 1_005], -5)).unwrap();
+            record(&env, &route, &RouteExecution::Skipped(SkippedRoute {
+                route_id: 7,
+                reason: SkipReason::NoProfit,
+                amount_in: 1_000,
+                leg_outputs: Vec::new(&env),
+                amount_out: 0,
+                flash_fee: 10,
+                expected_profit: -10,
+                min_profit: 0,
+            })).unwrap();
+
+            let stats = get_route_stats(&env, 7);
+            assert_eq!(stats.executions, 2);
+            assert_eq!(stats.skipped, 1);
+            assert_eq!(stats.volume, 2_000);
+            assert_eq!(stats.gross_profit, 55);
+            assert_eq!(stats.net_profit, 25);
+            assert_eq!(stats.fees_paid, 30);
+            assert_eq!((stats.best_profit, stats.worst_profit), (40, -5));
+            assert_eq!(stats.last_ledger, 12);
+
+            reset_route_stats(&env, 7);
+            assert_eq!(get_route_stats(&env, 7), TradeStats::default());
+        });
+    }
+
+    #[test]
+    fn test_record_splits_volume_between_pairs() {
+        let env = Env::default();
+        let contract = env.register_contract(None, FlashLoanExecutorContract);
+        let pools = [Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+        let route = route(&env, &pools);
+
+        env.as_contract(&contract, || {
+            record(&env, &route, &result(vec![&env, 2_000, 1_050], 40)).unwrap();
+
+            // First leg is split 60/40, the second trades the first's output
+            assert_eq!(get_pair_stats(&env, &pools[0]).volume, 600);
+            assert_eq!(get_pair_stats(&env, &pools[1]).volume, 400);
+            let pair = get_pair_stats(&env, &pools[2]);
+            assert_eq!(pair.volume, 2_000);
+            assert_eq!((pair.executions, pair.net_profit), (1, 40));
+
+            reset_pair_stats(&env, &pools[2]);
+            assert_eq!(get_pair_stats(&env, &pools[2]).executions, 0);
+        });
+    }
+}
@@ -0,0 +1,368 @@
+use soroban_sdk::{token, Address, Bytes, Env};
+use crate::errors::Error;
+use crate::events;
+use crate::flash_loan::{DebtLedger, FlashLoanContext, FlashLoanReceiverClient};
+use crate::flash_loan_provider::FlashLoanProviderType;
+use crate::security::ReentrancyGuard;
+
+const VAULT_ASSETS: &str = "vault_assets";
+const VAULT_SHARES: &str = "vault_shares";
+const VAULT_TOTAL_SHARES: &str = "vault_total";
+const VAULT_FEE_BPS: &str = "vault_fee_bps";
+
+/// Liquidity Vault
+///
+/// Lets the executor act as a flash loan lender backed by deposited
+/// inventory. Depositors hold shares of each token's pool; flash loan fees
+/// are added to the pool without minting shares, so they accrue to
+/// depositors pro rata.
+///
+/// Pool assets are tracked separately from the contract's token balance,
+/// which also holds arbitrage profit that must never be lent out.
+pub struct LiquidityVault;
+
+impl LiquidityVault {
+    /// Deposit `amount` of `token` and mint shares to `depositor`
+    pub fn deposit(
+        env: &Env,
+        depositor: &Address,
+        token: &Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
+
+        let total_assets = Self::total_assets(env, token);
+        let total_shares = Self::total_shares(env, token);
+
+        // First depositor sets the share price at 1:1
+        let shares = if total_shares == 0 || total_assets == 0 {
+            amount
+        } else {
+            amount.checked_mul(total_shares)
+                .ok_or(Error::ArithmeticOverflow)?
+                .checked_div(total_assets)
+                .ok_or(Error::ArithmeticOverflow)?
+        };
+
+        if shares <= 0 {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
+
+        let token_client = token::Client::new(env, token);
+        token_client.transfer(depositor, &env.current_contract_address(), &amount);
+
+        let new_assets = total_assets.checked_add(amount)
+            .ok_or(Error::ArithmeticOverflow)?;
+        let new_total_shares = total_shares.checked_add(shares)
+            .ok_or(Error::ArithmeticOverflow)?;
+        let new_shares = Self::shares_of(env, token, depositor).checked_add(shares)
+            .ok_or(Error::ArithmeticOverflow)?;
+
+        Self::set_total_assets(env, token, new_assets);
+        Self::set_total_shares(env, token, new_total_shares);
+        Self::set_shares(env, token, depositor, new_shares);
+
+        events::emit_liquidity_deposited(env, depositor.clone(), token.clone(), amount, shares);
+
+        Ok(shares)
+    }
+
+    /// Burn `shares` of `depositor` and return the underlying tokens
+    pub fn withdraw(
+        env: &Env,
+        depositor: &Address,
+        token: &Address,
+        shares: i128,
+    ) -> Result<i128, Error> {
+        let owned = Self::shares_of(env, token, depositor);
+        if shares <= 0 || shares > owned {
+            return Err(Error::InvalidWithdrawAmount);
+        }
+
+        let total_assets = Self::total_assets(env, token);
+        let total_shares = Self::total_shares(env, token);
+
+        let amount = shares.checked_mul(total_assets)
+            .ok_or(Error::ArithmeticOverflow)?
+            .checked_div(total_shares)
+            .ok_or(Error::ArithmeticOverflow)?;
+
+        Self::set_total_assets(env, token, total_assets - amount);
+        Self::set_total_shares(env, token, total_shares - shares);
+        Self::set_shares(env, token, depositor, owned - shares);
+
+        let token_client = token::Client::new(env, token);
+        token_client.transfer(&env.current_contract_address(), depositor, &amount);
+
+        events::emit_liquidity_withdrawn(env, depositor.clone(), token.clone(), amount, shares);
+
+        Ok(amount)
+    }
+
+    /// Lend pool liquidity to `receiver` and enforce repayment
+    ///
+    /// Flow:
+    /// 1. Transfer `amount` to the receiver
+    /// 2. Call the receiver's `on_flash_loan` callback
+    /// 3. Check the contract balance grew back by at least the fee
+    /// 4. Credit the fee to the pool
+    pub fn flash_loan(
+        env: &Env,
+        initiator: &Address,
+        receiver: &Address,
+        token: &Address,
+        amount: i128,
+        data: &Bytes,
+    ) -> Result<(), Error> {
+        let _guard = ReentrancyGuard::enter(env)?;
+
+        let lender = env.current_contract_address();
+        if receiver == &lender {
+            // Soroban would reject the callback as contract re-entry
+            return Err(Error::InvalidReceiver);
+        }
+
+        if amount <= 0 {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
+
+        let total_assets = Self::total_assets(env, token);
+        if amount > total_assets {
+            return Err(Error::InsufficientLiquidity);
+        }
+
+        let fee = Self::flash_fee(env, amount)?;
+        let mut context = FlashLoanContext {
+            provider: FlashLoanProviderType::Erc3156,
+            pool: lender.clone(),
+            token: token.clone(),
+            borrowed_amount: amount,
+            fee,
+            repay_amount: amount.checked_add(fee).ok_or(Error::ArithmeticOverflow)?,
+            is_repaid: false,
+        };
+
+        let token_client = token::Client::new(env, token);
+        let balance_before = token_client.balance(&lender);
+
+        token_client.transfer(&lender, receiver, &amount);
+        events::emit_flash_loan_started(env, token.clone(), amount, receiver.clone());
+
+        FlashLoanReceiverClient::new(env, receiver)
+            .on_flash_loan(initiator, token, &amount, &fee, data);
+
+        // Receiver must have sent back principal + fee
+        let balance_after = token_client.balance(&lender);
+        let repaid = balance_after.checked_sub(balance_before)
+            .ok_or(Error::ArithmeticOverflow)?
+            .checked_add(amount)
+            .ok_or(Error::ArithmeticOverflow)?;
+
+        if !context.can_repay(repaid) {
+            return Err(Error::RepaymentFailed);
+        }
+        context.is_repaid = true;
+
+        let new_assets = total_assets.checked_add(context.fee)
+            .ok_or(Error::ArithmeticOverflow)?;
+        Self::set_total_assets(env, token, new_assets);
+
+        events::emit_flash_loan_repaid(env, token.clone(), context.borrowed_amount, context.fee);
+
+        Ok(())
+    }
+
+    /// Lend pool liquidity to one of this contract's own entry points
+    ///
+    /// The vault cannot call back into the contract hosting it, so the
+    /// tokens stay where they are and only the debt is recorded. It is
+    /// settled by `repay` once the route has run.
+    pub fn borrow(env: &Env, token: &Address, amount: i128) -> Result<FlashLoanContext, Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidFlashLoanAmount);
+        }
+        if amount > Self::total_assets(env, token) {
+            return Err(Error::InsufficientLiquidity);
+        }
+
+        let lender = env.current_contract_address();
+        let fee = Self::flash_fee(env, amount)?;
+        let context = FlashLoanContext {
+            provider: FlashLoanProviderType::Erc3156,
+            pool: lender.clone(),
+            token: token.clone(),
+            borrowed_amount: amount,
+            fee,
+            repay_amount: amount.checked_add(fee).ok_or(Error::ArithmeticOverflow)?,
+            is_repaid: false,
+        };
+        DebtLedger::register(env, &context)?;
+
+        events::emit_flash_loan_started(env, token.clone(), amount, lender);
+
+        Ok(context)
+    }
+
+    /// Settle a loan opened by `borrow`, crediting its fee to depositors
+    ///
+    /// The principal must be back, i.e. the contract holds at least
+    /// `balance_before`, its balance when the loan was opened. The fee is
+    /// moved from the contract's own share of the balance into the pool, so
+    /// the caller books it against profit like any other lender's fee.
+    pub fn repay(
+        env: &Env,
+        context: &mut FlashLoanContext,
+        balance_before: i128,
+    ) -> Result<(), Error> {
+        let balance_after = token::Client::new(env, &context.token)
+            .balance(&env.current_contract_address());
+        if balance_after < balance_before {
+            return Err(Error::RepaymentFailed);
+        }
+
+        let new_assets = Self::total_assets(env, &context.token).checked_add(context.fee)
+            .ok_or(Error::ArithmeticOverflow)?;
+        Self::set_total_assets(env, &context.token, new_assets);
+
+        context.is_repaid = true;
+        DebtLedger::clear(env, context)?;
+
+        events::emit_flash_loan_repaid(env, context.token.clone(), context.borrowed_amount, context.fee);
+
+        Ok(())
+    }
+
+    /// Fee charged on a flash loan of `amount`, rounded up in the vault's favour
+    pub fn flash_fee(env: &Env, amount: i128) -> Result<i128, Error> {
+        let fee_bps = Self::fee_bps(env) as i128;
+        amount.checked_mul(fee_bps)
+            .ok_or(Error::ArithmeticOverflow)?
+            .checked_add(9999)
+            .ok_or(Error::ArithmeticOverflow)?
+            .checked_div(10000)
+            .ok_or(Error::ArithmeticOverflow)
+    }
+
+    /// Flash fee in basis points (defaults to 0)
+    pub fn fee_bps(env: &Env) -> u32 {
+        env.storage().instance().get(&VAULT_FEE_BPS).unwrap_or(0)
+    }
+
+    /// Update the flash fee (owner only)
+    pub fn set_fee_bps(env: &Env, fee_bps: u32) -> Result<(), Error> {
+        if fee_bps > 10000 {
+            return Err(Error::InvalidFee);
+        }
+        env.storage().instance().set(&VAULT_FEE_BPS, &fee_bps);
+        Ok(())
+    }
+
+    /// Pool liquidity available for lending, including accrued fees
+    pub fn total_assets(env: &Env, token: &Address) -> i128 {
+        let key = (VAULT_ASSETS, token);
+        env.storage().persistent().get(&key).unwrap_or(0)
+    }
+
+    /// Shares held by `depositor` in the `token` pool
+    pub fn shares_of(env: &Env, token: &Address, depositor: &Address) -> i128 {
+        let key = (VAULT_SHARES, token, depositor);
+        env.storage().persistent().get(&key).unwrap_or(0)
+    }
+
+    fn total_shares(env: &Env, token: &Address) -> i128 {
+        let key = (VAULT_TOTAL_SHARES, token);
+        env.storage().persistent().get(&key).unwrap_or(0)
+    }
+
+    fn set_total_assets(env: &Env, token: &Address, amount: i128) {
+        let key = (VAULT_ASSETS, token);
+        env.storage().persistent().set(&key, &amount);
+    }
+
+    fn set_total_shares(env: &Env, token: &Address, shares: i128) {
+        let key = (VAULT_TOTAL_SHARES, token);
+        env.storage().persistent().set(&key, &shares);
+    }
+
+    fn set_shares(env: &Env, token: &Address, depositor: &Address, shares: i128) {
+        let key = (VAULT_SHARES, token, depositor);
+        env.storage().persistent().set(&key, &shares);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::Bytes;
+    use soroban_sdk::{token, Address, Env};
+    use crate::{FlashLoanExecutorContract, FlashLoanExecutorContractClient};
+    use crate::mocks::create_token;
+    use crate::mocks::flash_receiver::{MockFlashReceiver, MockFlashReceiverClient};
+
+    fn setup(env: &Env) -> (FlashLoanExecutorContractClient<'_>, Address, Address, Address) {
+        env.mock_all_auths();
+
+        let owner = Address::generate(env);
+        let depositor = Address::generate(env);
+        let executor = env.register_contract(None, FlashLoanExecutorContract);
+        let client = FlashLoanExecutorContractClient::new(env, &executor);
+        client.initialize(&owner);
+
+        let token = create_token(env, &owner, &[(&depositor, 1_000_000)]);
+
+        (client, owner, depositor, token)
+    }
+
+    #[test]
+    fn test_flash_fee_accrues_to_depositors() {
+        let env = Env::default();
+        let (client, owner, depositor, token) = setup(&env);
+        client.set_flash_fee(&owner, &30);
+
+        assert_eq!(client.deposit_liquidity(&depositor, &token, &1_000_000), 1_000_000);
+        assert_eq!(client.max_flash_loan(&token), 1_000_000);
+        assert_eq!(client.flash_fee(&token, &500_000), 1_500);
+
+        // Receiver is pre-funded with enough to pay the fee
+        let receiver = env.register_contract(None, MockFlashReceiver);
+        MockFlashReceiverClient::new(&env, &receiver).init(&client.address, &true);
+        token::StellarAssetClient::new(&env, &token).mint(&receiver, &1_500);
+
+        client.flash_loan(&owner, &receiver, &token, &500_000, &Bytes::new(&env));
+        assert_eq!(client.max_flash_loan(&token), 1_001_500);
+
+        // Withdrawing every share returns principal plus the fee
+        assert_eq!(client.withdraw_liquidity(&depositor, &token, &1_000_000), 1_001_500);
+        assert_eq!(token::Client::new(&env, &token).balance(&depositor), 1_001_500);
+    }
+
+    #[test]
+    fn test_unrepaid_flash_loan_reverts() {
+        let env = Env::default();
+        let (client, owner, depositor, token) = setup(&env);
+        client.deposit_liquidity(&depositor, &token, &1_000_000);
+
+        let receiver = env.register_contract(None, MockFlashReceiver);
+        MockFlashReceiverClient::new(&env, &receiver).init(&client.address, &false);
+
+        let result = client.try_flash_loan(&owner, &receiver, &token, &500_000, &Bytes::new(&env));
+        assert!(result.is_err());
+        assert_eq!(token::Client::new(&env, &token).balance(&client.address), 1_000_000);
+
+        // Cannot lend more than was deposited
+        let result = client.try_flash_loan(&owner, &receiver, &token, &1_000_001, &Bytes::new(&env));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_set_flash_fee_is_owner_only_and_bounded() {
+        let env = Env::default();
+        let (client, owner, depositor, _token) = setup(&env);
+
+        assert!(client.try_set_flash_fee(&depositor, &10).is_err());
+        assert!(client.try_set_flash_fee(&owner, &10_001).is_err());
+        assert!(client.try_set_flash_fee(&owner, &10).is_ok());
+    }
+}